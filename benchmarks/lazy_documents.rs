@@ -0,0 +1,74 @@
+//! Lazy document loading memory benchmark
+//!
+//! Measures the live heap held by a sled-backed cache after indexing 20k
+//! documents, with documents kept in memory and with lazy document loading.
+//! Allocations are counted by a wrapper around the system allocator, so the
+//! figures cover everything the cache allocates, storage engine included.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use bytesize::ByteSize;
+use tiger_cache::{Document, TigerCache, TigerCacheConfig};
+
+/// Number of documents indexed
+const DOC_COUNT: usize = 20_000;
+
+/// Bytes currently allocated through `CountingAllocator`
+static LIVE_BYTES: AtomicIsize = AtomicIsize::new(0);
+
+/// System allocator that keeps track of the live heap size
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size() as isize, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size() as isize, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Index the documents and return the live heap growth in bytes
+fn measure(lazy_documents: bool) -> isize {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = TigerCacheConfig::new()
+        .with_storage_path(dir.path().join("db"))
+        .with_cache_size(ByteSize::mib(1))
+        .with_lazy_documents(lazy_documents);
+    config.storage.use_compression = false;
+    
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    let mut cache = TigerCache::with_config(config);
+    for i in 0..DOC_COUNT {
+        cache.add_document(
+            Document::new(format!("doc{i}"))
+                .with_field("title", format!("Product number {i} with a descriptive title"))
+                .with_field("body", format!(
+                    "Lorem ipsum dolor sit amet consectetur adipiscing elit item{i} {}",
+                    "extra text ".repeat(20),
+                )),
+        ).unwrap();
+    }
+    let live = LIVE_BYTES.load(Ordering::Relaxed) - before;
+    
+    // Searching still returns full documents either way
+    assert_eq!(cache.search("item42", None).unwrap()[0].document.id, "doc42");
+    live
+}
+
+fn main() {
+    for lazy_documents in [false, true] {
+        let live = measure(lazy_documents);
+        println!(
+            "lazy_documents={lazy_documents}: {:.1} MB live heap after indexing {DOC_COUNT} documents",
+            live as f64 / 1e6,
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Persistence format benchmark
+//!
+//! Compares saving and loading an index file of 100k documents as JSON and
+//! as bincode, and prints the size of each file.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tiger_cache::{Document, PersistenceFormat, TigerCache, TigerCacheConfig};
+
+/// Number of documents indexed
+const DOC_COUNT: usize = 100_000;
+
+/// Words the document fields are made of
+const WORDS: [&str; 20] = [
+    "apple", "banana", "cherry", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+];
+
+/// Build a cache saving in `format`, holding documents with a title and a body
+fn build_cache(format: PersistenceFormat) -> TigerCache {
+    let documents = (0..DOC_COUNT)
+        .map(|i| {
+            Document::new(format!("doc{i}"))
+                .with_field("title", format!("{} {} {} {i}", WORDS[i % 20], WORDS[(i / 20) % 20], WORDS[(i / 400) % 20]))
+                .with_field("body", format!("{} {} item number {}", WORDS[(i * 7) % 20], WORDS[(i * 13) % 20], i % 997))
+        })
+        .collect();
+    
+    let mut cache = TigerCache::with_config(TigerCacheConfig::new().with_persistence_format(format));
+    cache.add_documents_batch(documents).unwrap();
+    cache
+}
+
+fn bench_persistence_format(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    
+    let mut group = c.benchmark_group("persistence_100k");
+    group.sample_size(10);
+    
+    for (name, format) in [("json", PersistenceFormat::Json), ("bincode", PersistenceFormat::Bincode)] {
+        let mut cache = build_cache(format);
+        let path = dir.path().join(format!("index.{name}"));
+        
+        group.bench_function(format!("save_{name}"), |b| {
+            b.iter(|| cache.save_to_file(&path).unwrap())
+        });
+        println!("{name} index file: {} bytes", std::fs::metadata(&path).unwrap().len());
+        
+        group.bench_function(format!("load_{name}"), |b| {
+            b.iter(|| black_box(TigerCache::open(&path).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_persistence_format);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! Range filter benchmark
+//!
+//! Compares resolving a range filter from the sorted numeric index with
+//! checking the price of every matching document, over 100k documents.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tiger_cache::{Document, Filter, SearchOptions, TigerCache, TigerCacheConfig};
+
+/// Number of documents indexed
+const DOC_COUNT: usize = 100_000;
+
+/// Build a cache of products with prices spread evenly from 0 to 999.9
+fn build_cache(config: TigerCacheConfig) -> TigerCache {
+    let documents = (0..DOC_COUNT)
+        .map(|i| {
+            Document::new(format!("product{i}"))
+                .with_field("title", "Product")
+                .with_field("price", (i % 10_000) as f64 / 10.0)
+        })
+        .collect();
+    
+    let mut cache = TigerCache::with_config(config);
+    cache.add_documents_batch(documents).unwrap();
+    cache
+}
+
+fn bench_range_filter(c: &mut Criterion) {
+    // Every document matches the query; 1% of them are in the price range
+    let options = SearchOptions {
+        filters: vec![Filter::range("price", Some(100.0), Some(109.9))],
+        limit: 10,
+        ..Default::default()
+    };
+    
+    let mut group = c.benchmark_group("range_filter_100k");
+    group.sample_size(10);
+    
+    let configs = [
+        ("document_scan", TigerCacheConfig::new()),
+        ("numeric_index", TigerCacheConfig::new().with_numeric_fields(vec!["price".to_string()])),
+    ];
+    for (name, config) in configs {
+        let cache = build_cache(config);
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(cache.search("product", Some(options.clone())).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+criterion_group!(benches, bench_range_filter);
+criterion_main!(benches);
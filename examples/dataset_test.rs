@@ -176,9 +176,7 @@ fn benchmark_indexing(products: &[AmazonProduct], memory_tracker: &mut MemoryTra
     ];
     tiger_cache.set_indexed_fields(indexed_fields);
     
-    // Add documents with progress tracking
-    let total = products.len();
-    for (i, product) in products.iter().enumerate() {
+    let documents = products.iter().map(|product| {
         let mut doc = Document::new(&product.parent_asin);
         
         doc.add_field("title", &product.title)
@@ -186,17 +184,16 @@ fn benchmark_indexing(products: &[AmazonProduct], memory_tracker: &mut MemoryTra
            .add_field("store", &product.store)
            .add_field("main_category", &product.main_category)
            .add_field("details", &product.details);
-        
-        tiger_cache.add_document(doc)?;
-        
-        // Progress and memory tracking
-        if (i + 1) % 5000 == 0 || i + 1 == total {
-            let progress = (i + 1) as f64 / total as f64 * 100.0;
-            let current_memory = memory_tracker.get_memory_usage_mb();
-            println!("  Progress: {:.1}% ({}/{}) - Memory: {:.1} MB", 
-                     progress, i + 1, total, current_memory);
-        }
-    }
+        doc
+    }).collect();
+    
+    // Add documents with progress and memory tracking
+    tiger_cache.add_documents_batch_with_progress(documents, |added, total| {
+        let progress = added as f64 / total as f64 * 100.0;
+        let current_memory = memory_tracker.get_memory_usage_mb();
+        println!("  Progress: {:.1}% ({}/{}) - Memory: {:.1} MB", 
+                 progress, added, total, current_memory);
+    })?;
     
     let index_time = start_time.elapsed();
     println!("✅ Index created in {:.2}s", index_time.as_secs_f64());
@@ -265,6 +262,7 @@ fn benchmark_searches(tiger_cache: &TigerCache) -> Vec<SearchBenchmark> {
                 max_distance: 2,
                 score_threshold: 100, // 0.1 * 1000 as u32
                 limit: 10,
+                ..Default::default()
             };
             
             let start_time = Instant::now();
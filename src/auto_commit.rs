@@ -0,0 +1,91 @@
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, RecvTimeoutError, Sender};
+use parking_lot::Mutex;
+
+/// Background thread running a commit task at a fixed interval
+#[derive(Debug)]
+pub(crate) struct AutoCommitter {
+    /// Time between commits
+    interval: Duration,
+    
+    /// Dropping the sender wakes the thread and makes it exit
+    stop_sender: Mutex<Option<Sender<()>>>,
+    
+    /// Handle of the running thread
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AutoCommitter {
+    /// Create a stopped committer
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            stop_sender: Mutex::new(None),
+            handle: Mutex::new(None),
+        }
+    }
+    
+    /// Start running `commit` every interval, replacing any task already running
+    pub fn start(&self, commit: impl Fn() + Send + 'static) {
+        self.stop();
+        
+        let (sender, receiver) = bounded::<()>(0);
+        let interval = self.interval;
+        
+        // Spawn a background thread that commits until stopped
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(interval) {
+                commit();
+            }
+        });
+        
+        *self.stop_sender.lock() = Some(sender);
+        *self.handle.lock() = Some(handle);
+    }
+    
+    /// Stop the thread, waiting for a commit in progress to finish
+    pub fn stop(&self) {
+        drop(self.stop_sender.lock().take());
+        
+        if let Some(handle) = self.handle.lock().take() {
+            // The task itself may drop the last handle to the cache, which
+            // stops the committer from its own thread
+            if handle.thread().id() != std::thread::current().id() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+impl Drop for AutoCommitter {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    
+    #[test]
+    fn test_runs_until_stopped() {
+        let committer = AutoCommitter::new(Duration::from_millis(10));
+        let commits = Arc::new(AtomicUsize::new(0));
+        
+        let counter = commits.clone();
+        committer.start(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        committer.stop();
+        
+        let stopped_at = commits.load(Ordering::SeqCst);
+        assert!(stopped_at > 0);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(commits.load(Ordering::SeqCst), stopped_at);
+    }
+}
@@ -3,6 +3,7 @@ use bytesize::ByteSize;
 
 use crate::document::Document;
 use crate::cache::lru_cache::LruCache;
+use crate::cache::memory_manager::MemoryManager;
 
 /// Document cache for TigerCache
 ///
@@ -20,18 +21,31 @@ impl DocumentCache {
         }
     }
     
+    /// Charge the cached documents to `memory_manager`
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.cache = self.cache.with_memory_manager(memory_manager);
+        self
+    }
+    
     /// Get a document from the cache
     pub fn get(&self, doc_id: &str) -> Option<Arc<Document>> {
         self.cache.get(doc_id)
     }
     
+    /// Check whether a document is cached, without affecting its eviction order or the hit rate
+    pub fn contains(&self, doc_id: &str) -> bool {
+        self.cache.contains_key(doc_id)
+    }
+    
     /// Put a document in the cache
     pub fn put(&self, document: Document) -> Option<Arc<Document>> {
-        let doc_id = document.id.clone();
+        self.put_arc(Arc::new(document))
+    }
+    
+    /// Put a shared document in the cache without copying it
+    pub fn put_arc(&self, document: Arc<Document>) -> Option<Arc<Document>> {
         let size = estimate_document_size(&document);
-        let doc_arc = Arc::new(document);
-        
-        self.cache.put(doc_id, doc_arc.clone(), size)
+        self.cache.put(document.id.clone(), document, size)
     }
     
     /// Remove a document from the cache
@@ -44,6 +58,11 @@ impl DocumentCache {
         self.cache.clear();
     }
     
+    /// Evict least recently used documents until at least `bytes` are freed, returning the amount freed
+    pub fn evict(&self, bytes: ByteSize) -> ByteSize {
+        ByteSize::b(self.cache.evict(bytes.as_u64() as usize) as u64)
+    }
+    
     /// Get the current size of the cache in bytes
     pub fn size(&self) -> ByteSize {
         self.cache.size()
@@ -85,15 +104,18 @@ fn estimate_document_size(document: &Document) -> usize {
         
         // Add the size of the value
         match value {
-            crate::document::FieldValue::Text(text) => {
+            serde_json::Value::String(text) => {
                 size += text.len();
             }
-            crate::document::FieldValue::Number(_) => {
+            serde_json::Value::Number(_) => {
                 size += std::mem::size_of::<f64>();
             }
-            crate::document::FieldValue::Boolean(_) => {
+            serde_json::Value::Bool(_) => {
                 size += std::mem::size_of::<bool>();
             }
+            other => {
+                size += other.to_string().len();
+            }
         }
     }
     
@@ -3,6 +3,7 @@ use bytesize::ByteSize;
 use smallvec::SmallVec;
 
 use crate::cache::lru_cache::LruCache;
+use crate::cache::memory_manager::MemoryManager;
 use crate::intern::StringId;
 
 /// Index cache for TigerCache
@@ -25,6 +26,13 @@ impl IndexCache {
         }
     }
     
+    /// Charge the cached entries to `memory_manager`
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.trigram_cache = self.trigram_cache.with_memory_manager(memory_manager.clone());
+        self.inverted_cache = self.inverted_cache.with_memory_manager(memory_manager);
+        self
+    }
+    
     /// Get a trigram index entry from the cache
     pub fn get_trigram(&self, trigram_id: StringId) -> Option<SmallVec<[StringId; 4]>> {
         self.trigram_cache.get(&trigram_id)
@@ -53,6 +61,18 @@ impl IndexCache {
         self.inverted_cache.clear();
     }
     
+    /// Evict least recently used entries until at least `bytes` are freed, returning the amount freed
+    ///
+    /// Inverted index entries are evicted before trigram entries.
+    pub fn evict(&self, bytes: ByteSize) -> ByteSize {
+        let bytes = bytes.as_u64() as usize;
+        let mut freed = self.inverted_cache.evict(bytes);
+        if freed < bytes {
+            freed += self.trigram_cache.evict(bytes - freed);
+        }
+        ByteSize::b(freed as u64)
+    }
+    
     /// Get the current size of the trigram cache in bytes
     pub fn trigram_size(&self) -> ByteSize {
         self.trigram_cache.size()
@@ -85,12 +105,12 @@ impl IndexCache {
 }
 
 /// Estimate the size of a SmallVec in bytes
-fn estimate_smallvec_size<T, const N: usize>(vec: &SmallVec<[T; N]>) -> usize {
+fn estimate_smallvec_size<A: smallvec::Array>(vec: &SmallVec<A>) -> usize {
     // Base size for the SmallVec struct
-    let mut size = std::mem::size_of::<SmallVec<[T; N]>>();
+    let mut size = std::mem::size_of::<SmallVec<A>>();
     
     // Add the size of the elements
-    size += vec.len() * std::mem::size_of::<T>();
+    size += vec.len() * std::mem::size_of::<A::Item>();
     
     size
 }
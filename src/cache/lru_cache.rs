@@ -1,9 +1,23 @@
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::sync::Arc;
 use std::time::Instant;
 use bytesize::ByteSize;
 use parking_lot::RwLock;
 
+use crate::cache::memory_manager::MemoryManager;
+
+/// Policy deciding which entries are evicted first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EvictionPolicy {
+    /// Evict the least recently used entries
+    #[default]
+    Lru,
+    
+    /// Evict the least frequently used entries
+    Lfu,
+}
+
 /// LRU cache entry
 struct LruEntry<K, V> {
     /// Key
@@ -18,7 +32,7 @@ struct LruEntry<K, V> {
     /// Last access time
     last_access: Instant,
     
-    /// Reference count
+    /// Number of cache hits on this entry (the LFU frequency)
     ref_count: u32,
 }
 
@@ -30,6 +44,9 @@ pub struct LruCache<K, V> {
     /// Maximum size in bytes
     max_size: usize,
     
+    /// Which entries to evict first when the cache is full
+    policy: EvictionPolicy,
+    
     /// Current size in bytes
     current_size: RwLock<usize>,
     
@@ -38,23 +55,40 @@ pub struct LruCache<K, V> {
     
     /// Cache miss count
     misses: RwLock<u64>,
+    
+    /// Memory manager charged for the size of the cached entries, if any
+    memory_manager: Option<Arc<MemoryManager>>,
 }
 
 impl<K, V> LruCache<K, V>
 where
     K: Eq + Hash + Clone,
+    V: Clone,
 {
     /// Create a new LRU cache with the specified maximum size
     pub fn new(max_size: ByteSize) -> Self {
+        Self::with_policy(max_size, EvictionPolicy::Lru)
+    }
+    
+    /// Create a new cache with the specified maximum size and eviction policy
+    pub fn with_policy(max_size: ByteSize, policy: EvictionPolicy) -> Self {
         Self {
             entries: RwLock::new(HashMap::new()),
             max_size: max_size.as_u64() as usize,
+            policy,
             current_size: RwLock::new(0),
             hits: RwLock::new(0),
             misses: RwLock::new(0),
+            memory_manager: None,
         }
     }
     
+    /// Allocate the size of cached entries from `memory_manager`, and free it when they leave the cache
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.memory_manager = Some(memory_manager);
+        self
+    }
+    
     /// Get a value from the cache
     pub fn get<Q>(&self, key: &Q) -> Option<V>
     where
@@ -89,6 +123,15 @@ where
         }
     }
     
+    /// Check whether a key is cached, without counting a hit or miss or updating its access time
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.read().contains_key(key)
+    }
+    
     /// Put a value in the cache
     pub fn put(&self, key: K, value: V, size: usize) -> Option<V>
     where
@@ -109,13 +152,15 @@ where
             
             // Update current size
             *current_size = current_size.saturating_sub(old_size).saturating_add(size);
+            self.account(size, old_size);
             
             return Some(old_value);
         }
         
         // Check if we need to evict entries
+        let mut evicted_size = 0;
         if *current_size + size > self.max_size {
-            self.evict_entries(&mut entries, &mut current_size, size);
+            evicted_size = self.evict_entries(&mut entries, &mut current_size, size);
         }
         
         // Add the new entry
@@ -129,6 +174,7 @@ where
         
         // Update current size
         *current_size = current_size.saturating_add(size);
+        self.account(size, evicted_size);
         
         None
     }
@@ -151,10 +197,11 @@ where
             let size = entry.size;
             
             // Remove the entry
-            entries.remove(&key);
+            entries.remove::<K>(&key);
             
             // Update current size
             *current_size = current_size.saturating_sub(size);
+            self.account(0, size);
             
             Some(value)
         } else {
@@ -168,6 +215,7 @@ where
         let mut current_size = self.current_size.write();
         
         entries.clear();
+        self.account(0, *current_size);
         *current_size = 0;
     }
     
@@ -204,49 +252,166 @@ where
         }
     }
     
-    /// Evict entries to make room for a new entry
+    /// Evict entries in eviction-policy order until at least `bytes` have been freed
+    ///
+    /// Returns the number of bytes freed.
+    pub fn evict(&self, bytes: usize) -> usize {
+        let mut entries = self.entries.write();
+        let mut current_size = self.current_size.write();
+        
+        let mut freed_size = 0;
+        for key in self.eviction_order(&entries) {
+            if freed_size >= bytes {
+                break;
+            }
+            if let Some(entry) = entries.remove(&key) {
+                freed_size += entry.size;
+                *current_size = current_size.saturating_sub(entry.size);
+            }
+        }
+        self.account(0, freed_size);
+        
+        freed_size
+    }
+    
+    /// Evict entries to make room for a new entry, returning the number of bytes freed
     fn evict_entries(
         &self,
         entries: &mut HashMap<K, LruEntry<K, V>>,
         current_size: &mut usize,
         needed_size: usize,
-    ) {
+    ) -> usize {
         // Calculate how much space we need to free
         let target_size = self.max_size.saturating_sub(needed_size);
-        let mut size_to_free = current_size.saturating_sub(target_size);
+        let size_to_free = current_size.saturating_sub(target_size);
         
         if size_to_free == 0 {
-            return;
+            return 0;
         }
         
-        // Sort entries by last access time (oldest first)
-        let mut sorted_entries: Vec<_> = entries.iter().collect();
-        sorted_entries.sort_by(|a, b| a.1.last_access.cmp(&b.1.last_access));
-        
         // Evict entries until we have enough space
         let mut freed_size = 0;
-        let mut keys_to_remove = Vec::new();
-        
-        for (key, entry) in sorted_entries {
-            // Skip entries with non-zero reference count
-            if entry.ref_count > 0 {
-                continue;
-            }
-            
-            freed_size += entry.size;
-            keys_to_remove.push(key.clone());
-            
+        for key in self.eviction_order(entries) {
             if freed_size >= size_to_free {
                 break;
             }
-        }
-        
-        // Remove the evicted entries
-        for key in keys_to_remove {
             if let Some(entry) = entries.remove(&key) {
+                freed_size += entry.size;
                 *current_size = current_size.saturating_sub(entry.size);
             }
         }
+        
+        freed_size
+    }
+    
+    /// Charge the memory manager for `added` bytes entering the cache and `removed` bytes leaving it
+    fn account(&self, added: usize, removed: usize) {
+        let Some(memory_manager) = &self.memory_manager else {
+            return;
+        };
+        if added > removed {
+            memory_manager.allocate(ByteSize::b((added - removed) as u64));
+        } else if removed > added {
+            memory_manager.free(ByteSize::b((removed - added) as u64));
+        }
+    }
+    
+    /// Keys in the order the eviction policy would remove them
+    fn eviction_order(&self, entries: &HashMap<K, LruEntry<K, V>>) -> Vec<K> {
+        let mut sorted_entries: Vec<_> = entries.values().collect();
+        match self.policy {
+            // Oldest access first
+            EvictionPolicy::Lru => sorted_entries.sort_by_key(|entry| entry.last_access),
+            // Fewest accesses first, oldest access breaking ties
+            EvictionPolicy::Lfu => sorted_entries.sort_by_key(|entry| (entry.ref_count, entry.last_access)),
+        }
+        sorted_entries.into_iter().map(|entry| entry.key.clone()).collect()
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_evict_oldest_first() {
+        let cache: LruCache<&str, u32> = LruCache::new(ByteSize::b(1000));
+        cache.put("a", 1, 100);
+        cache.put("b", 2, 100);
+        cache.put("c", 3, 100);
+        
+        // Touch "a" so "b" becomes the least recently used entry
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        cache.get("a");
+        
+        assert_eq!(cache.evict(150), 200);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.size(), ByteSize::b(100));
+        
+        assert_eq!(cache.evict(0), 0);
+        assert_eq!(cache.evict(1000), 100);
+        assert!(cache.is_empty());
+    }
+    
+    /// Insert "a" and "b", read "a" twice, then read "b" once so it is the most recent
+    fn crafted_cache(policy: EvictionPolicy) -> LruCache<&'static str, u32> {
+        let cache = LruCache::with_policy(ByteSize::b(200), policy);
+        cache.put("a", 1, 100);
+        cache.put("b", 2, 100);
+        
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        cache.get("a");
+        cache.get("a");
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        cache.get("b");
+        
+        // Inserting "c" forces one eviction
+        cache.put("c", 3, 100);
+        cache
+    }
+    
+    #[test]
+    fn test_lru_policy_evicts_least_recent() {
+        let cache = crafted_cache(EvictionPolicy::Lru);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(2));
+        assert_eq!(cache.get("c"), Some(3));
+    }
+    
+    #[test]
+    fn test_lfu_policy_evicts_least_frequent() {
+        let cache = crafted_cache(EvictionPolicy::Lfu);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(3));
+    }
+    
+    #[test]
+    fn test_memory_manager_tracks_size() {
+        let memory_manager = Arc::new(MemoryManager::new(ByteSize::mib(1)));
+        let cache = LruCache::new(ByteSize::b(300)).with_memory_manager(memory_manager.clone());
+        
+        cache.put("a", 1, 100);
+        cache.put("b", 2, 100);
+        assert_eq!(memory_manager.current_usage(), ByteSize::b(200));
+        
+        // Replacing an entry charges the difference
+        cache.put("a", 1, 150);
+        assert_eq!(memory_manager.current_usage(), ByteSize::b(250));
+        
+        // Entries evicted to make room are freed
+        cache.put("c", 3, 200);
+        assert_eq!(memory_manager.current_usage(), cache.size());
+        
+        cache.remove("c");
+        assert_eq!(memory_manager.current_usage(), cache.size());
+        cache.put("d", 4, 50);
+        cache.evict(1);
+        assert_eq!(memory_manager.current_usage(), cache.size());
+        cache.clear();
+        assert_eq!(memory_manager.current_usage(), ByteSize::b(0));
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::Instant;
 use bytesize::ByteSize;
 use parking_lot::RwLock;
@@ -239,7 +239,7 @@ impl MemoryManager {
     
     /// Update memory statistics
     pub fn update_stats(&self, stats: MemoryStats) {
-        *self.stats.write() = stats;
+        *self.stats.write() = stats.clone();
         let _ = self.event_sender.send(MemoryEvent::StatsUpdated(stats));
     }
     
@@ -12,8 +12,102 @@ mod query_cache;
 
 // Re-exports
 pub use memory_manager::{MemoryManager, MemoryStats};
-pub use lru_cache::LruCache;
 pub use document_cache::DocumentCache;
 pub use index_cache::IndexCache;
 pub use query_cache::QueryCache;
 
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use bytesize::ByteSize;
+use crossbeam_channel::RecvTimeoutError;
+
+use memory_manager::MemoryEvent;
+
+/// Sizes and hit rates of the document, index, and query caches
+#[derive(Debug, Clone)]
+pub struct CacheStats {
+    /// Document cache size in bytes
+    pub document_cache_size: ByteSize,
+    
+    /// Index cache size in bytes (trigram and inverted entries combined)
+    pub index_cache_size: ByteSize,
+    
+    /// Query cache size in bytes
+    pub query_cache_size: ByteSize,
+    
+    /// Document cache hit rate (0.0 - 1.0)
+    pub document_cache_hit_rate: f64,
+    
+    /// Index cache hit rate, averaged over the trigram and inverted caches (0.0 - 1.0)
+    pub index_cache_hit_rate: f64,
+    
+    /// Query cache hit rate (0.0 - 1.0)
+    pub query_cache_hit_rate: f64,
+}
+
+impl CacheStats {
+    /// Collect the current statistics of each cache
+    pub fn collect(document_cache: &DocumentCache, index_cache: &IndexCache, query_cache: &QueryCache) -> Self {
+        Self {
+            document_cache_size: document_cache.size(),
+            index_cache_size: index_cache.total_size(),
+            query_cache_size: query_cache.size(),
+            document_cache_hit_rate: document_cache.hit_rate(),
+            index_cache_hit_rate: index_cache.average_hit_rate(),
+            query_cache_hit_rate: query_cache.hit_rate(),
+        }
+    }
+}
+
+/// Spawn a thread that evicts cache entries when the memory manager requests it
+///
+/// Query results are evicted first since they are cheapest to recompute, then
+/// documents, then index entries. The caches free what they evict from the
+/// memory manager they were built with, so each request only evicts down to
+/// 80% of the memory limit. The thread exits once the memory manager is dropped.
+pub fn spawn_eviction_listener(
+    memory_manager: &Arc<MemoryManager>,
+    document_cache: &Arc<DocumentCache>,
+    index_cache: &Arc<IndexCache>,
+    query_cache: &Arc<QueryCache>,
+) {
+    let receiver = memory_manager.event_receiver();
+    let memory_manager = Arc::downgrade(memory_manager);
+    let document_cache = Arc::downgrade(document_cache);
+    let index_cache = Arc::downgrade(index_cache);
+    let query_cache = Arc::downgrade(query_cache);
+    
+    std::thread::spawn(move || loop {
+        let bytes_to_free = match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(MemoryEvent::EvictionRequired { bytes_to_free }) => bytes_to_free.as_u64(),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => 0,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        
+        let Some(manager) = memory_manager.upgrade() else {
+            break;
+        };
+        // Requests queue up while usage stays high; only evict what is still over
+        // the target, or earlier requests would be evicted again
+        let target = manager.max_memory().as_u64() * 80 / 100;
+        let bytes_to_free = bytes_to_free.min(manager.current_usage().as_u64().saturating_sub(target));
+        drop(manager);
+        if bytes_to_free == 0 {
+            continue;
+        }
+        
+        let mut freed = 0;
+        freed += evict_from(&query_cache, bytes_to_free - freed, |cache, bytes| cache.evict(bytes));
+        freed += evict_from(&document_cache, bytes_to_free.saturating_sub(freed), |cache, bytes| cache.evict(bytes));
+        evict_from(&index_cache, bytes_to_free.saturating_sub(freed), |cache, bytes| cache.evict(bytes));
+    });
+}
+
+/// Evict up to `bytes` from a cache that may already have been dropped
+fn evict_from<C>(cache: &Weak<C>, bytes: u64, evict: impl Fn(&C, ByteSize) -> ByteSize) -> u64 {
+    match cache.upgrade() {
+        Some(cache) if bytes > 0 => evict(&cache, ByteSize::b(bytes)).as_u64(),
+        _ => 0,
+    }
+}
+
@@ -3,6 +3,7 @@ use bytesize::ByteSize;
 
 use crate::search::{SearchOptions, SearchResult};
 use crate::cache::lru_cache::LruCache;
+use crate::cache::memory_manager::MemoryManager;
 
 /// Query key for cache lookups
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -30,6 +31,12 @@ impl QueryCache {
         }
     }
     
+    /// Charge the cached results to `memory_manager`
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.cache = self.cache.with_memory_manager(memory_manager);
+        self
+    }
+    
     /// Get search results from the cache
     pub fn get(&self, query: &str, options: Option<&SearchOptions>) -> Option<Arc<Vec<SearchResult>>> {
         let key = QueryKey {
@@ -58,6 +65,11 @@ impl QueryCache {
         self.cache.clear();
     }
     
+    /// Evict least recently used query results until at least `bytes` are freed, returning the amount freed
+    pub fn evict(&self, bytes: ByteSize) -> ByteSize {
+        ByteSize::b(self.cache.evict(bytes.as_u64() as usize) as u64)
+    }
+    
     /// Get the current size of the cache in bytes
     pub fn size(&self) -> ByteSize {
         self.cache.size()
@@ -116,15 +128,18 @@ fn estimate_document_size(document: &crate::document::Document) -> usize {
         
         // Add the size of the value
         match value {
-            crate::document::FieldValue::Text(text) => {
+            serde_json::Value::String(text) => {
                 size += text.len();
             }
-            crate::document::FieldValue::Number(_) => {
+            serde_json::Value::Number(_) => {
                 size += std::mem::size_of::<f64>();
             }
-            crate::document::FieldValue::Boolean(_) => {
+            serde_json::Value::Bool(_) => {
                 size += std::mem::size_of::<bool>();
             }
+            other => {
+                size += other.to_string().len();
+            }
         }
     }
     
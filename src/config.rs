@@ -1,8 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use bytesize::ByteSize;
 use serde::{Deserialize, Serialize};
 
+use crate::persistence::PersistenceFormat;
+use crate::schema::Schema;
 use crate::storage::{StorageConfig, StorageType};
+use crate::tokenizer::{Tokenizer, TokenizerKind, ENGLISH_STOPWORDS};
+use crate::trigram::{NormalizeOptions, PunctuationSet, DEFAULT_NGRAM_SIZE};
 
 /// TigerCache configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,11 +29,84 @@ pub struct TigerCacheConfig {
     /// Maximum number of search results
     pub max_results: usize,
     
+    /// Upper bound on `SearchOptions::limit`; larger limits are clamped to it
+    pub max_result_limit: Option<usize>,
+    
+    /// Size of the n-grams used for fuzzy candidate lookup
+    pub ngram_size: usize,
+    
+    /// Encoding of index files saved without a storage engine
+    pub persistence_format: PersistenceFormat,
+    
+    /// Whether to gzip index files saved without a storage engine (requires the `compression` feature)
+    pub compress_index_file: bool,
+    
     /// Whether to enable background operations
     pub enable_background_ops: bool,
     
     /// Whether to collect metrics
     pub collect_metrics: bool,
+    
+    /// Keep documents only in the storage engine, loading search results on demand
+    ///
+    /// Only takes effect when storage is configured; the in-memory index then holds
+    /// just document IDs and search indices.
+    pub lazy_documents: bool,
+    
+    /// Built-in tokenizer used for indexing and querying
+    pub tokenizer: TokenizerKind,
+    
+    /// Caller-supplied tokenizer, taking precedence over `tokenizer` (not serialized)
+    #[serde(skip)]
+    pub custom_tokenizer: Option<Arc<dyn Tokenizer>>,
+    
+    /// Whether to fold accented characters to their base form ("café" matches "cafe")
+    pub fold_diacritics: bool,
+    
+    /// Whether to keep the original casing of tokens instead of lowercasing them
+    pub case_sensitive: bool,
+    
+    /// ASCII punctuation kept in tokens instead of being stripped ("c++", "c#")
+    pub preserve_punctuation: PunctuationSet,
+    
+    /// Words skipped during indexing and searching (compared after normalization)
+    pub stopwords: HashSet<String>,
+    
+    /// Whether to apply Porter stemming to indexed and query tokens
+    pub stemming: bool,
+    
+    /// Tokens with fewer characters are dropped from documents and queries
+    pub min_token_length: usize,
+    
+    /// Whether to index tokens by their Soundex code, for `SearchOptions::phonetic`
+    pub phonetic_index: bool,
+    
+    /// Whether numbers inside tokens are indexed on their own and numeric query tokens match exactly
+    pub numeric_tokens: bool,
+    
+    /// Fields indexed as whole values, matched exactly by `field:value` query terms and keyword filters
+    pub keyword_fields: Vec<String>,
+    
+    /// Numeric fields kept in a sorted index, so range filters on them don't check every document
+    pub numeric_fields: Vec<String>,
+    
+    /// Query terms mapped to additional terms they should also match
+    pub synonyms: HashMap<String, Vec<String>>,
+    
+    /// Numeric document field whose value multiplies the document's search score
+    pub boost_field: Option<String>,
+    
+    /// Whether large batches are indexed in parallel shards that are merged afterwards
+    pub sharded_batches: bool,
+    
+    /// Searches taking longer than this are reported to the slow-query observer
+    pub slow_query_threshold: Option<Duration>,
+    
+    /// How often a background thread commits a storage-backed cache (requires `enable_background_ops`)
+    pub auto_commit_interval: Option<Duration>,
+    
+    /// Field types documents are validated against before they are indexed
+    pub schema: Option<Schema>,
 }
 
 impl Default for TigerCacheConfig {
@@ -37,8 +117,31 @@ impl Default for TigerCacheConfig {
             max_distance: 2,
             score_threshold: 0.0,
             max_results: 100,
+            max_result_limit: None,
+            ngram_size: DEFAULT_NGRAM_SIZE,
+            persistence_format: PersistenceFormat::default(),
+            compress_index_file: false,
             enable_background_ops: true,
             collect_metrics: false,
+            lazy_documents: false,
+            tokenizer: TokenizerKind::default(),
+            custom_tokenizer: None,
+            fold_diacritics: false,
+            case_sensitive: false,
+            preserve_punctuation: PunctuationSet::default(),
+            stopwords: HashSet::new(),
+            stemming: false,
+            min_token_length: 1,
+            phonetic_index: false,
+            numeric_tokens: false,
+            keyword_fields: Vec::new(),
+            numeric_fields: Vec::new(),
+            synonyms: HashMap::new(),
+            boost_field: None,
+            sharded_batches: false,
+            slow_query_threshold: None,
+            auto_commit_interval: None,
+            schema: None,
         }
     }
 }
@@ -103,6 +206,44 @@ impl TigerCacheConfig {
         self
     }
     
+    /// Clamp the result limit of every search to at most `limit`
+    ///
+    /// Applies to `SearchOptions::limit` and the `max` of `suggest`, so callers
+    /// such as untrusted clients can't request unbounded result sets. Requests
+    /// above the cap return at most `limit` results rather than failing.
+    pub fn with_max_result_limit(mut self, limit: usize) -> Self {
+        self.max_result_limit = Some(limit);
+        self
+    }
+    
+    /// Enable or disable lazy document loading from storage
+    pub fn with_lazy_documents(mut self, lazy_documents: bool) -> Self {
+        self.lazy_documents = lazy_documents;
+        self
+    }
+    
+    /// Set the n-gram size used for fuzzy matching (default 3)
+    pub fn with_ngram_size(mut self, ngram_size: usize) -> Self {
+        self.ngram_size = ngram_size;
+        self
+    }
+    
+    /// Set the encoding of index files saved without a storage engine
+    ///
+    /// Either format is detected when opening a file.
+    pub fn with_persistence_format(mut self, format: PersistenceFormat) -> Self {
+        self.persistence_format = format;
+        self
+    }
+    
+    /// Enable or disable gzip compression of index files saved without a storage engine
+    ///
+    /// Requires the `compression` feature; saving fails without it.
+    pub fn with_compress_index_file(mut self, compress: bool) -> Self {
+        self.compress_index_file = compress;
+        self
+    }
+    
     /// Set whether to enable background operations
     pub fn with_background_ops(mut self, enable: bool) -> Self {
         self.enable_background_ops = enable;
@@ -116,16 +257,189 @@ impl TigerCacheConfig {
         self
     }
     
+    /// Set the built-in tokenizer
+    pub fn with_tokenizer(mut self, tokenizer: TokenizerKind) -> Self {
+        self.tokenizer = tokenizer;
+        self.custom_tokenizer = None;
+        self
+    }
+    
+    /// Supply a custom tokenizer
+    pub fn with_custom_tokenizer<T: Tokenizer + 'static>(mut self, tokenizer: T) -> Self {
+        self.tokenizer = TokenizerKind::Custom;
+        self.custom_tokenizer = Some(Arc::new(tokenizer));
+        self
+    }
+    
+    /// Enable or disable diacritic folding
+    pub fn with_fold_diacritics(mut self, fold_diacritics: bool) -> Self {
+        self.fold_diacritics = fold_diacritics;
+        self
+    }
+    
+    /// Enable or disable case-sensitive indexing and search
+    ///
+    /// This changes the stored tokens, so it must be chosen before indexing.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+    
+    /// Keep the given ASCII punctuation characters in tokens instead of stripping them
+    ///
+    /// With `"+#"`, "C++" and "C#" are indexed and searched as distinct terms
+    /// rather than both as "c". Words made up only of punctuation are still
+    /// dropped. This changes the stored tokens, so it must be chosen before indexing.
+    pub fn with_preserve_punctuation(mut self, chars: &str) -> Self {
+        self.preserve_punctuation = PunctuationSet::new(chars);
+        self
+    }
+    
+    /// Set the stopwords
+    pub fn with_stopwords<I, S>(mut self, stopwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stopwords = stopwords.into_iter().map(Into::into).collect();
+        self
+    }
+    
+    /// Add the built-in English stopword list
+    pub fn with_english_stopwords(mut self) -> Self {
+        self.stopwords.extend(ENGLISH_STOPWORDS.iter().map(|word| word.to_string()));
+        self
+    }
+    
+    /// Enable or disable Porter stemming
+    pub fn with_stemming(mut self, stemming: bool) -> Self {
+        self.stemming = stemming;
+        self
+    }
+    
+    /// Set the minimum token length (default 1)
+    ///
+    /// Short tokens produce many noisy n-gram candidates; dropping them shrinks
+    /// the index. This changes the stored tokens, so it must be chosen before indexing.
+    pub fn with_min_token_length(mut self, length: usize) -> Self {
+        self.min_token_length = length;
+        self
+    }
+    
+    /// Enable or disable the phonetic index
+    ///
+    /// Adds a Soundex code entry for each indexed token, letting searches with
+    /// `SearchOptions::phonetic` match names like "Smyth" to "Smith". Like other
+    /// analysis settings, it must be chosen before indexing.
+    pub fn with_phonetic_index(mut self, enabled: bool) -> Self {
+        self.phonetic_index = enabled;
+        self
+    }
+    
+    /// Index numbers embedded in tokens on their own and match numeric query tokens exactly
+    ///
+    /// Useful for SKUs and IDs: "SKU-4471" is also indexed as "4471", and a
+    /// query for "4471" doesn't fuzzily match "4470".
+    pub fn with_numeric_tokens(mut self, enabled: bool) -> Self {
+        self.numeric_tokens = enabled;
+        self
+    }
+    
+    /// Set the fields indexed as keywords, such as status codes or enum values
+    ///
+    /// Each value of a keyword field is indexed as one untokenized term instead
+    /// of going through the text pipeline, so it's only found by an exact,
+    /// case-sensitive `field:value` query term (quoted if it contains spaces) or a
+    /// `Filter::Keyword`, never by fuzzy matching. Like other analysis settings,
+    /// it must be chosen before indexing.
+    pub fn with_keyword_fields(mut self, fields: Vec<String>) -> Self {
+        self.keyword_fields = fields;
+        self
+    }
+    
+    /// Set the numeric fields (prices, timestamps, and the like) kept in a sorted index
+    ///
+    /// A `Filter::Range` on one of these fields is resolved from the index,
+    /// without checking each matching document, and works for documents not
+    /// held in memory. Like other analysis settings, it must be chosen before indexing.
+    pub fn with_numeric_fields(mut self, fields: Vec<String>) -> Self {
+        self.numeric_fields = fields;
+        self
+    }
+    
+    /// Set the synonym map used to expand query terms
+    ///
+    /// Expansion is one-directional; add both entries for a bidirectional pair.
+    pub fn with_synonyms(mut self, synonyms: HashMap<String, Vec<String>>) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+    
+    /// Set the numeric field used as a per-document score boost
+    ///
+    /// Documents without a numeric value in this field get a boost of 1.0.
+    pub fn with_boost_field(mut self, field: impl Into<String>) -> Self {
+        self.boost_field = Some(field.into());
+        self
+    }
+    
+    /// Set whether large batches are indexed in parallel shards
+    ///
+    /// Each shard gets its own interner and the shards are merged afterwards.
+    /// Whether that beats the sequential postings update depends on the core
+    /// count and the data, so measure before enabling it.
+    pub fn with_sharded_batches(mut self, enabled: bool) -> Self {
+        self.sharded_batches = enabled;
+        self
+    }
+    
+    /// Set the duration above which searches are reported as slow
+    ///
+    /// Reports go to the observer registered with `TigerCache::set_slow_query_observer`.
+    pub fn with_slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = Some(threshold);
+        self
+    }
+    
+    /// Commit a storage-backed cache in the background at this interval
+    ///
+    /// The index metadata is written on ticks where the index changed since
+    /// the last commit, and the storage engine is flushed on every tick.
+    /// Ignored when background operations are disabled.
+    pub fn with_auto_commit_interval(mut self, interval: Duration) -> Self {
+        self.auto_commit_interval = Some(interval);
+        self
+    }
+    
+    /// Reject documents that don't conform to `schema` when they are added
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+    
+    /// Get the text normalization options selected by this configuration
+    pub fn normalize_options(&self) -> NormalizeOptions {
+        NormalizeOptions {
+            fold_diacritics: self.fold_diacritics,
+            case_sensitive: self.case_sensitive,
+            preserve_punctuation: self.preserve_punctuation,
+        }
+    }
+    
+    /// Create the tokenizer selected by this configuration
+    pub fn build_tokenizer(&self) -> Arc<dyn Tokenizer> {
+        match &self.custom_tokenizer {
+            Some(tokenizer) => tokenizer.clone(),
+            None => self.tokenizer.build(),
+        }
+    }
+    
     /// Create a development configuration with smaller cache sizes
     pub fn development() -> Self {
         Self {
             storage: StorageConfig::development(),
-            indexed_fields: Vec::new(),
-            max_distance: 2,
-            score_threshold: 0.0,
-            max_results: 100,
-            enable_background_ops: true,
             collect_metrics: true,
+            ..Default::default()
         }
     }
     
@@ -133,12 +447,7 @@ impl TigerCacheConfig {
     pub fn production() -> Self {
         Self {
             storage: StorageConfig::production(),
-            indexed_fields: Vec::new(),
-            max_distance: 2,
-            score_threshold: 0.0,
-            max_results: 100,
-            enable_background_ops: true,
-            collect_metrics: false,
+            ..Default::default()
         }
     }
     
@@ -146,12 +455,11 @@ impl TigerCacheConfig {
     pub fn low_memory() -> Self {
         Self {
             storage: StorageConfig::low_memory(),
-            indexed_fields: Vec::new(),
             max_distance: 1, // Reduce max distance to save memory
             score_threshold: 0.5, // Higher threshold to reduce result set
             max_results: 50, // Fewer results to save memory
             enable_background_ops: false, // Disable background ops to save resources
-            collect_metrics: false,
+            ..Default::default()
         }
     }
 }
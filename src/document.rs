@@ -1,13 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::error::{Result, TigerCacheError};
+
 /// A document that can be indexed and searched
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Documents are equal when their IDs and all field values are equal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Document {
     /// Unique identifier for the document
     pub id: String,
     
     /// Fields containing the document data
+    #[serde(with = "serde_fields")]
     pub fields: HashMap<String, serde_json::Value>,
 }
 
@@ -31,6 +36,36 @@ impl Document {
         self
     }
 
+    /// Add a field to the document, returning it for use in a single expression
+    ///
+    /// Values that fail to serialize are skipped, as with `add_field`.
+    ///
+    /// ```rust
+    /// use tiger_cache::{Document, TigerCache};
+    ///
+    /// let mut cache = TigerCache::new();
+    /// cache.add_document(
+    ///     Document::new("doc1")
+    ///         .with_field("title", "Apple iPhone")
+    ///         .with_field("price", 999),
+    /// ).unwrap();
+    ///
+    /// assert_eq!(cache.search("iphone", None).unwrap()[0].document.id, "doc1");
+    /// ```
+    pub fn with_field<T>(mut self, name: impl Into<String>, value: T) -> Self
+    where
+        T: Serialize,
+    {
+        self.add_field(name, value);
+        self
+    }
+    
+    /// Add several fields to the document, replacing fields with the same name
+    pub fn with_fields(mut self, fields: HashMap<String, serde_json::Value>) -> Self {
+        self.fields.extend(fields);
+        self
+    }
+    
     /// Get a field value as a string if it exists and is a string
     pub fn get_text_field(&self, name: &str) -> Option<String> {
         self.fields.get(name).map(|value| {
@@ -43,22 +78,111 @@ impl Document {
         })
     }
 
+    /// Get a field value as a number if it exists and is numeric
+    pub fn get_number_field(&self, name: &str) -> Option<f64> {
+        self.fields.get(name).and_then(serde_json::Value::as_f64)
+    }
+    
+    /// Overlay another document's fields onto this one, keeping this document's ID
+    ///
+    /// Fields present in both take `other`'s value; the rest are kept.
+    pub fn merge(&mut self, other: &Document) {
+        self.fields.extend(other.fields.iter().map(|(name, value)| (name.clone(), value.clone())));
+    }
+    
+    /// Copy the document keeping only the named fields (the ID is always kept)
+    pub fn project(&self, fields: &[String]) -> Self {
+        Self {
+            id: self.id.clone(),
+            fields: fields
+                .iter()
+                .filter_map(|name| self.fields.get_key_value(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        }
+    }
+    
     /// Get all text fields as a vector of strings
     pub fn get_all_text_fields(&self) -> Vec<String> {
+        self.get_all_text_fields_named()
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect()
+    }
+    
+    /// Get all text fields as `(field name, text)` pairs
+    pub fn get_all_text_fields_named(&self) -> Vec<(&str, String)> {
         self.fields
             .iter()
-            .filter_map(|(_, value)| {
+            .filter_map(|(name, value)| {
                 if let serde_json::Value::String(s) = value {
-                    Some(s.clone())
+                    Some((name.as_str(), s.clone()))
                 } else if !value.is_object() && !value.is_array() {
                     // Convert simple non-string values to string representation
-                    Some(value.to_string())
+                    Some((name.as_str(), value.to_string()))
                 } else {
                     None
                 }
             })
             .collect()
     }
+
+    /// Serialize the document for a storage engine
+    ///
+    /// JSON is used because field values are arbitrary `serde_json::Value`s,
+    /// which non-self-describing formats such as bincode can't decode.
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Deserialize a document written by `to_bytes`
+    ///
+    /// Documents stored before JSON was used are bincode, which wrote the field
+    /// values without their types, so they can't be decoded; they're reported
+    /// as `LegacyDocumentEncoding` with their ID so they can be re-added.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| match legacy_document_id(bytes) {
+            Some(id) => TigerCacheError::LegacyDocumentEncoding(id),
+            None => e.into(),
+        })
+    }
+}
+
+/// Read the ID of a document stored in the original bincode encoding
+///
+/// The ID is the first thing written and, unlike the field values, its type
+/// is known, so it's the only part that can be read back.
+fn legacy_document_id(bytes: &[u8]) -> Option<String> {
+    let (id, read) = bincode::decode_from_slice::<String, _>(bytes, bincode::config::standard()).ok()?;
+    (read < bytes.len()).then_some(id)
+}
+
+/// Serialize document fields so that binary formats can round-trip them
+///
+/// `serde_json::Value` needs a self-describing format to deserialize, so
+/// non-human-readable serializers (bincode) store the fields as a JSON string.
+mod serde_fields {
+    use std::collections::HashMap;
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    
+    pub fn serialize<S: Serializer>(fields: &HashMap<String, serde_json::Value>, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            fields.serialize(serializer)
+        } else {
+            serde_json::to_string(fields).map_err(S::Error::custom)?.serialize(serializer)
+        }
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, serde_json::Value>, D::Error> {
+        if deserializer.is_human_readable() {
+            HashMap::deserialize(deserializer)
+        } else {
+            let json = String::deserialize(deserializer)?;
+            serde_json::from_str(&json).map_err(D::Error::custom)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -192,4 +316,95 @@ mod tests {
         assert!(fields.contains(&"Test Title".to_string()));
         // Complex types (arrays and objects) should be filtered out
     }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut doc = Document::new("test_id");
+        doc.add_field("title", "Test Document")
+            .add_field("tags", vec!["a", "b"])
+            .add_field("score", 4.5);
+        
+        let decoded = Document::from_bytes(&doc.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.id, doc.id);
+        assert_eq!(decoded.fields, doc.fields);
+        assert!(Document::from_bytes(b"not a document").is_err());
+    }
+
+    #[test]
+    fn test_legacy_bytes_reported() {
+        // {id: "doc1", fields: {title: "Hello"}} in the original bincode encoding
+        let mut legacy = vec![4];
+        legacy.extend_from_slice(b"doc1");
+        legacy.extend_from_slice(&[1, 5]);
+        legacy.extend_from_slice(b"title");
+        legacy.push(5);
+        legacy.extend_from_slice(b"Hello");
+        
+        let result = Document::from_bytes(&legacy);
+        assert!(matches!(result, Err(TigerCacheError::LegacyDocumentEncoding(id)) if id == "doc1"));
+        assert!(matches!(Document::from_bytes(b"not a document"), Err(TigerCacheError::JsonError(_))));
+    }
+
+    #[test]
+    fn test_bincode_roundtrip() {
+        let mut doc = Document::new("test_id");
+        doc.add_field("title", "Test Document")
+            .add_field("meta", serde_json::json!({"pages": 12, "draft": false}));
+        
+        let bytes = bincode::encode_to_vec(&doc, bincode::config::standard()).unwrap();
+        let (decoded, _): (Document, usize) = bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(decoded.fields, doc.fields);
+    }
+
+    #[test]
+    fn test_equality() {
+        let mut doc = Document::new("test_id");
+        doc.add_field("title", "Test Document").add_field("pages", 12);
+        
+        let mut same = Document::new("test_id");
+        same.add_field("pages", 12).add_field("title", "Test Document");
+        assert_eq!(doc, same);
+        
+        let mut other_value = same.clone();
+        other_value.add_field("pages", 13);
+        assert_ne!(doc, other_value);
+        
+        let mut other_id = doc.clone();
+        other_id.id = "other_id".to_string();
+        assert_ne!(doc, other_id);
+        
+        let mut extra_field = doc.clone();
+        extra_field.add_field("draft", true);
+        assert_ne!(doc, extra_field);
+    }
+
+    #[test]
+    fn test_with_fields() {
+        let doc = Document::new("doc1")
+            .with_field("title", "Apple iPhone")
+            .with_fields(HashMap::from([
+                ("price".to_string(), serde_json::json!(999)),
+                ("title".to_string(), serde_json::json!("Apple iPhone 15")),
+            ]));
+        
+        assert_eq!(doc.get_text_field("title"), Some("Apple iPhone 15".to_string()));
+        assert_eq!(doc.get_number_field("price"), Some(999.0));
+        assert_eq!(doc.fields.len(), 2);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut doc = Document::new("test_id");
+        doc.add_field("title", "Old Title").add_field("pages", 12);
+        
+        let mut update = Document::new("update_id");
+        update.add_field("title", "New Title").add_field("draft", false);
+        doc.merge(&update);
+        
+        let mut expected = Document::new("test_id");
+        expected.add_field("title", "New Title")
+            .add_field("pages", 12)
+            .add_field("draft", false);
+        assert_eq!(doc, expected);
+    }
 }
@@ -0,0 +1,95 @@
+use crate::error::{Result, TigerCacheError};
+
+/// Length of an AES-GCM nonce in bytes
+pub const NONCE_LEN: usize = 12;
+
+/// Encrypt `plaintext` with a fresh random nonce, returning the nonce and ciphertext
+///
+/// `aad` is authenticated but not encrypted: decryption only succeeds with the
+/// same associated data, which binds the ciphertext to its context.
+#[cfg(feature = "encryption")]
+pub fn encrypt(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<([u8; NONCE_LEN], Vec<u8>)> {
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+    use aes_gcm::Aes256Gcm;
+    
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| TigerCacheError::SerializationError("Encryption failed".to_string()))?;
+    Ok((nonce.into(), ciphertext))
+}
+
+/// Decrypt and authenticate `ciphertext` along with the associated data it was encrypted with
+///
+/// Fails with `TigerCacheError::DecryptionFailed` on a wrong key, tampered
+/// data, or different associated data.
+#[cfg(feature = "encryption")]
+pub fn decrypt(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::{Aes256Gcm, Nonce};
+    
+    if nonce.len() != NONCE_LEN {
+        return Err(TigerCacheError::DecryptionFailed("Invalid nonce length".to_string()));
+    }
+    
+    Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| TigerCacheError::DecryptionFailed("Wrong key or corrupted data".to_string()))
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn encrypt(_key: &[u8; 32], _plaintext: &[u8], _aad: &[u8]) -> Result<([u8; NONCE_LEN], Vec<u8>)> {
+    Err(TigerCacheError::EncryptionUnavailable)
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn decrypt(_key: &[u8; 32], _nonce: &[u8], _ciphertext: &[u8], _aad: &[u8]) -> Result<Vec<u8>> {
+    Err(TigerCacheError::EncryptionUnavailable)
+}
+
+/// Encrypt a value into a single buffer: nonce followed by ciphertext
+pub fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let (nonce, ciphertext) = encrypt(key, plaintext, aad)?;
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt a buffer produced by `seal` with the same associated data
+pub fn open(key: &[u8; 32], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return Err(TigerCacheError::DecryptionFailed("Encrypted value is truncated".to_string()));
+    }
+    decrypt(key, &sealed[..NONCE_LEN], &sealed[NONCE_LEN..], aad)
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_seal_and_open() {
+        let key = [7u8; 32];
+        let sealed = seal(&key, b"secret document", b"doc:a").unwrap();
+        assert_ne!(&sealed[NONCE_LEN..], b"secret document");
+        assert_eq!(open(&key, &sealed, b"doc:a").unwrap(), b"secret document");
+        
+        // Each call uses a fresh nonce
+        assert_ne!(seal(&key, b"secret document", b"doc:a").unwrap(), sealed);
+    }
+    
+    #[test]
+    fn test_open_wrong_key() {
+        let sealed = seal(&[7u8; 32], b"secret document", b"doc:a").unwrap();
+        assert!(matches!(open(&[8u8; 32], &sealed, b"doc:a"), Err(TigerCacheError::DecryptionFailed(_))));
+        assert!(matches!(open(&[7u8; 32], &sealed[..4], b"doc:a"), Err(TigerCacheError::DecryptionFailed(_))));
+    }
+    
+    #[test]
+    fn test_open_different_associated_data() {
+        let sealed = seal(&[7u8; 32], b"secret document", b"doc:a").unwrap();
+        assert!(matches!(open(&[7u8; 32], &sealed, b"doc:b"), Err(TigerCacheError::DecryptionFailed(_))));
+        assert!(matches!(open(&[7u8; 32], &sealed, b""), Err(TigerCacheError::DecryptionFailed(_))));
+    }
+}
@@ -24,9 +24,41 @@ pub enum TigerCacheError {
     #[error("Invalid document format: {0}")]
     InvalidDocument(String),
 
+    /// Stored document written in the original bincode encoding, whose field values can't be decoded
+    #[error("Document {0} is stored in the legacy binary encoding, which can't be decoded; add it again")]
+    LegacyDocumentEncoding(String),
+
+    /// Indices built with different analysis settings can't be combined
+    #[error("Incompatible index: {0}")]
+    IncompatibleIndex(String),
+
     /// Invalid search query
     #[error("Invalid search query: {0}")]
     InvalidQuery(String),
+
+    /// Index file written with a format version this build can't read
+    #[error("Unsupported index file format version {found} (expected {expected})")]
+    UnsupportedFormatVersion { found: u32, expected: u32 },
+
+    /// Index file contents don't match the checksum in its header
+    #[error("Index file checksum mismatch: expected {expected:#010x}, found {found:#010x}")]
+    ChecksumMismatch { expected: u32, found: u32 },
+
+    /// Encrypted data couldn't be decrypted (wrong key or tampered data)
+    #[error("Decryption failed: {0}")]
+    DecryptionFailed(String),
+
+    /// Encryption was requested but the `encryption` feature isn't enabled
+    #[error("Encryption requires the `encryption` feature")]
+    EncryptionUnavailable,
+
+    /// A search was abandoned because its cancel flag was set
+    #[error("Search cancelled")]
+    Cancelled,
+
+    /// A conditional update found the document at a different version than expected
+    #[error("Version conflict on document {doc_id}: expected version {expected}, found {found}")]
+    VersionConflict { doc_id: String, expected: u64, found: u64 },
 }
 
 /// Result type alias for Tiger Cache operations
@@ -0,0 +1,62 @@
+use crate::document::Document;
+
+/// Number of documents inserted per `add_documents_batch` call during bulk imports
+pub(crate) const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// What a bulk import does with a line that can't be turned into a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportErrorPolicy {
+    /// Stop at the first invalid line and return its error
+    #[default]
+    FailFast,
+    
+    /// Skip invalid lines and keep importing
+    Skip,
+}
+
+/// Build a document from one NDJSON line, taking its ID from `id_field`
+///
+/// String and numeric IDs are accepted; the ID field itself isn't stored as a
+/// document field. Errors describe what is wrong with the line.
+pub(crate) fn document_from_ndjson_line(line: &str, id_field: &str) -> Result<Document, String> {
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let serde_json::Value::Object(mut fields) = value else {
+        return Err("expected a JSON object".to_string());
+    };
+    
+    let id = match fields.remove(id_field) {
+        Some(serde_json::Value::String(id)) => id,
+        Some(serde_json::Value::Number(id)) => id.to_string(),
+        Some(_) => {
+            return Err(format!("field `{id_field}` is not a string or number"));
+        }
+        None => {
+            return Err(format!("missing ID field `{id_field}`"));
+        }
+    };
+    
+    let mut document = Document::new(id);
+    document.fields = fields.into_iter().collect();
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_document_from_ndjson_line() {
+        let doc = document_from_ndjson_line(r#"{"sku": "A1", "title": "Widget", "price": 9.5}"#, "sku").unwrap();
+        assert_eq!(doc.id, "A1");
+        assert_eq!(doc.fields.len(), 2);
+        assert_eq!(doc.fields["price"], serde_json::json!(9.5));
+        
+        let doc = document_from_ndjson_line(r#"{"sku": 42, "title": "Gadget"}"#, "sku").unwrap();
+        assert_eq!(doc.id, "42");
+        
+        assert!(document_from_ndjson_line(r#"{"title": "No ID"}"#, "sku").is_err());
+        assert!(document_from_ndjson_line(r#"{"sku": null}"#, "sku").is_err());
+        assert!(document_from_ndjson_line(r#"["not", "an", "object"]"#, "sku").is_err());
+        assert!(document_from_ndjson_line("{broken", "sku").is_err());
+    }
+}
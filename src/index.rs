@@ -1,29 +1,204 @@
 use crate::document::Document;
 use crate::error::{TigerCacheError, Result};
 use crate::intern::{StringId, StringInterner};
-use crate::trigram::{extract_tokens, generate_trigrams, normalize_text};
+use crate::phonetic::soundex;
+use crate::postings::{field_bit_at, FieldMask, Postings, OVERFLOW_FIELD_BIT};
+use crate::query::QueryAst;
+use crate::stemmer::porter_stem;
+use crate::tokenizer::{StandardTokenizer, Tokenizer, TokenizerKind};
+use crate::trigram::{generate_ngrams_with, normalize_text_with, NormalizeOptions, DEFAULT_NGRAM_SIZE};
+use ordered_float::OrderedFloat;
 use rayon::prelude::*;
+use roaring::RoaringBitmap;
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::Bound;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Smallest number of documents per shard when indexing a batch in parallel
+const MIN_SHARD_DOCUMENTS: usize = 1024;
+
+/// Prefix marking keyword field values in the inverted index, keeping them apart
+/// from tokens, which never contain control characters
+const KEYWORD_PREFIX: char = '\u{0}';
+
+/// Unique tokens of each indexed field of a document, with how often each occurs
+type FieldTokens = Vec<(String, FxHashMap<String, u32>)>;
+
+/// Counts describing the composition of an index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Number of documents in the index
+    pub document_count: usize,
+    
+    /// Number of unique indexed tokens
+    pub unique_tokens: usize,
+    
+    /// Number of unique n-grams in the trigram index
+    pub unique_trigrams: usize,
+    
+    /// Number of interned strings (document IDs, tokens, and n-grams share the interner)
+    pub interned_strings: usize,
+}
+
+/// A problem found by checking an index's internal consistency
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InconsistencyReport {
+    /// An ID the index is keyed by has no interned string
+    UninternedId { id: u32 },
+    
+    /// The number of documents differs from the number with a recorded length
+    DocumentCountMismatch { documents: usize, lengths: usize },
+    
+    /// A token's postings list is empty instead of having been removed
+    EmptyPostings { token: String },
+    
+    /// A token's postings list names a document that isn't in the index
+    DanglingPosting { token: String, doc_id: String },
+    
+    /// An n-gram in the trigram index lists a token without postings
+    ///
+    /// Removing documents leaves these behind until the index is compacted.
+    DanglingTrigram { ngram: String, token: String },
+    
+    /// A Soundex code in the phonetic index lists a token without postings
+    DanglingPhonetic { code: String, token: String },
+    
+    /// A numeric field's index names a document that isn't in the index
+    DanglingNumericEntry { field: String, doc_id: String },
+}
+
+impl fmt::Display for InconsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UninternedId { id } => write!(f, "ID #{id} has no interned string"),
+            Self::DocumentCountMismatch { documents, lengths } => {
+                write!(f, "{documents} documents but {lengths} document lengths")
+            }
+            Self::EmptyPostings { token } => write!(f, "token {token:?} has no postings"),
+            Self::DanglingPosting { token, doc_id } => {
+                write!(f, "token {token:?} lists missing document {doc_id}")
+            }
+            Self::DanglingTrigram { ngram, token } => {
+                write!(f, "n-gram {ngram:?} lists token {token:?} without postings")
+            }
+            Self::DanglingPhonetic { code, token } => {
+                write!(f, "phonetic code {code} lists token {token:?} without postings")
+            }
+            Self::DanglingNumericEntry { field, doc_id } => {
+                write!(f, "numeric field {field} lists missing document {doc_id}")
+            }
+        }
+    }
+}
 
 /// The main index structure that holds documents and search indices
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Index {
-    /// Map of document IDs to documents
-    documents: FxHashMap<StringId, Document>,
+    /// Map of document IDs to documents (`None` when only the storage engine holds the document)
+    documents: FxHashMap<StringId, Option<Document>>,
+    
+    /// Whether full documents are kept in memory alongside the search indices
+    retain_documents: bool,
     
-    /// Inverted index mapping token IDs to document IDs
-    inverted_index: FxHashMap<StringId, SmallVec<[StringId; 8]>>,
+    /// Inverted index mapping token IDs to the documents (and their fields) containing them
+    inverted_index: FxHashMap<StringId, Postings>,
+    
+    /// Names of the fields seen while indexing; a field's position is its bit in posting masks
+    #[serde(default)]
+    field_names: Vec<String>,
     
     /// Trigram index mapping trigram IDs to token IDs
     trigram_index: FxHashMap<StringId, SmallVec<[StringId; 4]>>,
     
+    /// Phonetic index mapping Soundex code IDs to token IDs (empty unless enabled)
+    #[serde(default)]
+    phonetic_index: FxHashMap<StringId, SmallVec<[StringId; 4]>>,
+    
+    /// Total number of indexed tokens (repeats included) of each document
+    #[serde(default)]
+    document_lengths: FxHashMap<StringId, u32>,
+    
+    /// Size of the n-grams stored in the trigram index
+    ngram_size: usize,
+    
     /// String interner for memory efficiency
     interner: StringInterner,
     
     /// Fields to be indexed for search
     indexed_fields: Vec<String>,
+    
+    /// Tokenizer applied to documents and queries (persisted as its kind tag)
+    #[serde(with = "crate::tokenizer::serde_tokenizer")]
+    tokenizer: Arc<dyn Tokenizer>,
+    
+    /// Options applied when normalizing documents and queries
+    normalize: NormalizeOptions,
+    
+    /// Normalized tokens that are never indexed or searched
+    stopwords: FxHashSet<String>,
+    
+    /// Whether tokens are reduced to their Porter stem
+    stemming: bool,
+    
+    /// Tokens with fewer characters are never indexed or searched
+    #[serde(default)]
+    min_token_length: usize,
+    
+    /// Whether indexed tokens are added to the phonetic index
+    #[serde(default)]
+    phonetic: bool,
+    
+    /// Whether digit runs inside tokens are indexed as tokens of their own,
+    /// with digit-only query tokens matched exactly
+    #[serde(default)]
+    numeric_tokens: bool,
+    
+    /// Fields indexed as whole, untokenized values that only match exactly
+    #[serde(default)]
+    keyword_fields: Vec<String>,
+    
+    /// Numeric fields whose values are kept sorted in the numeric index
+    #[serde(default)]
+    numeric_fields: Vec<String>,
+    
+    /// Sorted index of each numeric field, mapping values to the documents holding them
+    #[serde(default)]
+    numeric_index: FxHashMap<String, BTreeMap<OrderedFloat<f64>, Vec<StringId>>>,
+    
+    /// Query-time synonym expansions, keyed by normalized token (supplied by the configuration)
+    #[serde(skip)]
+    synonyms: FxHashMap<String, Vec<String>>,
+    
+    /// Numeric document field multiplying search scores (supplied by the configuration)
+    #[serde(skip)]
+    boost_field: Option<String>,
+    
+    /// Whether large batches are indexed in parallel shards (supplied by the configuration)
+    #[serde(skip)]
+    sharded_batches: bool,
+    
+    /// Expiry times of documents added with a TTL, keyed by document ID
+    #[serde(default)]
+    expirations: FxHashMap<String, SystemTime>,
+    
+    /// Version of each document, starting at 1 and incremented every time it's replaced
+    #[serde(default)]
+    versions: FxHashMap<String, u64>,
+    
+    /// Whether settings affecting tokenization changed since documents were
+    /// indexed, so re-tokenizing a document may not find its postings
+    #[serde(default)]
+    stale_analysis: bool,
+    
+    /// Whether numeric fields were added while some documents weren't held in
+    /// memory, so the numeric index misses their values
+    #[serde(default)]
+    stale_numeric_index: bool,
 }
 
 impl Index {
@@ -31,76 +206,713 @@ impl Index {
     pub fn new() -> Self {
         Self {
             documents: FxHashMap::default(),
+            retain_documents: true,
             inverted_index: FxHashMap::default(),
+            field_names: Vec::new(),
             trigram_index: FxHashMap::default(),
+            phonetic_index: FxHashMap::default(),
+            document_lengths: FxHashMap::default(),
+            ngram_size: DEFAULT_NGRAM_SIZE,
             interner: StringInterner::new(),
             indexed_fields: Vec::new(),
+            tokenizer: Arc::new(StandardTokenizer),
+            normalize: NormalizeOptions::default(),
+            stopwords: FxHashSet::default(),
+            stemming: false,
+            min_token_length: 1,
+            phonetic: false,
+            numeric_tokens: false,
+            keyword_fields: Vec::new(),
+            numeric_fields: Vec::new(),
+            numeric_index: FxHashMap::default(),
+            synonyms: FxHashMap::default(),
+            boost_field: None,
+            sharded_batches: false,
+            expirations: FxHashMap::default(),
+            versions: FxHashMap::default(),
+            stale_analysis: false,
+            stale_numeric_index: false,
         }
     }
     
     /// Set the fields to be indexed for search
     pub fn set_indexed_fields(&mut self, fields: Vec<String>) -> &mut Self {
+        self.analysis_changed(self.indexed_fields != fields);
         self.indexed_fields = fields;
         self
     }
     
-    /// Add a document to the index
-    pub fn add_document(&mut self, document: Document) -> Result<()> {
-        let doc_id_str = document.id.clone();
-        let doc_id = self.interner.intern(&doc_id_str);
+    /// Record a change to a setting affecting tokenization, until the next `reindex`
+    fn analysis_changed(&mut self, changed: bool) {
+        self.stale_analysis |= changed && !self.documents.is_empty();
+    }
+    
+    /// Set whether full documents are kept in memory
+    ///
+    /// When disabled, only document IDs and the search indices are kept; callers
+    /// are expected to hold documents elsewhere (such as a storage engine) and
+    /// resolve the IDs returned by `search_ids`. Documents already held are released.
+    pub fn set_retain_documents(&mut self, retain: bool) -> &mut Self {
+        self.retain_documents = retain;
+        if !retain {
+            self.documents.values_mut().for_each(|document| *document = None);
+        }
+        self
+    }
+    
+    /// Check whether full documents are kept in memory
+    pub fn retains_documents(&self) -> bool {
+        self.retain_documents
+    }
+    
+    /// Set the tokenizer used for documents and queries
+    ///
+    /// Documents already in the index keep the tokens produced by the previous
+    /// tokenizer, so this should be called before adding documents.
+    pub fn set_tokenizer(&mut self, tokenizer: Arc<dyn Tokenizer>) -> &mut Self {
+        // Custom tokenizers can't be compared, so replacing one always counts as a change
+        self.analysis_changed(true);
+        self.tokenizer = tokenizer;
+        self
+    }
+    
+    /// Re-attach the custom tokenizer a loaded index was built with, which isn't persisted
+    pub(crate) fn restore_tokenizer(&mut self, tokenizer: Arc<dyn Tokenizer>) -> &mut Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+    
+    /// Get the kind of the configured tokenizer
+    pub fn tokenizer_kind(&self) -> TokenizerKind {
+        self.tokenizer.kind()
+    }
+    
+    /// Set the n-gram size used for fuzzy matching (default 3)
+    ///
+    /// Existing entries in the trigram index were generated with the previous size,
+    /// so call `reindex` after changing it on an index that already holds documents.
+    pub fn set_ngram_size(&mut self, n: usize) -> &mut Self {
+        self.ngram_size = n.max(1);
+        self
+    }
+    
+    /// Get the n-gram size used for fuzzy matching
+    pub fn ngram_size(&self) -> usize {
+        self.ngram_size
+    }
+    
+    /// Generate the n-grams of a token using the configured size
+    pub(crate) fn ngrams(&self, token: &str) -> HashSet<String> {
+        generate_ngrams_with(token, self.ngram_size, &self.normalize)
+    }
+    
+    /// Set the text normalization options used for documents and queries
+    ///
+    /// Like the tokenizer, this should be set before adding documents or stopwords.
+    pub fn set_normalize_options(&mut self, options: NormalizeOptions) -> &mut Self {
+        self.analysis_changed(self.normalize != options);
+        self.normalize = options;
+        self
+    }
+    
+    /// Set the stopwords skipped during indexing and searching
+    ///
+    /// Stopwords are normalized before comparison, so casing and punctuation
+    /// don't matter. Like the tokenizer, this should be set before adding documents.
+    pub fn set_stopwords<I, S>(&mut self, stopwords: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let stopwords: FxHashSet<String> = stopwords
+            .into_iter()
+            .map(|word| self.stopword_key(word.as_ref()))
+            .filter(|word| !word.is_empty())
+            .collect();
+        self.analysis_changed(self.stopwords != stopwords);
+        self.stopwords = stopwords;
+        self
+    }
+    
+    /// Normalize a word for stopword comparison, which ignores case even in case-sensitive mode
+    fn stopword_key(&self, word: &str) -> String {
+        let options = NormalizeOptions { case_sensitive: false, ..self.normalize };
+        normalize_text_with(word, &options)
+    }
+    
+    /// Check whether a token is a stopword
+    fn is_stopword(&self, token: &str) -> bool {
+        !self.stopwords.is_empty() && self.stopwords.contains(&self.stopword_key(token))
+    }
+    
+    /// Enable or disable Porter stemming of tokens
+    ///
+    /// Stemming changes the tokens stored in the index, so call `reindex`
+    /// after changing it on an index that already holds documents.
+    pub fn set_stemming(&mut self, enabled: bool) -> &mut Self {
+        self.analysis_changed(self.stemming != enabled);
+        self.stemming = enabled;
+        self
+    }
+    
+    /// Set the minimum number of characters a token needs to be indexed or searched
+    ///
+    /// Like other analysis settings, this only applies to documents indexed afterwards.
+    pub fn set_min_token_length(&mut self, length: usize) -> &mut Self {
+        self.analysis_changed(self.min_token_length != length);
+        self.min_token_length = length;
+        self
+    }
+    
+    /// Set whether tokens are indexed by their Soundex code, for `SearchOptions::phonetic`
+    ///
+    /// Like other analysis settings, this only applies to documents indexed afterwards.
+    pub fn set_phonetic(&mut self, enabled: bool) -> &mut Self {
+        self.phonetic = enabled;
+        self
+    }
+    
+    /// Set whether digit runs inside tokens are indexed on their own and digit-only query tokens match exactly
+    ///
+    /// With this enabled, "Test-123" is indexed as "test123" and "123", and a
+    /// query for "123" matches it without fuzzy candidates such as "124". Like
+    /// other analysis settings, this only applies to documents indexed afterwards.
+    pub fn set_numeric_tokens(&mut self, enabled: bool) -> &mut Self {
+        self.analysis_changed(self.numeric_tokens != enabled);
+        self.numeric_tokens = enabled;
+        self
+    }
+    
+    /// Set the fields indexed as keywords: each value is a single untokenized
+    /// term, matched only exactly and only by `field:value` query terms
+    ///
+    /// Keyword fields are left out of full-text indexing. String values are used
+    /// as they are, without normalization; numbers and booleans by their JSON
+    /// text, and arrays by each element. Like other analysis settings, this only
+    /// applies to documents indexed afterwards.
+    pub fn set_keyword_fields(&mut self, fields: Vec<String>) -> &mut Self {
+        self.analysis_changed(self.keyword_fields != fields);
+        self.keyword_fields = fields;
+        self
+    }
+    
+    /// Check whether a field is indexed as a keyword
+    pub(crate) fn is_keyword_field(&self, field_name: &str) -> bool {
+        self.keyword_fields.iter().any(|name| name == field_name)
+    }
+    
+    /// Parse query syntax, scoping `name:term` words to `name` only for fields
+    /// this index indexed or is configured with
+    pub(crate) fn parse_query(&self, query: &str) -> QueryAst {
+        QueryAst::parse_with_fields(query, |field| {
+            self.field_names.iter()
+                .chain(&self.indexed_fields)
+                .chain(&self.keyword_fields)
+                .any(|name| name == field)
+        })
+    }
+    
+    /// Get the IDs of documents whose keyword field holds exactly `value`
+    pub(crate) fn keyword_documents(&self, field_name: &str, value: &str) -> Vec<String> {
+        match self.field_mask(field_name).filter(|_| self.is_keyword_field(field_name)) {
+            Some(fields) => self.get_documents_for_token_in_fields(&keyword_token(value), fields),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Check whether a document's keyword field holds exactly `value`
+    pub(crate) fn has_keyword(&self, doc_id: &str, field_name: &str, value: &str) -> bool {
+        let (Some(fields), Some(doc_id), Some(token_id)) = (
+            self.field_mask(field_name).filter(|_| self.is_keyword_field(field_name)),
+            self.interner.get_id(doc_id),
+            self.interner.get_id(&keyword_token(value)),
+        ) else {
+            return false;
+        };
+        self.inverted_index
+            .get(&token_id)
+            .is_some_and(|postings| postings.docs_in_fields(fields).contains(doc_id.as_u32()))
+    }
+    
+    /// Set the fields whose numeric values are kept in a sorted index, so range
+    /// filters on them don't check every matching document
+    ///
+    /// Documents whose field value isn't a number are left out of the numeric
+    /// index. Added fields are backfilled from the documents held in memory;
+    /// if some aren't held, range filters check documents until the next `reindex`.
+    pub fn set_numeric_fields(&mut self, fields: Vec<String>) -> &mut Self {
+        if self.numeric_fields == fields {
+            return self;
+        }
         
-        // Extract tokens from indexed fields
-        let mut all_tokens = FxHashSet::default();
+        self.numeric_index.retain(|field_name, _| fields.contains(field_name));
+        let added: Vec<&String> = fields.iter().filter(|name| !self.numeric_fields.contains(name)).collect();
+        for (&doc_id, document) in &self.documents {
+            let Some(document) = document else {
+                self.stale_numeric_index |= !added.is_empty();
+                continue;
+            };
+            for &field_name in &added {
+                let Some(value) = document.get_number_field(field_name) else {
+                    continue;
+                };
+                self.numeric_index
+                    .entry(field_name.clone())
+                    .or_default()
+                    .entry(OrderedFloat(value))
+                    .or_default()
+                    .push(doc_id);
+            }
+        }
         
-        if self.indexed_fields.is_empty() {
-            // If no specific fields are set, index all text fields
-            for text in document.get_all_text_fields() {
-                for token in extract_tokens(&text) {
-                    all_tokens.insert(token);
+        self.numeric_fields = fields;
+        self
+    }
+    
+    /// Add a document's numeric field values to the numeric index
+    fn index_numbers(&mut self, doc_id: StringId, document: &Document) {
+        for field_name in &self.numeric_fields {
+            let Some(value) = document.get_number_field(field_name) else {
+                continue;
+            };
+            self.numeric_index
+                .entry(field_name.clone())
+                .or_default()
+                .entry(OrderedFloat(value))
+                .or_default()
+                .push(doc_id);
+        }
+    }
+    
+    /// Remove a document from the numeric index, looking up only its own values
+    /// when it's held in memory and checking every value otherwise
+    fn unindex_numbers(&mut self, doc_id: StringId, document: Option<&Document>) {
+        for (field_name, values) in &mut self.numeric_index {
+            match document {
+                Some(document) => {
+                    let Some(value) = document.get_number_field(field_name) else {
+                        continue;
+                    };
+                    if let Some(doc_ids) = values.get_mut(&OrderedFloat(value)) {
+                        doc_ids.retain(|&id| id != doc_id);
+                        if doc_ids.is_empty() {
+                            values.remove(&OrderedFloat(value));
+                        }
+                    }
+                }
+                None => values.retain(|_, doc_ids| {
+                    doc_ids.retain(|&id| id != doc_id);
+                    !doc_ids.is_empty()
+                }),
+            }
+        }
+    }
+    
+    /// Get the documents whose numeric field lies within the inclusive bounds, from the numeric index
+    ///
+    /// Returns `None` if the field isn't in the numeric index, or the index
+    /// misses documents, so callers can fall back to checking documents. Bounds
+    /// that are NaN or cross match nothing.
+    pub(crate) fn numeric_range_documents(&self, field_name: &str, min: Option<f64>, max: Option<f64>) -> Option<RoaringBitmap> {
+        if self.stale_numeric_index || !self.numeric_fields.iter().any(|name| name == field_name) {
+            return None;
+        }
+        
+        let (min, max) = (min.map(OrderedFloat), max.map(OrderedFloat));
+        if min.is_some_and(|min| min.is_nan() || max.is_some_and(|max| min > max)) || max.is_some_and(|max| max.is_nan()) {
+            return Some(RoaringBitmap::new());
+        }
+        
+        let bounds = (min.map_or(Bound::Unbounded, Bound::Included), max.map_or(Bound::Unbounded, Bound::Included));
+        Some(self.numeric_index
+            .get(field_name)
+            .into_iter()
+            .flat_map(|values| values.range(bounds))
+            .flat_map(|(_, doc_ids)| doc_ids.iter().map(|doc_id| doc_id.as_u32()))
+            .collect())
+    }
+    
+    /// Check whether a document is in a set returned by `numeric_range_documents`
+    pub(crate) fn document_in(&self, doc_id: &str, documents: &RoaringBitmap) -> bool {
+        self.interner.get_id(doc_id).is_some_and(|doc_id| documents.contains(doc_id.as_u32()))
+    }
+    
+    /// Check whether a query token is matched exactly as a number
+    pub(crate) fn is_exact_numeric(&self, token: &str) -> bool {
+        self.numeric_tokens && is_numeric(token)
+    }
+    
+    /// Check whether Porter stemming is enabled
+    pub fn stemming(&self) -> bool {
+        self.stemming
+    }
+    
+    /// Set the synonyms used to expand query tokens
+    ///
+    /// Terms and synonyms are passed through `tokenize`, so lookups aren't defeated
+    /// by casing or punctuation. Set this after the tokenizer and stemming settings.
+    pub fn set_synonyms(&mut self, synonyms: &HashMap<String, Vec<String>>) -> &mut Self {
+        let mut normalized: FxHashMap<String, Vec<String>> = FxHashMap::default();
+        
+        for (term, expansions) in synonyms {
+            // Only single-token terms can match a query token
+            let mut tokens = self.tokenize(term);
+            if tokens.len() != 1 {
+                continue;
+            }
+            let term = tokens.remove(0);
+            let entry = normalized.entry(term.clone()).or_default();
+            for expansion in expansions.iter().flat_map(|expansion| self.tokenize(expansion)) {
+                if expansion != term && !entry.contains(&expansion) {
+                    entry.push(expansion);
                 }
             }
+        }
+        
+        normalized.retain(|_, expansions| !expansions.is_empty());
+        self.synonyms = normalized;
+        self
+    }
+    
+    /// Get the synonyms of a normalized query token
+    pub(crate) fn synonyms_for(&self, token: &str) -> &[String] {
+        self.synonyms.get(token).map(Vec::as_slice).unwrap_or_default()
+    }
+    
+    /// Set the numeric document field whose value multiplies search scores
+    pub fn set_boost_field(&mut self, field: Option<String>) -> &mut Self {
+        self.boost_field = field;
+        self
+    }
+    
+    /// Set whether large batches are indexed in parallel shards
+    pub fn set_sharded_batches(&mut self, enabled: bool) -> &mut Self {
+        self.sharded_batches = enabled;
+        self
+    }
+    
+    /// Get the score multiplier of a document
+    ///
+    /// Read from the boost field of the in-memory document; documents without a
+    /// non-negative numeric value there, or not held in memory, get 1.0.
+    pub(crate) fn document_boost(&self, doc_id: &str) -> f64 {
+        let Some(field) = &self.boost_field else {
+            return 1.0;
+        };
+        self.get_document(doc_id)
+            .and_then(|document| document.get_number_field(field))
+            .filter(|boost| boost.is_finite() && *boost >= 0.0)
+            .unwrap_or(1.0)
+    }
+    
+    /// Split text into index tokens using the configured tokenizer, dropping stopwords
+    /// and applying stemming if enabled
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        let mut tokens = self.tokenizer.tokenize_with(text, &self.normalize);
+        tokens.retain(|token| token.chars().count() >= self.min_token_length && !self.is_stopword(token));
+        
+        if self.stemming {
+            for token in &mut tokens {
+                *token = porter_stem(token);
+            }
+        }
+        
+        tokens
+    }
+    
+    /// Get the tokenized text fields of a document, by field name
+    fn text_fields<'a>(&'a self, document: &'a Document) -> Vec<(&'a str, String)> {
+        let mut texts: Vec<(&str, String)> = if self.indexed_fields.is_empty() {
+            // If no specific fields are set, index all text fields
+            document.get_all_text_fields_named()
         } else {
             // Otherwise, only index the specified fields
-            for field_name in &self.indexed_fields {
-                if let Some(text) = document.get_text_field(field_name) {
-                    for token in extract_tokens(&text) {
-                        all_tokens.insert(token);
-                    }
+            self.indexed_fields
+                .iter()
+                .filter_map(|field_name| Some((field_name.as_str(), document.get_text_field(field_name)?)))
+                .collect()
+        };
+        texts.retain(|(field_name, _)| !self.is_keyword_field(field_name));
+        texts
+    }
+    
+    /// Check whether a document has the tokens consecutively in one of its text fields
+    ///
+    /// Documents not held in memory can't be checked, and are assumed to.
+    pub(crate) fn contains_phrase(&self, doc_id: &str, tokens: &[String]) -> bool {
+        let Some(document) = self.interner.get_id(doc_id).and_then(|doc_id| self.documents.get(&doc_id)?.as_ref()) else {
+            return true;
+        };
+        self.text_fields(document)
+            .iter()
+            .any(|(_, text)| self.tokenize(text).windows(tokens.len()).any(|window| window == tokens))
+    }
+    
+    /// Extract the unique tokens of each of a document's indexed fields with
+    /// their occurrence counts, along with the total token count including repeats
+    fn document_tokens(&self, document: &Document) -> (FieldTokens, u32) {
+        let mut length = 0u32;
+        
+        let mut field_tokens: FieldTokens = self.text_fields(document)
+            .into_iter()
+            .map(|(field_name, text)| {
+                let tokens = self.tokenize(&text);
+                length = length.saturating_add(tokens.len() as u32);
+                
+                // Numbers embedded in tokens ("test123") are findable on their own
+                let numbers: Vec<String> = if self.numeric_tokens {
+                    tokens.iter()
+                        .filter(|token| !is_numeric(token))
+                        .flat_map(|token| digit_runs(token))
+                        .filter(|number| number.len() >= self.min_token_length)
+                        .map(str::to_string)
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                let mut counts: FxHashMap<String, u32> = FxHashMap::default();
+                for token in tokens.into_iter().chain(numbers) {
+                    *counts.entry(token).or_default() += 1;
                 }
+                (field_name.to_string(), counts)
+            })
+            .collect();
+        
+        // Keyword fields contribute their whole values, which don't count towards the length
+        for field_name in &self.keyword_fields {
+            let Some(value) = document.fields.get(field_name) else {
+                continue;
+            };
+            let values: FxHashMap<String, u32> = keyword_values(value).iter().map(|value| (keyword_token(value), 1)).collect();
+            if !values.is_empty() {
+                field_tokens.push((field_name.clone(), values));
+            }
+        }
+        
+        (field_tokens, length)
+    }
+    
+    /// Get the mask bit of a field, registering the field if it's new
+    fn field_bit(&mut self, field_name: &str) -> FieldMask {
+        let position = match self.field_names.iter().position(|name| name == field_name) {
+            Some(position) => position,
+            None => {
+                self.field_names.push(field_name.to_string());
+                self.field_names.len() - 1
+            }
+        };
+        field_bit_at(position)
+    }
+    
+    /// Get the mask bit of a field, or `None` if no indexed document has the field
+    pub(crate) fn field_mask(&self, field_name: &str) -> Option<FieldMask> {
+        let position = self.field_names.iter().position(|name| name == field_name)?;
+        Some(field_bit_at(position))
+    }
+    
+    /// Add a document's tokens to the inverted and trigram indices
+    fn index_tokens(&mut self, doc_id: StringId, field_tokens: FieldTokens) {
+        // Merge the per-field token counts into one mask and count per token
+        let mut token_fields: FxHashMap<String, (FieldMask, u32)> = FxHashMap::default();
+        for (field_name, tokens) in field_tokens {
+            let bit = self.field_bit(&field_name);
+            for (token, count) in tokens {
+                let (fields, frequency) = token_fields.entry(token).or_default();
+                *fields |= bit;
+                *frequency += count;
             }
         }
         
         // Update inverted index and trigram index
-        for token in all_tokens {
+        for (token, (fields, frequency)) in token_fields {
+            let previously_interned = self.interner.get_id(&token).is_some();
             let token_id = self.interner.intern(&token);
             
-            // Add document ID to inverted index for this token
-            self.inverted_index
-                .entry(token_id)
-                .or_insert_with(SmallVec::new)
-                .push(doc_id);
+            // Add the document to the inverted index for this token
+            let postings = self.inverted_index.entry(token_id).or_default();
+            let new_token = postings.is_empty();
+            postings.insert(doc_id, fields, frequency);
             
-            // Generate trigrams for the token
-            let trigrams = generate_trigrams(&token);
+            // Tokens already in the index are already in the trigram index, and
+            // keyword values are never matched fuzzily or phonetically
+            if !new_token || token.starts_with(KEYWORD_PREFIX) {
+                continue;
+            }
             
-            // Add token to trigram index for each trigram
-            for trigram in trigrams {
+            // Add token to trigram index for each trigram. Only a token interned
+            // before can still be listed from before its postings were emptied,
+            // so the (linear) duplicate check is skipped for the rest.
+            for trigram in self.ngrams(&token) {
                 let trigram_id = self.interner.intern(&trigram);
-                self.trigram_index
-                    .entry(trigram_id)
-                    .or_insert_with(SmallVec::new)
-                    .push(token_id);
+                let token_ids = self.trigram_index.entry(trigram_id).or_insert_with(SmallVec::new);
+                if !previously_interned || !token_ids.contains(&token_id) {
+                    token_ids.push(token_id);
+                }
+            }
+            
+            if let Some(code) = self.phonetic.then(|| soundex(&token)).flatten() {
+                let code_id = self.interner.intern(&code);
+                let token_ids = self.phonetic_index.entry(code_id).or_insert_with(SmallVec::new);
+                if !previously_interned || !token_ids.contains(&token_id) {
+                    token_ids.push(token_id);
+                }
+            }
+        }
+    }
+    
+    /// Remove a document's postings from the inverted index
+    ///
+    /// A document held in memory is re-tokenized so only its own postings are
+    /// touched; otherwise, or when analysis settings changed since it may have
+    /// been indexed, every postings list is checked.
+    fn unindex_document(&mut self, doc_id: StringId, document: Option<&Document>) {
+        self.unindex_numbers(doc_id, document);
+        
+        match document {
+            Some(document) if !self.stale_analysis => {
+                let (field_tokens, _) = self.document_tokens(document);
+                let tokens: FxHashSet<String> = field_tokens.into_iter().flat_map(|(_, tokens)| tokens.into_keys()).collect();
+                for token in tokens {
+                    let Some(token_id) = self.interner.get_id(&token) else {
+                        continue;
+                    };
+                    if let Some(postings) = self.inverted_index.get_mut(&token_id) {
+                        postings.remove(doc_id);
+                        if postings.is_empty() {
+                            self.inverted_index.remove(&token_id);
+                        }
+                    }
+                }
+            }
+            _ => {
+                // Clean up empty entries in inverted index in the same pass
+                self.inverted_index.retain(|_, postings| {
+                    postings.remove(doc_id);
+                    !postings.is_empty()
+                });
             }
         }
+    }
+    
+    /// Get the total number of indexed tokens of a document
+    ///
+    /// Returns `None` for unknown documents and for indexes saved before lengths were recorded.
+    pub fn document_length(&self, doc_id: &str) -> Option<u32> {
+        let doc_id = self.interner.get_id(doc_id)?;
+        self.document_lengths.get(&doc_id).copied()
+    }
+    
+    /// Add a document to the index
+    pub fn add_document(&mut self, document: Document) -> Result<()> {
+        let doc_id_str = document.id.clone();
+        let doc_id = self.interner.intern(&doc_id_str);
+        
+        // Replace any previous version rather than adding to its postings
+        let replaced = self.documents.remove(&doc_id);
+        if let Some(previous) = &replaced {
+            self.unindex_document(doc_id, previous.as_ref());
+        }
+        self.bump_version(&doc_id_str, replaced.is_some());
         
-        // Store the document
-        self.documents.insert(doc_id, document);
+        // Extract tokens from indexed fields
+        let (field_tokens, length) = self.document_tokens(&document);
+        self.document_lengths.insert(doc_id, length);
+        self.index_tokens(doc_id, field_tokens);
+        self.index_numbers(doc_id, &document);
+        
+        // Store the document, replacing any expiry from a previous version
+        self.expirations.remove(&doc_id_str);
+        self.documents.insert(doc_id, self.retain_documents.then_some(document));
         
         Ok(())
     }
     
     /// Add multiple documents to the index efficiently
+    ///
+    /// With sharded batches enabled, large batches are split into one shard
+    /// per worker thread. Each shard is indexed in parallel with its own
+    /// interner, then the shards are merged pairwise in parallel and the result
+    /// merged into this index, so the postings updates aren't serialized
+    /// through a single interner. Merging adds its own cost, so this is off by
+    /// default.
     pub fn add_documents_batch(&mut self, documents: Vec<Document>) -> Result<()> {
+        let shards = if self.sharded_batches {
+            rayon::current_num_threads().min(documents.len() / MIN_SHARD_DOCUMENTS)
+        } else {
+            1
+        };
+        if shards > 1 {
+            self.add_documents_sharded(documents, shards)
+        } else {
+            self.add_documents_sequential(documents)
+        }
+    }
+    
+    /// Index a batch in `shards` parallel partial indices and merge them into this one
+    fn add_documents_sharded(&mut self, documents: Vec<Document>, shards: usize) -> Result<()> {
+        let chunk_size = documents.len().div_ceil(shards);
+        let mut documents = documents.into_iter();
+        let chunks: Vec<Vec<Document>> = (0..shards)
+            .map(|_| documents.by_ref().take(chunk_size).collect())
+            .collect();
+        
+        // Later shards are merged over earlier ones, so the last copy of a
+        // repeated document ID wins, as with sequential indexing
+        let merged = chunks
+            .into_par_iter()
+            .map(|chunk| -> Result<Index> {
+                let mut shard = self.empty_shard();
+                shard.add_documents_sequential(chunk)?;
+                Ok(shard)
+            })
+            .try_reduce_with(|mut left, right| {
+                left.merge(right)?;
+                Ok(left)
+            });
+        
+        let Some(shard) = merged else {
+            return Ok(());
+        };
+        let shard = shard?;
+        
+        // An index that has never interned anything can take the shard's
+        // structures as they are, skipping a full re-interning pass
+        if self.interner.is_empty() {
+            self.documents = shard.documents;
+            self.inverted_index = shard.inverted_index;
+            self.field_names = shard.field_names;
+            self.trigram_index = shard.trigram_index;
+            self.phonetic_index = shard.phonetic_index;
+            self.document_lengths = shard.document_lengths;
+            self.interner = shard.interner;
+            self.versions = shard.versions;
+            self.numeric_index = shard.numeric_index;
+            return Ok(());
+        }
+        
+        self.merge(shard)
+    }
+    
+    /// Create an empty index with the same analysis settings, to index a shard of a batch
+    fn empty_shard(&self) -> Index {
+        let mut shard = Index::new();
+        shard.retain_documents = self.retain_documents;
+        shard.ngram_size = self.ngram_size;
+        shard.indexed_fields = self.indexed_fields.clone();
+        shard.tokenizer = self.tokenizer.clone();
+        shard.normalize = self.normalize;
+        shard.stopwords = self.stopwords.clone();
+        shard.stemming = self.stemming;
+        shard.min_token_length = self.min_token_length;
+        shard.phonetic = self.phonetic;
+        shard.numeric_tokens = self.numeric_tokens;
+        shard.keyword_fields = self.keyword_fields.clone();
+        shard.numeric_fields = self.numeric_fields.clone();
+        shard
+    }
+    
+    /// Add a batch, tokenizing in parallel but updating the indices sequentially
+    fn add_documents_sequential(&mut self, documents: Vec<Document>) -> Result<()> {
         // Pre-allocate capacity for better performance
         let estimated_tokens = documents.len() * 10; // rough estimate
         self.inverted_index.reserve(estimated_tokens);
@@ -110,63 +922,25 @@ impl Index {
         // Process documents in parallel to extract tokens
         let token_data: Vec<_> = documents
             .par_iter()
-            .map(|document| {
-                let mut all_tokens = FxHashSet::default();
-                
-                if self.indexed_fields.is_empty() {
-                    // If no specific fields are set, index all text fields
-                    for text in document.get_all_text_fields() {
-                        for token in extract_tokens(&text) {
-                            all_tokens.insert(token);
-                        }
-                    }
-                } else {
-                    // Otherwise, only index the specified fields
-                    for field_name in &self.indexed_fields {
-                        if let Some(text) = document.get_text_field(field_name) {
-                            for token in extract_tokens(&text) {
-                                all_tokens.insert(token);
-                            }
-                        }
-                    }
-                }
-                
-                (document.id.clone(), all_tokens)
-            })
+            .map(|document| (document.id.clone(), self.document_tokens(document)))
             .collect();
         
-        // Now sequentially update the indices to avoid conflicts
-        for (doc_id_str, all_tokens) in token_data {
+        // Now sequentially update the indices to avoid conflicts, replacing
+        // previous versions (including earlier ones in the same batch)
+        for (document, (doc_id_str, (field_tokens, length))) in documents.into_iter().zip(token_data) {
             let doc_id = self.interner.intern(&doc_id_str);
-            
-            // Update inverted index and trigram index
-            for token in all_tokens {
-                let token_id = self.interner.intern(&token);
-                
-                // Add document ID to inverted index for this token
-                self.inverted_index
-                    .entry(token_id)
-                    .or_insert_with(SmallVec::new)
-                    .push(doc_id);
-                
-                // Generate trigrams for the token
-                let trigrams = generate_trigrams(&token);
-                
-                // Add token to trigram index for each trigram
-                for trigram in trigrams {
-                    let trigram_id = self.interner.intern(&trigram);
-                    self.trigram_index
-                        .entry(trigram_id)
-                        .or_insert_with(SmallVec::new)
-                        .push(token_id);
-                }
+            let replaced = self.documents.remove(&doc_id);
+            if let Some(previous) = &replaced {
+                self.unindex_document(doc_id, previous.as_ref());
             }
-        }
-        
-        // Store all documents
-        for document in documents {
-            let doc_id = self.interner.intern(&document.id);
-            self.documents.insert(doc_id, document);
+            self.bump_version(&doc_id_str, replaced.is_some());
+            
+            self.document_lengths.insert(doc_id, length);
+            self.index_tokens(doc_id, field_tokens);
+            self.index_numbers(doc_id, &document);
+            
+            self.expirations.remove(&doc_id_str);
+            self.documents.insert(doc_id, self.retain_documents.then_some(document));
         }
         
         Ok(())
@@ -184,16 +958,14 @@ impl Index {
             return Err(TigerCacheError::DocumentNotFound(doc_id.to_string()));
         }
         
-        // Remove document ID from inverted index
-        for (_, doc_ids) in self.inverted_index.iter_mut() {
-            doc_ids.retain(|id| *id != doc_id_interned);
-        }
-        
         // Remove the document
-        self.documents.remove(&doc_id_interned);
+        let document = self.documents.remove(&doc_id_interned).flatten();
+        self.document_lengths.remove(&doc_id_interned);
+        self.expirations.remove(doc_id);
+        self.versions.remove(doc_id);
         
-        // Clean up empty entries in inverted index
-        self.inverted_index.retain(|_, doc_ids| !doc_ids.is_empty());
+        // Remove document ID from inverted index
+        self.unindex_document(doc_id_interned, document.as_ref());
         
         // Clean up trigram index (more complex, would need to track token usage)
         // For simplicity, we'll leave this for now and just clean up during reindexing
@@ -202,9 +974,105 @@ impl Index {
     }
     
     /// Get a document by ID
+    ///
+    /// Returns `None` for documents that aren't held in memory or have expired.
     pub fn get_document(&self, doc_id: &str) -> Option<&Document> {
+        if self.is_expired(doc_id) {
+            return None;
+        }
         let doc_id_interned = self.interner.get_id(doc_id)?;
-        self.documents.get(&doc_id_interned)
+        self.documents.get(&doc_id_interned)?.as_ref()
+    }
+    
+    /// Check whether a document is in the index, whether or not it's held in memory
+    ///
+    /// Expired documents aren't reported.
+    pub fn contains_document(&self, doc_id: &str) -> bool {
+        !self.is_expired(doc_id)
+            && self.interner.get_id(doc_id).is_some_and(|doc_id| self.documents.contains_key(&doc_id))
+    }
+    
+    /// Get a document's version, which starts at 1 and is incremented every time the document is replaced
+    ///
+    /// Returns `None` for documents not in the index or expired. Removing a
+    /// document discards its version, so re-adding it starts again at 1.
+    pub fn document_version(&self, doc_id: &str) -> Option<u64> {
+        // Indexes saved before versions were recorded start every document at 1
+        self.contains_document(doc_id)
+            .then(|| self.versions.get(doc_id).copied().unwrap_or(1))
+    }
+    
+    /// Set the version of an indexed document, as restored from storage
+    ///
+    /// Unknown documents are ignored.
+    pub(crate) fn set_document_version(&mut self, doc_id: &str, version: u64) {
+        if self.interner.get_id(doc_id).is_some_and(|id| self.documents.contains_key(&id)) {
+            self.versions.insert(doc_id.to_string(), version);
+        }
+    }
+    
+    /// Advance the version of a document being added, continuing from the one it replaces
+    fn bump_version(&mut self, doc_id: &str, replaced: bool) {
+        let previous = if replaced { self.versions.get(doc_id).copied().unwrap_or(1) } else { 0 };
+        self.versions.insert(doc_id.to_string(), previous + 1);
+    }
+    
+    /// Keep a copy of an already indexed document in memory, without reindexing it
+    ///
+    /// Used to hold on to documents loaded from storage. Unknown documents are ignored.
+    pub(crate) fn cache_document(&mut self, document: Document) {
+        if let Some(doc_id) = self.interner.get_id(&document.id) {
+            if let Some(slot) = self.documents.get_mut(&doc_id) {
+                *slot = Some(document);
+            }
+        }
+    }
+    
+    /// Make an indexed document expire at the given time
+    ///
+    /// Expired documents are skipped by searches and `get_document` until they
+    /// are removed. Re-adding the document clears the expiry.
+    pub fn set_expiry(&mut self, doc_id: &str, expires_at: SystemTime) -> Result<()> {
+        let indexed = self.interner.get_id(doc_id).is_some_and(|id| self.documents.contains_key(&id));
+        if !indexed {
+            return Err(TigerCacheError::DocumentNotFound(doc_id.to_string()));
+        }
+        
+        self.expirations.insert(doc_id.to_string(), expires_at);
+        Ok(())
+    }
+    
+    /// Check whether a document has passed its expiry time
+    pub fn is_expired(&self, doc_id: &str) -> bool {
+        !self.expirations.is_empty() && self.is_expired_at(doc_id, SystemTime::now())
+    }
+    
+    /// Check a document's expiry against a fixed time, so a search can reuse one clock read
+    pub(crate) fn is_expired_at(&self, doc_id: &str, now: SystemTime) -> bool {
+        self.expirations.get(doc_id).is_some_and(|&expires_at| expires_at <= now)
+    }
+    
+    /// Whether any document has an expiry time
+    pub(crate) fn has_expirations(&self) -> bool {
+        !self.expirations.is_empty()
+    }
+    
+    /// Get the IDs of all documents that have expired
+    pub fn expired_document_ids(&self) -> Vec<String> {
+        let now = SystemTime::now();
+        self.expirations
+            .iter()
+            .filter(|(_, &expires_at)| expires_at <= now)
+            .map(|(doc_id, _)| doc_id.clone())
+            .collect()
+    }
+    
+    /// Get the IDs of all documents in the index
+    pub(crate) fn document_ids(&self) -> Vec<String> {
+        self.documents
+            .keys()
+            .filter_map(|&doc_id| self.interner.get(doc_id).map(|s| s.to_string()))
+            .collect()
     }
     
     /// Get the number of documents in the index
@@ -212,23 +1080,128 @@ impl Index {
         self.documents.len()
     }
     
+    /// Get counts of the documents, tokens, n-grams, and interned strings in the index
+    pub fn stats(&self) -> IndexStats {
+        IndexStats {
+            document_count: self.documents.len(),
+            unique_tokens: self.inverted_index.len(),
+            unique_trigrams: self.trigram_index.len(),
+            interned_strings: self.interner.len(),
+        }
+    }
+    
+    /// Check that the index's structures agree with each other, without changing anything
+    ///
+    /// Verifies that postings and numeric index entries only name documents in
+    /// the index, that trigram and phonetic lists only name tokens with postings,
+    /// that every key resolves through the interner, and that every document has
+    /// a recorded length. Returns the problems found, sorted, or an empty list for
+    /// a consistent index.
+    pub fn verify(&self) -> Vec<InconsistencyReport> {
+        let mut reports = Vec::new();
+        
+        // Strings of uninterned IDs are shown by number, and reported once
+        let name = |id: StringId| -> String {
+            self.interner.get(id).map_or_else(|| format!("#{}", id.as_u32()), str::to_string)
+        };
+        let keys: FxHashSet<StringId> = self.documents.keys()
+            .chain(self.inverted_index.keys())
+            .chain(self.trigram_index.keys())
+            .chain(self.phonetic_index.keys())
+            .copied()
+            .collect();
+        for id in keys {
+            if self.interner.get(id).is_none() {
+                reports.push(InconsistencyReport::UninternedId { id: id.as_u32() });
+            }
+        }
+        
+        if self.documents.len() != self.document_lengths.len()
+            || self.document_lengths.keys().any(|doc_id| !self.documents.contains_key(doc_id))
+        {
+            reports.push(InconsistencyReport::DocumentCountMismatch {
+                documents: self.documents.len(),
+                lengths: self.document_lengths.len(),
+            });
+        }
+        
+        for (&token_id, postings) in &self.inverted_index {
+            if postings.is_empty() {
+                reports.push(InconsistencyReport::EmptyPostings { token: name(token_id) });
+            }
+            for ordinal in &postings.docs_in_fields(FieldMask::MAX) {
+                let doc_id = StringId::new(ordinal);
+                if !self.documents.contains_key(&doc_id) {
+                    reports.push(InconsistencyReport::DanglingPosting { token: name(token_id), doc_id: name(doc_id) });
+                }
+            }
+        }
+        
+        for (&ngram_id, token_ids) in &self.trigram_index {
+            for &token_id in token_ids.iter().filter(|token_id| !self.inverted_index.contains_key(token_id)) {
+                reports.push(InconsistencyReport::DanglingTrigram { ngram: name(ngram_id), token: name(token_id) });
+            }
+        }
+        for (&code_id, token_ids) in &self.phonetic_index {
+            for &token_id in token_ids.iter().filter(|token_id| !self.inverted_index.contains_key(token_id)) {
+                reports.push(InconsistencyReport::DanglingPhonetic { code: name(code_id), token: name(token_id) });
+            }
+        }
+        
+        for (field_name, values) in &self.numeric_index {
+            for &doc_id in values.values().flatten().filter(|doc_id| !self.documents.contains_key(doc_id)) {
+                reports.push(InconsistencyReport::DanglingNumericEntry { field: field_name.clone(), doc_id: name(doc_id) });
+            }
+        }
+        
+        reports.sort();
+        reports
+    }
+    
+    /// Estimate the memory used by the interner and the search indices in bytes
+    ///
+    /// Covers interned strings, the postings of the inverted and trigram
+    /// indices, and the numeric index; documents held in memory aren't included.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let inverted: usize = self.inverted_index
+            .values()
+            .map(|postings| std::mem::size_of::<StringId>() + postings.estimated_memory_bytes())
+            .sum();
+        let trigrams: usize = self.trigram_index
+            .values()
+            .chain(self.phonetic_index.values())
+            .map(|token_ids| std::mem::size_of::<StringId>() + estimate_smallvec_size(token_ids))
+            .sum();
+        let numbers: usize = self.numeric_index
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(|doc_ids| std::mem::size_of::<OrderedFloat<f64>>() + doc_ids.capacity() * std::mem::size_of::<StringId>())
+            .sum();
+        
+        self.interner.estimated_memory_bytes() + inverted + trigrams + numbers
+    }
+    
     /// Find candidate tokens for a search query using trigram matching
     pub fn find_candidate_tokens(&self, query: &str) -> FxHashSet<String> {
-        let normalized_query = normalize_text(query);
-        let query_tokens = extract_tokens(&normalized_query);
         let mut candidate_tokens = FxHashSet::default();
         
-        for query_token in query_tokens {
-            let query_trigrams = generate_trigrams(&query_token);
-            
-            // Find tokens that share at least one trigram with the query token
-            for trigram in query_trigrams {
-                if let Some(trigram_id) = self.interner.get_id(&trigram) {
-                    if let Some(token_ids) = self.trigram_index.get(&trigram_id) {
-                        for &token_id in token_ids {
-                            if let Some(token) = self.interner.get(token_id) {
-                                candidate_tokens.insert(token.to_string());
-                            }
+        for query_token in self.tokenize(query) {
+            candidate_tokens.extend(self.candidate_tokens_for(&query_token));
+        }
+        
+        candidate_tokens
+    }
+    
+    /// Find indexed tokens sharing at least one trigram with an already tokenized query token
+    pub(crate) fn candidate_tokens_for(&self, query_token: &str) -> FxHashSet<String> {
+        let mut candidate_tokens = FxHashSet::default();
+        
+        for trigram in self.ngrams(query_token) {
+            if let Some(trigram_id) = self.interner.get_id(&trigram) {
+                if let Some(token_ids) = self.trigram_index.get(&trigram_id) {
+                    for &token_id in token_ids {
+                        if let Some(token) = self.interner.get(token_id) {
+                            candidate_tokens.insert(token.to_string());
                         }
                     }
                 }
@@ -238,27 +1211,340 @@ impl Index {
         candidate_tokens
     }
     
+    /// Find indexed tokens with the same Soundex code as an already tokenized query token
+    ///
+    /// Always empty unless the phonetic index is enabled.
+    pub(crate) fn phonetic_tokens_for(&self, query_token: &str) -> FxHashSet<String> {
+        soundex(query_token)
+            .and_then(|code| self.interner.get_id(&code))
+            .and_then(|code_id| self.phonetic_index.get(&code_id))
+            .into_iter()
+            .flatten()
+            .filter_map(|&token_id| self.interner.get(token_id).map(|token| token.to_string()))
+            .collect()
+    }
+    
+    /// Get the indexed tokens starting with a prefix, in sorted order
+    ///
+    /// This scans every indexed token, which is fine for occasional wildcard
+    /// queries but not something to call per candidate.
+    pub fn tokens_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut tokens: Vec<String> = self.inverted_index
+            .keys()
+            .filter_map(|&token_id| self.interner.get(token_id))
+            .filter(|token| token.starts_with(prefix))
+            .map(|token| token.to_string())
+            .collect();
+        tokens.sort_unstable();
+        tokens
+    }
+    
     /// Get document IDs containing a specific token
     pub fn get_documents_for_token(&self, token: &str) -> Vec<String> {
+        self.get_documents_for_token_in_fields(token, FieldMask::MAX)
+    }
+    
+    /// Get document IDs containing a token in any of the fields in `fields`,
+    /// with the number of times each contains it
+    pub(crate) fn get_documents_with_frequency(&self, token: &str, fields: FieldMask) -> Vec<(String, u32)> {
+        let Some(postings) = self.interner.get_id(token).and_then(|token_id| self.inverted_index.get(&token_id)) else {
+            return Vec::new();
+        };
+        postings.docs_in_fields(fields)
+            .iter()
+            .filter_map(|ordinal| {
+                let doc_id = StringId::new(ordinal);
+                Some((self.interner.get(doc_id)?.to_string(), postings.frequency(doc_id)))
+            })
+            .collect()
+    }
+    
+    /// Get document IDs containing a token in any of the fields in `fields`
+    pub(crate) fn get_documents_for_token_in_fields(&self, token: &str, fields: FieldMask) -> Vec<String> {
         if let Some(token_id) = self.interner.get_id(token) {
-            if let Some(doc_ids) = self.inverted_index.get(&token_id) {
-                return doc_ids.iter()
-                    .filter_map(|&doc_id| self.interner.get(doc_id).map(|s| s.to_string()))
+            if let Some(postings) = self.inverted_index.get(&token_id) {
+                return postings.docs_in_fields(fields)
+                    .iter()
+                    .filter_map(|ordinal| self.interner.get(StringId::new(ordinal)).map(|s| s.to_string()))
                     .collect();
             }
         }
         Vec::new()
     }
     
+    /// Merge another index into this one
+    ///
+    /// The other index's strings are re-interned here and its documents,
+    /// postings, and trigram lists remapped to this index's IDs, so nothing is
+    /// re-tokenized. Documents in both indices are replaced by the other
+    /// index's version. Both indices must use the same analysis settings.
+    pub fn merge(&mut self, other: Index) -> Result<()> {
+        let mismatch = if self.ngram_size != other.ngram_size {
+            Some("n-gram sizes")
+        } else if self.tokenizer_kind() != other.tokenizer_kind() {
+            Some("tokenizers")
+        } else if self.normalize != other.normalize {
+            Some("normalization options")
+        } else if self.stemming != other.stemming {
+            Some("stemming settings")
+        } else if self.stopwords != other.stopwords {
+            Some("stopwords")
+        } else if self.min_token_length.max(1) != other.min_token_length.max(1) {
+            Some("minimum token lengths")
+        } else if self.phonetic != other.phonetic {
+            Some("phonetic settings")
+        } else if self.numeric_tokens != other.numeric_tokens {
+            Some("numeric token settings")
+        } else if self.keyword_fields != other.keyword_fields {
+            Some("keyword fields")
+        } else if self.numeric_fields != other.numeric_fields {
+            Some("numeric fields")
+        } else {
+            None
+        };
+        if let Some(setting) = mismatch {
+            return Err(TigerCacheError::IncompatibleIndex(format!("indices use different {setting}")));
+        }
+        
+        // Map the other index's documents, dropping any previous versions here.
+        // Version numbers carry on from the replaced document's, counting each
+        // replacement made in the other index.
+        let mut doc_ids: FxHashMap<StringId, StringId> = FxHashMap::default();
+        for &other_doc_id in other.documents.keys() {
+            let doc_id_str = other.interner.get(other_doc_id).unwrap_or_default();
+            let doc_id = self.interner.intern(doc_id_str);
+            let mut version = other.versions.get(doc_id_str).copied().unwrap_or(1);
+            if let Some(previous) = self.documents.remove(&doc_id) {
+                self.unindex_document(doc_id, previous.as_ref());
+                self.document_lengths.remove(&doc_id);
+                self.expirations.remove(doc_id_str);
+                version += self.versions.get(doc_id_str).copied().unwrap_or(1);
+            }
+            self.versions.insert(doc_id_str.to_string(), version);
+            doc_ids.insert(other_doc_id, doc_id);
+        }
+        
+        // Field positions differ between the indices, except for the shared overflow bit
+        let field_positions: Vec<u8> = other.field_names
+            .iter()
+            .map(|name| self.field_bit(name).trailing_zeros() as u8)
+            .collect();
+        let overflow_position = OVERFLOW_FIELD_BIT.trailing_zeros() as u8;
+        let map_position = |position: u8| match position {
+            position if position == overflow_position => overflow_position,
+            position => field_positions[position as usize],
+        };
+        // An index loaded in an inconsistent state may name documents it
+        // doesn't hold (see `verify`); those entries are left out
+        let map_doc = |ordinal: u32| doc_ids.get(&StringId::new(ordinal)).map(|doc_id| doc_id.as_u32());
+        
+        // Union the postings
+        let mut token_ids: FxHashMap<StringId, StringId> = FxHashMap::default();
+        for (&other_token_id, other_postings) in &other.inverted_index {
+            let token_id = self.interner.intern(other.interner.get(other_token_id).unwrap_or_default());
+            let postings = self.inverted_index.entry(token_id).or_default();
+            postings.union_remapped(other_postings, map_doc, map_position);
+            if postings.is_empty() {
+                self.inverted_index.remove(&token_id);
+            } else {
+                token_ids.insert(other_token_id, token_id);
+            }
+        }
+        
+        // Union the trigram and phonetic lists, leaving out tokens the other index no longer contains
+        let lists = [(&other.trigram_index, &mut self.trigram_index), (&other.phonetic_index, &mut self.phonetic_index)];
+        for (other_index, index) in lists {
+            for (&other_key_id, other_token_ids) in other_index {
+                let live: Vec<StringId> = other_token_ids.iter().filter_map(|id| token_ids.get(id).copied()).collect();
+                if live.is_empty() {
+                    continue;
+                }
+                
+                let key_id = self.interner.intern(other.interner.get(other_key_id).unwrap_or_default());
+                let token_ids = index.entry(key_id).or_insert_with(SmallVec::new);
+                let existing: FxHashSet<StringId> = token_ids.iter().copied().collect();
+                token_ids.extend(live.into_iter().filter(|id| !existing.contains(id)));
+            }
+        }
+        
+        let retain_documents = self.retain_documents;
+        for (other_doc_id, document) in other.documents {
+            let doc_id = doc_ids[&other_doc_id];
+            if let Some(&length) = other.document_lengths.get(&other_doc_id) {
+                self.document_lengths.insert(doc_id, length);
+            }
+            self.documents.insert(doc_id, document.filter(|_| retain_documents));
+        }
+        self.expirations.extend(other.expirations);
+        
+        for (field_name, other_values) in other.numeric_index {
+            let values = self.numeric_index.entry(field_name).or_default();
+            for (value, other_doc_ids) in other_values {
+                let mapped: Vec<StringId> = other_doc_ids.iter().filter_map(|doc_id| doc_ids.get(doc_id).copied()).collect();
+                if !mapped.is_empty() {
+                    values.entry(value).or_default().extend(mapped);
+                }
+            }
+        }
+        
+        // The other index's documents were tokenized with its own indexed fields
+        self.stale_analysis |= other.stale_analysis || other.indexed_fields != self.indexed_fields;
+        self.stale_numeric_index |= other.stale_numeric_index;
+        
+        Ok(())
+    }
+    
+    /// Drop interned strings no longer referenced by the index and renumber the rest
+    ///
+    /// Removing documents leaves their IDs, tokens, and n-grams interned, and
+    /// tokens listed in the trigram index. Compacting keeps only what the
+    /// remaining documents reference, so long-running indexes with churn
+    /// don't grow without bound. Postings, lengths, and numeric entries naming
+    /// documents that aren't in the index are dropped too. Returns the number
+    /// of strings dropped.
+    pub fn compact(&mut self) -> usize {
+        // Postings, lengths and numeric entries naming documents that aren't in
+        // the index (see `verify`) are dropped
+        let documents = &self.documents;
+        self.document_lengths.retain(|doc_id, _| documents.contains_key(doc_id));
+        for postings in self.inverted_index.values_mut() {
+            postings.retain(|doc_id| documents.contains_key(&doc_id));
+        }
+        self.inverted_index.retain(|_, postings| !postings.is_empty());
+        for values in self.numeric_index.values_mut() {
+            values.retain(|_, doc_ids| {
+                doc_ids.retain(|doc_id| documents.contains_key(doc_id));
+                !doc_ids.is_empty()
+            });
+        }
+        
+        // Trigram and phonetic lists may still name tokens whose postings were emptied
+        let inverted_index = &self.inverted_index;
+        for index in [&mut self.trigram_index, &mut self.phonetic_index] {
+            index.retain(|_, token_ids| {
+                token_ids.retain(|token_id| inverted_index.contains_key(token_id));
+                !token_ids.is_empty()
+            });
+        }
+        
+        let live_ids: FxHashSet<StringId> = self.documents.keys()
+            .chain(self.inverted_index.keys())
+            .chain(self.trigram_index.keys())
+            .chain(self.phonetic_index.keys())
+            .copied()
+            .collect();
+        let before = self.interner.len();
+        let remap = self.interner.gc(&live_ids);
+        let map = |id: StringId| remap.get(&id).copied();
+        
+        self.documents = self.documents.drain().filter_map(|(id, document)| Some((map(id)?, document))).collect();
+        self.document_lengths = self.document_lengths.drain().filter_map(|(id, length)| Some((map(id)?, length))).collect();
+        for doc_ids in self.numeric_index.values_mut().flat_map(BTreeMap::values_mut) {
+            *doc_ids = doc_ids.iter().filter_map(|&doc_id| map(doc_id)).collect();
+        }
+        self.inverted_index = self.inverted_index
+            .drain()
+            .filter_map(|(token_id, postings)| Some((map(token_id)?, postings.remapped(map))))
+            .collect();
+        self.trigram_index = self.trigram_index
+            .drain()
+            .filter_map(|(trigram_id, token_ids)| Some((map(trigram_id)?, token_ids.into_iter().filter_map(map).collect())))
+            .collect();
+        self.phonetic_index = self.phonetic_index
+            .drain()
+            .filter_map(|(code_id, token_ids)| Some((map(code_id)?, token_ids.into_iter().filter_map(map).collect())))
+            .collect();
+        
+        before - self.interner.len()
+    }
+    
+    /// Rebuild the inverted and trigram indices from the stored documents
+    ///
+    /// Needed after changing analysis settings such as stemming, since existing
+    /// tokens were produced with the old settings. Fails if some documents aren't
+    /// held in memory; use `reindex_documents` to supply them instead.
+    pub fn reindex(&mut self) -> Result<()> {
+        if let Some((&doc_id, _)) = self.documents.iter().find(|(_, document)| document.is_none()) {
+            let doc_id = self.interner.get(doc_id).unwrap_or_default().to_string();
+            return Err(TigerCacheError::InvalidDocument(format!(
+                "document {doc_id} is not held in memory and can't be reindexed"
+            )));
+        }
+        
+        let documents: Vec<Document> = self.documents.drain().filter_map(|(_, document)| document).collect();
+        self.reindex_documents(documents)
+    }
+    
+    /// Replace the index contents with the given documents, indexed with the current settings
+    pub fn reindex_documents(&mut self, documents: Vec<Document>) -> Result<()> {
+        // Expiry times and versions describe the documents, not the derived indices, so they survive
+        let expirations = std::mem::take(&mut self.expirations);
+        let versions = std::mem::take(&mut self.versions);
+        self.clear();
+        self.add_documents_batch(documents)?;
+        self.expirations = expirations;
+        self.versions = versions;
+        Ok(())
+    }
+    
     /// Clear the index
     pub fn clear(&mut self) {
         self.documents.clear();
         self.inverted_index.clear();
         self.trigram_index.clear();
+        self.phonetic_index.clear();
+        self.document_lengths.clear();
+        self.field_names.clear();
         self.interner.clear();
+        self.expirations.clear();
+        self.versions.clear();
+        self.numeric_index.clear();
+        self.stale_analysis = false;
+        self.stale_numeric_index = false;
     }
 }
 
+/// Get the inverted index key of a keyword field value
+fn keyword_token(value: &str) -> String {
+    format!("{KEYWORD_PREFIX}{value}")
+}
+
+/// Get the values a keyword field is indexed under: strings as they are,
+/// numbers and booleans as JSON text, and each such element of an array
+fn keyword_values(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(s) => vec![s.clone()],
+        serde_json::Value::Number(_) | serde_json::Value::Bool(_) => vec![value.to_string()],
+        serde_json::Value::Array(values) => values
+            .iter()
+            .filter(|value| !value.is_array())
+            .flat_map(keyword_values)
+            .collect(),
+        serde_json::Value::Null | serde_json::Value::Object(_) => Vec::new(),
+    }
+}
+
+/// Check whether a token consists only of ASCII digits
+fn is_numeric(token: &str) -> bool {
+    !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Get the maximal runs of ASCII digits in a token ("a12b345" gives "12" and "345")
+fn digit_runs(token: &str) -> impl Iterator<Item = &str> {
+    token.split(|c: char| !c.is_ascii_digit()).filter(|run| !run.is_empty())
+}
+
+/// Estimate the size of a SmallVec in bytes
+fn estimate_smallvec_size<A: smallvec::Array>(vec: &SmallVec<A>) -> usize {
+    // Base size for the SmallVec struct
+    let mut size = std::mem::size_of::<SmallVec<A>>();
+    
+    // Add the size of the elements
+    size += vec.len() * std::mem::size_of::<A::Item>();
+    
+    size
+}
+
 impl Default for Index {
     fn default() -> Self {
         Self::new()
@@ -268,6 +1554,7 @@ impl Default for Index {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::SearchOptions;
     
     fn create_test_document(id: &str) -> Document {
         let mut doc = Document::new(id);
@@ -297,6 +1584,71 @@ mod tests {
         assert!(est_id.is_some());
     }
     
+    #[test]
+    fn test_postings_record_fields() {
+        let mut index = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Red Widget").add_field("description", "A red gadget");
+        index.add_document(doc).unwrap();
+        
+        let title = index.field_mask("title").unwrap();
+        let description = index.field_mask("description").unwrap();
+        assert_ne!(title, description);
+        assert!(index.field_mask("color").is_none());
+        
+        assert_eq!(index.get_documents_for_token_in_fields("widget", title), vec!["doc1".to_string()]);
+        assert!(index.get_documents_for_token_in_fields("widget", description).is_empty());
+        assert_eq!(index.get_documents_for_token_in_fields("red", description), vec!["doc1".to_string()]);
+        
+        // A token in several fields is still a single posting
+        let red = index.interner.get_id("red").unwrap();
+        assert_eq!(index.inverted_index[&red].docs_in_fields(FieldMask::MAX).len(), 1);
+        assert_eq!(index.inverted_index[&red].docs_in_fields(title).len(), 1);
+        assert_eq!(index.inverted_index[&red].docs_in_fields(description).len(), 1);
+    }
+    
+    #[test]
+    fn test_stats() {
+        let mut index = Index::new();
+        assert_eq!(index.stats().interned_strings, 0);
+        
+        let mut doc1 = Document::new("d1");
+        doc1.add_field("title", "cat");
+        let mut doc2 = Document::new("d2");
+        doc2.add_field("title", "car cat");
+        index.add_document(doc1).unwrap();
+        index.add_document(doc2).unwrap();
+        
+        // "cat" and "car" give $$c, $ca, cat, at$, car, ar$; the n-grams "cat"
+        // and "car" share interned strings with the tokens
+        assert_eq!(index.stats(), IndexStats {
+            document_count: 2,
+            unique_tokens: 2,
+            unique_trigrams: 6,
+            interned_strings: 8,
+        });
+    }
+    
+    #[test]
+    fn test_estimated_memory_bytes() {
+        let mut index = Index::new();
+        let mut previous = index.estimated_memory_bytes();
+        assert_eq!(previous, 0);
+        
+        for (i, title) in ["Apple iPhone", "Samsung Galaxy", "Apple Watch", "Google Pixel"].iter().enumerate() {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", *title);
+            index.add_document(doc).unwrap();
+            
+            let estimate = index.estimated_memory_bytes();
+            assert!(estimate > previous);
+            previous = estimate;
+        }
+        
+        index.clear();
+        assert_eq!(index.estimated_memory_bytes(), 0);
+    }
+    
     #[test]
     fn test_remove_document() {
         let mut index = Index::new();
@@ -310,13 +1662,474 @@ mod tests {
         
         // Check that document was removed from inverted index
         let doc1_id = index.interner.get_id("doc1");
-        for (_, doc_ids) in &index.inverted_index {
+        for (_, postings) in &index.inverted_index {
             if let Some(id) = doc1_id {
-                assert!(!doc_ids.contains(&id));
+                assert!(!postings.docs_in_fields(FieldMask::MAX).contains(id.as_u32()));
             }
         }
     }
     
+    #[test]
+    fn test_remove_document_after_settings_change() {
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Rugged laptop").add_field("sku", "AB-1");
+        let changes: [fn(&mut Index); 4] = [
+            |index| { index.set_indexed_fields(vec!["sku".to_string()]); },
+            |index| { index.set_min_token_length(10); },
+            |index| { index.set_keyword_fields(vec!["title".to_string()]); },
+            |index| { index.set_stemming(true); },
+        ];
+        
+        for change in changes {
+            let mut index = Index::new();
+            index.add_document(doc.clone()).unwrap();
+            change(&mut index);
+            
+            // Postings made with the old settings are removed even though
+            // re-tokenizing the document no longer produces them
+            index.remove_document("doc1").unwrap();
+            assert!(index.get_documents_for_token("rugged").is_empty());
+            assert!(index.get_documents_for_token("laptop").is_empty());
+            assert!(index.search_ids("rugged laptop", None).unwrap().is_empty());
+            assert!(index.inverted_index.is_empty());
+            
+            // Reindexing makes the settings current again
+            index.add_document(doc.clone()).unwrap();
+            index.reindex().unwrap();
+            assert!(!index.stale_analysis);
+        }
+        
+        // Settings applied before any documents are added are current
+        let mut index = Index::new();
+        index.set_stemming(true).set_min_token_length(2);
+        index.add_document(doc.clone()).unwrap();
+        index.set_stemming(true);
+        assert!(!index.stale_analysis);
+    }
+    
+    #[test]
+    fn test_readd_document_replaces_postings() {
+        let mut index = Index::new();
+        index.add_document(create_test_document("doc1")).unwrap();
+        let first_score = index.search("test", None).unwrap()[0].score;
+        
+        index.add_document(create_test_document("doc1")).unwrap();
+        index.add_documents_batch(vec![create_test_document("doc1"), create_test_document("doc1")]).unwrap();
+        assert_eq!(index.document_count(), 1);
+        assert_eq!(index.get_documents_for_token("test"), vec!["doc1".to_string()]);
+        
+        let test_id = index.interner.get_id("test").unwrap();
+        let tes_id = index.interner.get_id("tes").unwrap();
+        assert_eq!(index.trigram_index[&tes_id].iter().filter(|&&id| id == test_id).count(), 1);
+        
+        let results = index.search("test", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].score, first_score);
+        
+        // Tokens only in the previous version are dropped
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Replacement");
+        index.add_document(doc).unwrap();
+        assert!(index.get_documents_for_token("test").is_empty());
+        assert_eq!(index.get_documents_for_token("replacement"), vec!["doc1".to_string()]);
+    }
+    
+    #[test]
+    fn test_merge() {
+        let add = |index: &mut Index, id: &str, title: &str, body: &str| {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title).add_field("body", body);
+            index.add_document(doc).unwrap();
+        };
+        
+        let mut first = Index::new();
+        add(&mut first, "doc1", "Apple iPhone", "phone");
+        add(&mut first, "doc2", "Samsung Galaxy", "phone");
+        
+        // A different field order and overlapping document IDs
+        let mut second = Index::new();
+        let mut doc = Document::new("doc3");
+        doc.add_field("body", "tablet");
+        second.add_document(doc).unwrap();
+        add(&mut second, "doc4", "Apple Watch", "watch");
+        add(&mut second, "doc2", "Google Pixel", "phone");
+        
+        first.merge(second).unwrap();
+        assert_eq!(first.document_count(), 4);
+        
+        let mut apple = first.get_documents_for_token("apple");
+        apple.sort();
+        assert_eq!(apple, vec!["doc1".to_string(), "doc4".to_string()]);
+        
+        // The second index's version of doc2 replaced the first's
+        assert!(first.get_documents_for_token("samsung").is_empty());
+        assert_eq!(first.get_documents_for_token("pixel"), vec!["doc2".to_string()]);
+        assert_eq!(first.get_document("doc2").unwrap().get_text_field("title"), Some("Google Pixel".to_string()));
+        
+        // Field masks were remapped to this index's field positions
+        let title = first.field_mask("title").unwrap();
+        let body = first.field_mask("body").unwrap();
+        assert_eq!(first.get_documents_for_token_in_fields("watch", title), vec!["doc4".to_string()]);
+        assert_eq!(first.get_documents_for_token_in_fields("tablet", body), vec!["doc3".to_string()]);
+        assert!(first.get_documents_for_token_in_fields("tablet", title).is_empty());
+        
+        // Fuzzy search finds tokens from both indices through the merged trigram lists
+        let ids: Vec<String> = first.search("aple", None).unwrap().into_iter().map(|r| r.document.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"doc1".to_string()) && ids.contains(&"doc4".to_string()));
+        assert_eq!(first.search("tablte", None).unwrap()[0].document.id, "doc3");
+    }
+    
+    #[test]
+    fn test_sharded_batch_matches_sequential() {
+        let words = ["apple", "banana", "cherry", "delta", "echo", "foxtrot", "golf", "hotel"];
+        let documents: Vec<Document> = (0..400)
+            .map(|i| {
+                // Repeated IDs check that the last copy wins across shards
+                let mut doc = Document::new(format!("doc{}", i % 350));
+                doc.add_field("title", format!("{} {} item{}", words[i % 8], words[(i / 8) % 8], i % 37))
+                    .add_field("body", format!("{} model{}", words[(i / 3) % 8], i));
+                doc
+            })
+            .collect();
+        
+        let results = |index: &Index, query: &str| {
+            let options = SearchOptions { limit: 500, ..Default::default() };
+            index.search(query, Some(options)).unwrap()
+                .into_iter()
+                .map(|r| (r.document.id, r.score))
+                .collect::<Vec<_>>()
+        };
+        
+        // Into an empty index, and into one whose documents are partly replaced
+        for prefilled in [false, true] {
+            let mut sequential = Index::new();
+            let mut sharded = Index::new();
+            if prefilled {
+                for id in ["doc5", "doc999"] {
+                    sequential.add_document(create_test_document(id)).unwrap();
+                    sharded.add_document(create_test_document(id)).unwrap();
+                }
+            }
+            sequential.add_documents_sequential(documents.clone()).unwrap();
+            sharded.add_documents_sharded(documents.clone(), 4).unwrap();
+            
+            let stats = sequential.stats();
+            assert_eq!(stats.document_count, if prefilled { 351 } else { 350 });
+            assert_eq!(sharded.stats().document_count, stats.document_count);
+            assert_eq!(sharded.stats().unique_tokens, stats.unique_tokens);
+            assert_eq!(sharded.document_length("doc10"), sequential.document_length("doc10"));
+            
+            for query in ["apple", "bananna", "item12", "model360", "cherry golf", "body:hotel", "test"] {
+                assert_eq!(results(&sharded, query), results(&sequential, query), "query {query}");
+            }
+            
+            // Merging leaves out trigram entries of replaced tokens, which the
+            // sequential path only drops on compaction
+            sequential.compact();
+            sharded.compact();
+            assert_eq!(sharded.stats(), sequential.stats());
+        }
+    }
+    
+    #[test]
+    fn test_merge_incompatible() {
+        let mut first = Index::new();
+        let mut second = Index::new();
+        second.set_stemming(true);
+        
+        assert!(matches!(first.merge(second), Err(TigerCacheError::IncompatibleIndex(_))));
+    }
+    
+    #[test]
+    fn test_merge_dangling_entries() {
+        let mut first = Index::new();
+        let mut second = Index::new();
+        for index in [&mut first, &mut second] {
+            index.set_numeric_fields(vec!["price".to_string()]);
+        }
+        first.add_document(create_test_document("doc1")).unwrap();
+        for (id, title) in [("doc2", "Apple Watch"), ("doc3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title).add_field("price", 100);
+            second.add_document(doc).unwrap();
+        }
+        
+        // The other index names a document it doesn't hold, as one loaded in
+        // an inconsistent state might
+        let doc_id = second.interner.get_id("doc3").unwrap();
+        second.documents.remove(&doc_id);
+        second.document_lengths.remove(&doc_id);
+        
+        first.merge(second).unwrap();
+        assert_eq!(first.document_count(), 2);
+        assert_eq!(first.search("watch", None).unwrap()[0].document.id, "doc2");
+        assert!(first.search("galaxy", None).unwrap().is_empty());
+        assert!(first.verify().iter().all(|report| matches!(report, InconsistencyReport::DanglingTrigram { .. })));
+    }
+    
+    #[test]
+    fn test_compact() {
+        let mut index = Index::new();
+        for i in 0..100 {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("Widget model{i}"));
+            index.add_document(doc).unwrap();
+        }
+        for i in 10..100 {
+            index.remove_document(&format!("doc{i}")).unwrap();
+        }
+        
+        let before = index.interner.len();
+        let dropped = index.compact();
+        assert!(dropped > 0);
+        assert_eq!(index.interner.len(), before - dropped);
+        assert!(index.interner.get_id("doc50").is_none());
+        assert!(index.interner.get_id("model50").is_none());
+        
+        // Everything still resolves through the renumbered IDs
+        assert_eq!(index.document_count(), 10);
+        assert_eq!(index.get_documents_for_token("model5"), vec!["doc5".to_string()]);
+        assert_eq!(index.document_length("doc5"), Some(2));
+        assert_eq!(index.search("widgte", None).unwrap().len(), 10);
+        
+        // New documents get IDs past the surviving strings
+        let mut doc = Document::new("doc100");
+        doc.add_field("title", "Widget model100");
+        index.add_document(doc).unwrap();
+        assert_eq!(index.search("model100", None).unwrap()[0].document.id, "doc100");
+        assert_eq!(index.compact(), 0);
+    }
+    
+    #[test]
+    fn test_document_versions() {
+        let mut index = Index::new();
+        assert_eq!(index.document_version("doc1"), None);
+        
+        index.add_document(create_test_document("doc1")).unwrap();
+        assert_eq!(index.document_version("doc1"), Some(1));
+        index.add_document(create_test_document("doc1")).unwrap();
+        index.add_documents_batch(vec![create_test_document("doc1"), create_test_document("doc2")]).unwrap();
+        assert_eq!(index.document_version("doc1"), Some(3));
+        assert_eq!(index.document_version("doc2"), Some(1));
+        
+        // Merged documents carry on from the versions they replace
+        let mut other = Index::new();
+        other.add_document(create_test_document("doc1")).unwrap();
+        other.add_document(create_test_document("doc1")).unwrap();
+        other.add_document(create_test_document("doc3")).unwrap();
+        index.merge(other).unwrap();
+        assert_eq!(index.document_version("doc1"), Some(5));
+        assert_eq!(index.document_version("doc3"), Some(1));
+        
+        // Reindexing keeps versions; removing discards them
+        index.reindex().unwrap();
+        assert_eq!(index.document_version("doc1"), Some(5));
+        index.remove_document("doc1").unwrap();
+        assert_eq!(index.document_version("doc1"), None);
+        index.add_document(create_test_document("doc1")).unwrap();
+        assert_eq!(index.document_version("doc1"), Some(1));
+    }
+    
+    #[test]
+    fn test_verify() {
+        let mut index = Index::new();
+        index.set_numeric_fields(vec!["price".to_string()]);
+        for (id, title) in [("doc1", "Apple iPhone"), ("doc2", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title).add_field("price", 100);
+            index.add_document(doc).unwrap();
+        }
+        assert_eq!(index.verify(), Vec::new());
+        
+        // Removing a document leaves its tokens' trigram entries until compaction
+        index.remove_document("doc2").unwrap();
+        let reports = index.verify();
+        assert!(!reports.is_empty());
+        assert!(reports.iter().all(|report| matches!(report, InconsistencyReport::DanglingTrigram { .. })));
+        assert!(reports.contains(&InconsistencyReport::DanglingTrigram { ngram: "gal".to_string(), token: "galaxy".to_string() }));
+        index.compact();
+        assert_eq!(index.verify(), Vec::new());
+        
+        // Drop a document behind the index's back, leaving its postings and length
+        let doc_id = index.interner.get_id("doc1").unwrap();
+        index.documents.remove(&doc_id);
+        let reports = index.verify();
+        assert!(reports.contains(&InconsistencyReport::DocumentCountMismatch { documents: 0, lengths: 1 }));
+        assert!(reports.contains(&InconsistencyReport::DanglingPosting { token: "apple".to_string(), doc_id: "doc1".to_string() }));
+        assert!(reports.contains(&InconsistencyReport::DanglingNumericEntry { field: "price".to_string(), doc_id: "doc1".to_string() }));
+        assert_eq!(index.verify(), reports, "verify doesn't change the index");
+        assert_eq!(reports[0].to_string(), "0 documents but 1 document lengths");
+        
+        // Postings naming a string that was never interned
+        let token_id = index.interner.get_id("apple").unwrap();
+        index.inverted_index.get_mut(&token_id).unwrap().insert(StringId::new(9999), 0b1, 1);
+        assert!(index.verify().contains(&InconsistencyReport::DanglingPosting { token: "apple".to_string(), doc_id: "#9999".to_string() }));
+    }
+    
+    #[test]
+    fn test_compact_dangling_entries() {
+        let mut index = Index::new();
+        index.set_numeric_fields(vec!["price".to_string()]);
+        for (id, title) in [("doc1", "Apple iPhone"), ("doc2", "Apple Watch"), ("doc3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title).add_field("price", 100);
+            index.add_document(doc).unwrap();
+        }
+        
+        // Drop documents behind the index's back, and add a posting naming a
+        // string that was never interned
+        for id in ["doc2", "doc3"] {
+            let doc_id = index.interner.get_id(id).unwrap();
+            index.documents.remove(&doc_id);
+        }
+        let token_id = index.interner.get_id("apple").unwrap();
+        index.inverted_index.get_mut(&token_id).unwrap().insert(StringId::new(9999), 0b1, 1);
+        assert!(index.verify().iter().any(|report| matches!(report, InconsistencyReport::DanglingPosting { .. })));
+        
+        // Compacting drops the dangling entries instead of panicking
+        index.compact();
+        assert_eq!(index.verify(), Vec::new());
+        assert_eq!(index.document_count(), 1);
+        let results = index.search("apple", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "doc1");
+        assert!(index.search("galaxy", None).unwrap().is_empty());
+        assert_eq!(index.interner.get_id("doc3"), None);
+    }
+    
+    #[test]
+    fn test_numeric_index() {
+        let mut index = Index::new();
+        index.set_numeric_fields(vec!["price".to_string()]);
+        let add = |index: &mut Index, id: &str, price: serde_json::Value| {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Phone").add_field("price", price);
+            index.add_document(doc).unwrap();
+        };
+        add(&mut index, "cheap", serde_json::json!(99));
+        add(&mut index, "mid", serde_json::json!(499.5));
+        add(&mut index, "pricey", serde_json::json!(999));
+        add(&mut index, "unknown", serde_json::json!("call us"));
+        
+        let range = |index: &Index, min: Option<f64>, max: Option<f64>| -> Vec<String> {
+            let documents = index.numeric_range_documents("price", min, max).unwrap();
+            let mut ids: Vec<String> = index.document_ids().into_iter().filter(|id| index.document_in(id, &documents)).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(range(&index, Some(99.0), Some(499.5)), vec!["cheap", "mid"]);
+        assert_eq!(range(&index, Some(100.0), None), vec!["mid", "pricey"]);
+        assert_eq!(range(&index, None, None), vec!["cheap", "mid", "pricey"]);
+        assert!(range(&index, Some(500.0), Some(100.0)).is_empty());
+        assert!(range(&index, Some(f64::NAN), None).is_empty());
+        assert!(index.numeric_range_documents("title", None, None).is_none());
+        
+        // Replacing and removing documents moves and drops their values
+        add(&mut index, "cheap", serde_json::json!(1999));
+        index.remove_document("mid").unwrap();
+        assert_eq!(range(&index, None, Some(1000.0)), vec!["pricey"]);
+        
+        // Merged and compacted indices keep their values under the new IDs
+        let mut other = Index::new();
+        other.set_numeric_fields(vec!["price".to_string()]);
+        add(&mut other, "used", serde_json::json!(250));
+        index.merge(other).unwrap();
+        index.compact();
+        assert_eq!(range(&index, None, Some(1000.0)), vec!["pricey", "used"]);
+        assert_eq!(range(&index, Some(1000.0), None), vec!["cheap"]);
+        
+        let mut plain = Index::new();
+        add(&mut plain, "used", serde_json::json!(250));
+        assert!(matches!(index.merge(plain), Err(TigerCacheError::IncompatibleIndex(_))));
+    }
+    
+    #[test]
+    fn test_numeric_fields_backfilled() {
+        let mut index = Index::new();
+        for (id, price) in [("cheap", 99), ("pricey", 999)] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Phone").add_field("price", price);
+            index.add_document(doc).unwrap();
+        }
+        
+        // Fields added later are filled in from the documents already indexed
+        index.set_numeric_fields(vec!["price".to_string()]);
+        let documents = index.numeric_range_documents("price", Some(500.0), None).unwrap();
+        assert!(index.document_in("pricey", &documents));
+        assert!(!index.document_in("cheap", &documents));
+        
+        // Without the documents in memory, the numeric index isn't used until a reindex
+        index.set_numeric_fields(Vec::new());
+        index.set_retain_documents(false);
+        index.set_numeric_fields(vec!["price".to_string()]);
+        assert!(index.numeric_range_documents("price", None, None).is_none());
+        index.clear();
+        assert!(index.numeric_range_documents("price", None, None).is_some());
+    }
+    
+    #[test]
+    fn test_numeric_tokens() {
+        let mut index = Index::new();
+        index.set_numeric_tokens(true);
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Widget Test-123 rev4b");
+        index.add_document(doc).unwrap();
+        
+        for token in ["widget", "test123", "123", "rev4b", "4"] {
+            assert_eq!(index.get_documents_for_token(token), vec!["doc1".to_string()], "{token}");
+        }
+        assert!(index.is_exact_numeric("123"));
+        assert!(!index.is_exact_numeric("test123"));
+        
+        // Removing the document drops its numeric tokens too
+        index.remove_document("doc1").unwrap();
+        assert!(index.get_documents_for_token("123").is_empty());
+        
+        let mut plain = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Test-123");
+        plain.add_document(doc).unwrap();
+        assert!(plain.get_documents_for_token("123").is_empty());
+        assert!(!plain.is_exact_numeric("123"));
+    }
+    
+    #[test]
+    fn test_phonetic_index() {
+        let mut index = Index::new();
+        index.set_phonetic(true);
+        for (id, name) in [("doc1", "Rupert Smith"), ("doc2", "Steven Jones")] {
+            let mut doc = Document::new(id);
+            doc.add_field("name", name);
+            index.add_document(doc).unwrap();
+        }
+        
+        assert_eq!(index.phonetic_tokens_for("robert"), FxHashSet::from_iter(["rupert".to_string()]));
+        assert_eq!(index.phonetic_tokens_for("smyth"), FxHashSet::from_iter(["smith".to_string()]));
+        assert!(index.phonetic_tokens_for("brown").is_empty());
+        
+        // Compacting drops the codes of removed tokens and remaps the rest
+        index.remove_document("doc2").unwrap();
+        index.compact();
+        assert!(index.interner.get_id("S315").is_none());
+        assert_eq!(index.phonetic_tokens_for("stephen"), FxHashSet::default());
+        assert_eq!(index.phonetic_tokens_for("robert"), FxHashSet::from_iter(["rupert".to_string()]));
+        
+        // Merged indices must agree on the setting
+        let mut other = Index::new();
+        assert!(matches!(other.merge(index.clone()), Err(TigerCacheError::IncompatibleIndex(_))));
+        other.set_phonetic(true);
+        other.merge(index).unwrap();
+        assert_eq!(other.phonetic_tokens_for("smyth"), FxHashSet::from_iter(["smith".to_string()]));
+        
+        // Nothing is indexed by sound unless enabled
+        let mut plain = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("name", "Rupert Smith");
+        plain.add_document(doc).unwrap();
+        assert!(plain.phonetic_tokens_for("robert").is_empty());
+    }
+    
     #[test]
     fn test_find_candidate_tokens() {
         let mut index = Index::new();
@@ -331,4 +2144,199 @@ mod tests {
         let candidates = index.find_candidate_tokens("documant"); // Misspelled
         assert!(candidates.contains("document"));
     }
+    
+    #[test]
+    fn test_whitespace_tokenizer() {
+        let mut index = Index::new();
+        index.set_tokenizer(TokenizerKind::Whitespace.build());
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Wi-Fi router");
+        index.add_document(doc).unwrap();
+        
+        assert!(index.interner.get_id("wi-fi").is_some());
+        assert!(index.interner.get_id("wifi").is_none());
+        assert_eq!(index.get_documents_for_token("wi-fi"), vec!["doc1"]);
+    }
+    
+    #[test]
+    fn test_tokenizer_kind_persisted() {
+        let mut index = Index::new();
+        index.set_tokenizer(TokenizerKind::Whitespace.build());
+        
+        let json = serde_json::to_string(&index).unwrap();
+        let loaded: Index = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.tokenizer_kind(), TokenizerKind::Whitespace);
+    }
+    
+    #[test]
+    fn test_stopwords() {
+        let mut index = Index::new();
+        index.set_stopwords(["The", "and"]);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "The cat and the hat");
+        index.add_document(doc).unwrap();
+        
+        // Stopwords are never indexed
+        assert!(index.interner.get_id("the").is_none());
+        assert!(index.get_documents_for_token("and").is_empty());
+        assert_eq!(index.get_documents_for_token("cat"), vec!["doc1"]);
+        
+        // ...and never show up as candidates
+        let candidates = index.find_candidate_tokens("the and");
+        assert!(candidates.is_empty());
+        let candidates = index.find_candidate_tokens("thee");
+        assert!(!candidates.contains("the"));
+    }
+    
+    #[test]
+    fn test_stemming_and_reindex() {
+        let mut index = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Running shoes");
+        index.add_document(doc).unwrap();
+        assert!(index.get_documents_for_token("run").is_empty());
+        
+        // Changing the setting only takes effect for existing documents after a reindex
+        index.set_stemming(true);
+        assert!(index.get_documents_for_token("run").is_empty());
+        index.reindex().unwrap();
+        
+        assert_eq!(index.document_count(), 1);
+        assert_eq!(index.get_documents_for_token("run"), vec!["doc1"]);
+        assert_eq!(index.get_documents_for_token("shoe"), vec!["doc1"]);
+        assert!(index.get_documents_for_token("running").is_empty());
+        assert_eq!(index.tokenize("Runs"), vec!["run"]);
+    }
+    
+    #[test]
+    fn test_fold_diacritics() {
+        let mut index = Index::new();
+        index.set_normalize_options(NormalizeOptions { fold_diacritics: true, ..Default::default() })
+            .set_stopwords(["à"]);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Crème brûlée à la café");
+        index.add_document(doc).unwrap();
+        
+        assert_eq!(index.get_documents_for_token("creme"), vec!["doc1"]);
+        assert_eq!(index.get_documents_for_token("brulee"), vec!["doc1"]);
+        assert!(index.get_documents_for_token("crème").is_empty());
+        assert!(index.get_documents_for_token("a").is_empty());
+        
+        // Query tokens fold the same way
+        assert_eq!(index.tokenize("CAFÉ"), index.tokenize("cafe"));
+    }
+    
+    #[test]
+    fn test_ngram_size() {
+        for n in [2, 4] {
+            let mut index = Index::new();
+            index.set_ngram_size(n);
+            index.add_document(create_test_document("doc1")).unwrap();
+            
+            // The trigram index holds n-grams of the configured size
+            let ngram = if n == 2 { "te" } else { "test" };
+            assert!(index.interner.get_id(ngram).is_some());
+            assert!(index.find_candidate_tokens("tset").contains("test"));
+            assert!(index.find_candidate_tokens("documnet").contains("document"));
+        }
+        
+        // Changing the size takes effect for existing documents after a reindex
+        let mut index = Index::new();
+        index.add_document(create_test_document("doc1")).unwrap();
+        index.set_ngram_size(2);
+        index.reindex().unwrap();
+        assert_eq!(index.ngram_size(), 2);
+        assert!(index.interner.get_id("$t").is_some());
+        assert!(index.interner.get_id("$$t").is_none());
+    }
+    
+    #[test]
+    fn test_case_sensitive() {
+        let mut index = Index::new();
+        index.set_normalize_options(NormalizeOptions { case_sensitive: true, ..Default::default() })
+            .set_stopwords(["the"]);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "The Apple store sells apple pie");
+        index.add_document(doc).unwrap();
+        
+        assert_eq!(index.get_documents_for_token("Apple"), vec!["doc1"]);
+        assert_eq!(index.get_documents_for_token("apple"), vec!["doc1"]);
+        assert!(index.get_documents_for_token("Store").is_empty());
+        assert!(index.get_documents_for_token("The").is_empty());
+        assert!(index.find_candidate_tokens("Appel").contains("Apple"));
+    }
+    
+    #[test]
+    fn test_tokens_with_prefix() {
+        let mut index = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "apply apple application banana");
+        index.add_document(doc).unwrap();
+        
+        assert_eq!(index.tokens_with_prefix("app"), vec!["apple", "application", "apply"]);
+        assert_eq!(index.tokens_with_prefix("banana"), vec!["banana"]);
+        assert!(index.tokens_with_prefix("cherry").is_empty());
+        
+        // N-grams share the interner but aren't tokens
+        assert!(index.tokens_with_prefix("$").is_empty());
+    }
+    
+    #[test]
+    fn test_without_retained_documents() {
+        let mut index = Index::new();
+        index.add_document(create_test_document("doc1")).unwrap();
+        index.set_retain_documents(false);
+        index.add_document(create_test_document("doc2")).unwrap();
+        
+        // IDs and postings are kept, documents aren't
+        assert_eq!(index.document_count(), 2);
+        assert!(index.get_document("doc1").is_none());
+        assert!(index.get_document("doc2").is_none());
+        assert_eq!(index.get_documents_for_token("searching").len(), 2);
+        
+        // Reindexing needs the documents supplied
+        assert!(index.reindex().is_err());
+        let documents = vec![create_test_document("doc1"), create_test_document("doc2")];
+        index.reindex_documents(documents).unwrap();
+        assert_eq!(index.document_count(), 2);
+        
+        // A loaded document can be held again without reindexing
+        index.cache_document(create_test_document("doc1"));
+        index.cache_document(create_test_document("unknown"));
+        assert!(index.get_document("doc1").is_some());
+        assert_eq!(index.document_count(), 2);
+        assert_eq!(index.get_documents_for_token("searching").len(), 2);
+    }
+    
+    #[test]
+    fn test_expired_documents_hidden() {
+        let mut index = Index::new();
+        index.add_document(create_test_document("doc1")).unwrap();
+        index.add_document(create_test_document("doc2")).unwrap();
+        
+        let past = SystemTime::now() - std::time::Duration::from_secs(1);
+        let future = SystemTime::now() + std::time::Duration::from_secs(3600);
+        index.set_expiry("doc1", past).unwrap();
+        index.set_expiry("doc2", future).unwrap();
+        assert!(index.set_expiry("missing", past).is_err());
+        
+        assert!(index.is_expired("doc1"));
+        assert!(index.get_document("doc1").is_none());
+        assert!(index.get_document("doc2").is_some());
+        assert_eq!(index.expired_document_ids(), vec!["doc1".to_string()]);
+        
+        let ids: Vec<String> = index.search_ids("test", None).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["doc2".to_string()]);
+        
+        // Reindexing keeps expiry times; re-adding a document clears its own
+        index.reindex().unwrap();
+        assert!(index.is_expired("doc1"));
+        index.add_document(create_test_document("doc1")).unwrap();
+        assert!(!index.is_expired("doc1"));
+        assert!(index.get_document("doc1").is_some());
+    }
 }
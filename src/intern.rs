@@ -1,4 +1,4 @@
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 
 /// A unique identifier for an interned string
@@ -71,6 +71,16 @@ impl StringInterner {
         self.string_to_id.is_empty()
     }
     
+    /// Estimate the memory used by the interned strings and both lookup maps in bytes
+    pub fn estimated_memory_bytes(&self) -> usize {
+        // Each string is held twice, once per map
+        let entry_size = 2 * (std::mem::size_of::<String>() + std::mem::size_of::<StringId>());
+        self.id_to_string
+            .values()
+            .map(|s| entry_size + 2 * s.len())
+            .sum()
+    }
+    
     /// Clear all interned strings
     pub fn clear(&mut self) {
         self.string_to_id.clear();
@@ -78,6 +88,35 @@ impl StringInterner {
         self.next_id = 0;
     }
     
+    /// Drop every string whose ID isn't in `live_ids` and renumber the rest
+    ///
+    /// Surviving strings keep their relative order and get dense IDs starting
+    /// at zero, so `next_id` shrinks too. Returns the old-to-new ID mapping of
+    /// the surviving strings; every stored ID must be remapped through it.
+    pub fn gc(&mut self, live_ids: &FxHashSet<StringId>) -> FxHashMap<StringId, StringId> {
+        let mut live: Vec<(StringId, String)> = self.id_to_string
+            .drain()
+            .filter(|(id, _)| live_ids.contains(id))
+            .collect();
+        live.sort_unstable_by_key(|(id, _)| id.as_u32());
+        
+        self.string_to_id.clear();
+        self.next_id = 0;
+        
+        let mut remap = FxHashMap::default();
+        remap.reserve(live.len());
+        for (old_id, string) in live {
+            let id = StringId::new(self.next_id);
+            self.next_id += 1;
+            
+            self.string_to_id.insert(string.clone(), id);
+            self.id_to_string.insert(id, string);
+            remap.insert(old_id, id);
+        }
+        
+        remap
+    }
+    
     /// Iterate over all interned strings and their IDs
     pub fn iter(&self) -> impl Iterator<Item = (StringId, &str)> {
         self.id_to_string.iter().map(|(&id, s)| (id, s.as_str()))
@@ -131,4 +170,22 @@ mod tests {
         assert_eq!(interner.len(), 0);
         assert!(interner.is_empty());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_gc() {
+        let mut interner = StringInterner::new();
+        let ids: Vec<StringId> = ["a", "b", "c", "d"].iter().map(|s| interner.intern(s)).collect();
+        
+        let live: FxHashSet<StringId> = [ids[1], ids[3]].into_iter().collect();
+        let remap = interner.gc(&live);
+        
+        assert_eq!(interner.len(), 2);
+        assert_eq!(remap.len(), 2);
+        assert_eq!(interner.get(remap[&ids[1]]), Some("b"));
+        assert_eq!(interner.get(remap[&ids[3]]), Some("d"));
+        assert_eq!(interner.get_id("a"), None);
+        
+        // IDs are dense again, so new strings continue after the survivors
+        assert_eq!(interner.intern("e"), StringId::new(2));
+    }
+}
@@ -38,19 +38,39 @@ mod document;
 mod error;
 mod index;
 mod intern;
+mod postings;
 mod tiger_cache;
 mod persistence;
 mod search;
+mod query;
 mod trigram;
+mod phonetic;
+mod schema;
 mod storage;
 mod config;
+mod cache;
+mod tokenizer;
+mod stemmer;
+mod encryption;
+mod import;
+mod shared;
+mod auto_commit;
 
 // Re-export public API
 pub use document::Document;
+pub use index::{InconsistencyReport, IndexStats};
 pub use error::{TigerCacheError, Result};
-pub use tiger_cache::TigerCache;
-pub use search::{SearchOptions, SearchResult};
+pub use tiger_cache::{BatchGuard, CommitStats, MetricsObserver, SlowQueryObserver, TigerCache};
+pub use shared::SharedTigerCache;
+pub use query::QueryAst;
+pub use search::{AggKind, DistanceMetric, Filter, FreshnessSpec, QueryMetrics, SearchOptions, SlowQuery, SearchResult, ScoreExplanation, TokenMatch};
 pub use config::TigerCacheConfig;
+pub use cache::CacheStats;
+pub use schema::{FieldSpec, FieldType, Schema};
+pub use persistence::PersistenceFormat;
+pub use import::ImportErrorPolicy;
+pub use tokenizer::{Tokenizer, TokenizerKind, StandardTokenizer, WhitespaceTokenizer};
+pub use trigram::{NormalizeOptions, PunctuationSet};
 
 // Re-export storage API
 pub use storage::{
@@ -0,0 +1,79 @@
+/// Compute the American Soundex code of a word
+///
+/// The code is the word's first letter followed by three digits encoding the
+/// consonants that follow, so words that sound alike share a code: "Smith" and
+/// "Smyth" are both "S530". Characters other than ASCII letters are ignored;
+/// returns `None` when the word has no ASCII letters.
+pub fn soundex(word: &str) -> Option<String> {
+    let mut letters = word.chars().filter(|c| c.is_ascii_alphabetic()).map(|c| c.to_ascii_uppercase());
+    let first = letters.next()?;
+    
+    let mut code = String::with_capacity(4);
+    code.push(first);
+    
+    // Adjacent letters with the same digit are coded once, as are letters with
+    // the same digit separated only by H or W; vowels separate them
+    let mut previous = soundex_digit(first);
+    for letter in letters {
+        let digit = soundex_digit(letter);
+        if digit != 0 && digit != previous {
+            code.push(char::from(b'0' + digit));
+            if code.len() == 4 {
+                break;
+            }
+        }
+        if letter != 'H' && letter != 'W' {
+            previous = digit;
+        }
+    }
+    
+    while code.len() < 4 {
+        code.push('0');
+    }
+    Some(code)
+}
+
+/// Get the Soundex digit of an uppercase letter, 0 for vowels and the letters H, W, and Y
+fn soundex_digit(letter: char) -> u8 {
+    match letter {
+        'B' | 'F' | 'P' | 'V' => 1,
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => 2,
+        'D' | 'T' => 3,
+        'L' => 4,
+        'M' | 'N' => 5,
+        'R' => 6,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("Robert").as_deref(), Some("R163"));
+        assert_eq!(soundex("Rupert").as_deref(), Some("R163"));
+        assert_eq!(soundex("Rubin").as_deref(), Some("R150"));
+        assert_eq!(soundex("Ashcraft").as_deref(), Some("A261"));
+        assert_eq!(soundex("Tymczak").as_deref(), Some("T522"));
+        assert_eq!(soundex("Pfister").as_deref(), Some("P236"));
+        assert_eq!(soundex("Honeyman").as_deref(), Some("H555"));
+        assert_eq!(soundex("Lee").as_deref(), Some("L000"));
+    }
+    
+    #[test]
+    fn test_soundex_equivalents() {
+        for (a, b) in [("Smith", "Smyth"), ("Stephen", "Steven"), ("Jackson", "Jaxon"), ("Aaron", "Arran")] {
+            assert_eq!(soundex(a), soundex(b), "{a} / {b}");
+        }
+        assert_ne!(soundex("Smith"), soundex("Jones"));
+    }
+    
+    #[test]
+    fn test_soundex_without_letters() {
+        assert_eq!(soundex("1234"), None);
+        assert_eq!(soundex(""), None);
+        assert_eq!(soundex("r2d2").as_deref(), Some("R300"));
+    }
+}
@@ -0,0 +1,330 @@
+use roaring::RoaringBitmap;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use smallvec::SmallVec;
+
+use crate::intern::StringId;
+
+/// Bitmask of fields, one bit per field in `Index::field_names`
+pub(crate) type FieldMask = u32;
+
+/// Bit shared by every field past the first 31
+pub(crate) const OVERFLOW_FIELD_BIT: FieldMask = 1 << (FieldMask::BITS - 1);
+
+/// Get the mask bit of the field at `position` in `Index::field_names`
+pub(crate) fn field_bit_at(position: usize) -> FieldMask {
+    if position < FieldMask::BITS as usize - 1 {
+        1 << position
+    } else {
+        OVERFLOW_FIELD_BIT
+    }
+}
+
+/// The documents containing a token, overall and per field
+///
+/// Documents are stored as bitmaps of their interned ID values, which serve as
+/// document ordinals. Each field the token appears in gets its own bitmap, keyed
+/// by the field's bit position; most tokens appear in one or two fields.
+/// Term frequencies are only stored for documents containing the token more
+/// than once.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct Postings {
+    /// Every document containing the token
+    docs: RoaringBitmap,
+    
+    /// Documents containing the token in each field, keyed by the field's bit position
+    fields: SmallVec<[(u8, RoaringBitmap); 1]>,
+    
+    /// Number of occurrences in each document containing the token more than once, by ordinal
+    #[serde(serialize_with = "serialize_frequencies")]
+    frequencies: FxHashMap<u32, u32>,
+}
+
+impl Postings {
+    /// Record that a document contains the token `frequency` times, in the fields of `fields`
+    pub fn insert(&mut self, doc_id: StringId, fields: FieldMask, frequency: u32) {
+        let ordinal = doc_id.as_u32();
+        self.docs.insert(ordinal);
+        if frequency > 1 {
+            self.frequencies.insert(ordinal, frequency);
+        } else {
+            self.frequencies.remove(&ordinal);
+        }
+        
+        for position in (0..FieldMask::BITS as u8).filter(|position| fields & (1 << position) != 0) {
+            match self.fields.iter_mut().find(|(field, _)| *field == position) {
+                Some((_, docs)) => {
+                    docs.insert(ordinal);
+                }
+                None => {
+                    self.fields.push((position, RoaringBitmap::from_iter([ordinal])));
+                }
+            }
+        }
+    }
+    
+    /// Remove a document, returning whether it was present
+    pub fn remove(&mut self, doc_id: StringId) -> bool {
+        let ordinal = doc_id.as_u32();
+        if !self.docs.remove(ordinal) {
+            return false;
+        }
+        
+        for (_, docs) in &mut self.fields {
+            docs.remove(ordinal);
+        }
+        self.fields.retain(|(_, docs)| !docs.is_empty());
+        self.frequencies.remove(&ordinal);
+        true
+    }
+    
+    /// Keep only the documents for which `keep` returns true
+    pub fn retain(&mut self, keep: impl Fn(StringId) -> bool) {
+        let removed: Vec<u32> = self.docs.iter().filter(|&ordinal| !keep(StringId::new(ordinal))).collect();
+        for ordinal in removed {
+            self.remove(StringId::new(ordinal));
+        }
+    }
+    
+    /// Get the number of times a document contains the token (0 if it doesn't)
+    pub fn frequency(&self, doc_id: StringId) -> u32 {
+        let ordinal = doc_id.as_u32();
+        match self.frequencies.get(&ordinal) {
+            Some(&frequency) => frequency,
+            None => u32::from(self.docs.contains(ordinal)),
+        }
+    }
+    
+    /// Check whether no document contains the token
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+    
+    /// Add another index's postings for the same token
+    ///
+    /// The other postings' document ordinals are passed through `map_doc`,
+    /// leaving out documents it returns `None` for, and their field bit
+    /// positions through `map_position`.
+    pub fn union_remapped(&mut self, other: &Postings, map_doc: impl Fn(u32) -> Option<u32>, map_position: impl Fn(u8) -> u8) {
+        let remap = |docs: &RoaringBitmap| -> RoaringBitmap { docs.iter().filter_map(&map_doc).collect() };
+        self.docs |= remap(&other.docs);
+        
+        for (other_position, other_docs) in &other.fields {
+            let position = map_position(*other_position);
+            let docs = remap(other_docs);
+            if docs.is_empty() {
+                continue;
+            }
+            match self.fields.iter_mut().find(|(field, _)| *field == position) {
+                Some((_, existing)) => *existing |= docs,
+                None => self.fields.push((position, docs)),
+            }
+        }
+        
+        self.frequencies.extend(other.frequencies.iter().filter_map(|(&ordinal, &frequency)| Some((map_doc(ordinal)?, frequency))));
+    }
+    
+    /// Copy the postings with every document ID passed through `map`
+    ///
+    /// Documents `map` returns `None` for are left out.
+    pub fn remapped(&self, map: impl Fn(StringId) -> Option<StringId>) -> Self {
+        let remap = |docs: &RoaringBitmap| -> RoaringBitmap {
+            docs.iter().filter_map(|ordinal| map(StringId::new(ordinal))).map(StringId::as_u32).collect()
+        };
+        
+        Self {
+            docs: remap(&self.docs),
+            fields: self.fields
+                .iter()
+                .map(|(position, docs)| (*position, remap(docs)))
+                .filter(|(_, docs)| !docs.is_empty())
+                .collect(),
+            frequencies: self.frequencies
+                .iter()
+                .filter_map(|(&ordinal, &frequency)| Some((map(StringId::new(ordinal))?.as_u32(), frequency)))
+                .collect(),
+        }
+    }
+    
+    /// Get the documents containing the token in any of the fields in `fields`
+    pub fn docs_in_fields(&self, fields: FieldMask) -> RoaringBitmap {
+        if fields == FieldMask::MAX {
+            return self.docs.clone();
+        }
+        
+        self.fields
+            .iter()
+            .filter(|(position, _)| fields & (1 << position) != 0)
+            .fold(RoaringBitmap::new(), |acc, (_, docs)| acc | docs)
+    }
+    
+    /// Estimate the memory used by the postings in bytes
+    pub fn estimated_memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.docs.serialized_size()
+            + self.fields
+                .iter()
+                .map(|(_, docs)| std::mem::size_of::<(u8, RoaringBitmap)>() + docs.serialized_size())
+                .sum::<usize>()
+            + self.frequencies.capacity() * std::mem::size_of::<(u32, u32)>()
+    }
+}
+
+/// Serialize term frequencies as `(ordinal, frequency)` pairs in ordinal order,
+/// since JSON map keys are strings and can't be read back through `PostingsRepr`
+fn serialize_frequencies<S: Serializer>(frequencies: &FxHashMap<u32, u32>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut pairs: Vec<(u32, u32)> = frequencies.iter().map(|(&ordinal, &frequency)| (ordinal, frequency)).collect();
+    pairs.sort_unstable();
+    pairs.serialize(serializer)
+}
+
+/// Serialized postings as written by the current format
+#[derive(Deserialize)]
+struct PostingsData {
+    docs: RoaringBitmap,
+    fields: SmallVec<[(u8, RoaringBitmap); 1]>,
+    #[serde(default)]
+    frequencies: Vec<(u32, u32)>,
+}
+
+/// Postings in any format a self-describing (JSON) index file may contain
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PostingsRepr {
+    Current(PostingsData),
+    
+    /// Plain list of document IDs, written before postings were bitmaps
+    Legacy(Vec<StringId>),
+}
+
+impl<'de> Deserialize<'de> for Postings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Only self-describing formats can tell the layouts apart
+        let data = if deserializer.is_human_readable() {
+            match PostingsRepr::deserialize(deserializer)? {
+                PostingsRepr::Current(data) => data,
+                PostingsRepr::Legacy(doc_ids) => {
+                    // Field information wasn't recorded, so field-scoped queries
+                    // only match these documents once they're reindexed
+                    return Ok(Self {
+                        docs: doc_ids.into_iter().map(StringId::as_u32).collect(),
+                        fields: SmallVec::new(),
+                        frequencies: FxHashMap::default(),
+                    });
+                }
+            }
+        } else {
+            PostingsData::deserialize(deserializer)?
+        };
+        
+        Ok(Self {
+            docs: data.docs,
+            fields: data.fields,
+            frequencies: data.frequencies.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_field_bits() {
+        assert_eq!(field_bit_at(0), 1);
+        assert_eq!(field_bit_at(30), 1 << 30);
+        assert_eq!(field_bit_at(31), OVERFLOW_FIELD_BIT);
+        assert_eq!(field_bit_at(100), OVERFLOW_FIELD_BIT);
+    }
+    
+    #[test]
+    fn test_insert_and_remove() {
+        let mut postings = Postings::default();
+        postings.insert(StringId::new(1), 0b01, 1);
+        postings.insert(StringId::new(2), 0b11, 1);
+        postings.insert(StringId::new(3), 0b10, 1);
+        
+        assert_eq!(postings.docs_in_fields(0b01).iter().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(postings.docs_in_fields(0b10).iter().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(postings.docs_in_fields(FieldMask::MAX).len(), 3);
+        assert!(postings.docs_in_fields(0b100).is_empty());
+        
+        assert!(postings.remove(StringId::new(2)));
+        assert!(!postings.remove(StringId::new(2)));
+        assert_eq!(postings.docs_in_fields(0b11).iter().collect::<Vec<_>>(), vec![1, 3]);
+        
+        let remapped = postings.remapped(|id| Some(StringId::new(id.as_u32() * 10)));
+        assert_eq!(remapped.docs_in_fields(0b10).iter().collect::<Vec<_>>(), vec![30]);
+        
+        // Unmapped documents are left out
+        let remapped = postings.remapped(|id| (id.as_u32() == 1).then_some(id));
+        assert_eq!(remapped.docs_in_fields(FieldMask::MAX).iter().collect::<Vec<_>>(), vec![1]);
+        assert!(remapped.docs_in_fields(0b10).is_empty());
+        assert_eq!(remapped.frequency(StringId::new(3)), 0);
+        
+        let mut retained = postings.clone();
+        retained.retain(|id| id.as_u32() == 3);
+        assert_eq!(retained.docs_in_fields(FieldMask::MAX).iter().collect::<Vec<_>>(), vec![3]);
+        
+        postings.remove(StringId::new(1));
+        postings.remove(StringId::new(3));
+        assert!(postings.is_empty());
+        assert!(postings.fields.is_empty());
+    }
+    
+    #[test]
+    fn test_union_remapped() {
+        let mut postings = Postings::default();
+        postings.insert(StringId::new(1), 0b01, 1);
+        
+        let mut other = Postings::default();
+        other.insert(StringId::new(1), 0b01, 1);
+        other.insert(StringId::new(2), 0b10, 1);
+        
+        // The other index's field 0 is field 1 here and its field 1 is field 2
+        postings.union_remapped(&other, |ordinal| Some(ordinal + 10), |position| position + 1);
+        assert_eq!(postings.docs_in_fields(FieldMask::MAX).iter().collect::<Vec<_>>(), vec![1, 11, 12]);
+        assert_eq!(postings.docs_in_fields(0b001).iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(postings.docs_in_fields(0b010).iter().collect::<Vec<_>>(), vec![11]);
+        assert_eq!(postings.docs_in_fields(0b100).iter().collect::<Vec<_>>(), vec![12]);
+    }
+    
+    #[test]
+    fn test_frequencies() {
+        let mut postings = Postings::default();
+        postings.insert(StringId::new(1), 0b01, 3);
+        postings.insert(StringId::new(2), 0b01, 1);
+        assert_eq!(postings.frequency(StringId::new(1)), 3);
+        assert_eq!(postings.frequency(StringId::new(2)), 1);
+        assert_eq!(postings.frequency(StringId::new(3)), 0);
+        
+        let remapped = postings.remapped(|id| Some(StringId::new(id.as_u32() * 10)));
+        assert_eq!(remapped.frequency(StringId::new(10)), 3);
+        
+        let mut merged = Postings::default();
+        merged.union_remapped(&postings, |ordinal| Some(ordinal + 100), |position| position);
+        assert_eq!(merged.frequency(StringId::new(101)), 3);
+        assert_eq!(merged.frequency(StringId::new(102)), 1);
+        
+        postings.remove(StringId::new(1));
+        assert_eq!(postings.frequency(StringId::new(1)), 0);
+        assert!(postings.frequencies.is_empty());
+    }
+    
+    #[test]
+    fn test_serde_round_trip() {
+        let mut postings = Postings::default();
+        postings.insert(StringId::new(7), 0b101, 1);
+        postings.insert(StringId::new(9), 0b001, 4);
+        
+        let json = serde_json::to_string(&postings).unwrap();
+        assert_eq!(serde_json::from_str::<Postings>(&json).unwrap(), postings);
+    }
+    
+    #[test]
+    fn test_legacy_json_postings() {
+        let postings: Postings = serde_json::from_str("[4, 2]").unwrap();
+        assert_eq!(postings.docs_in_fields(FieldMask::MAX).iter().collect::<Vec<_>>(), vec![2, 4]);
+        assert!(postings.docs_in_fields(0b1).is_empty());
+    }
+}
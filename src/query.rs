@@ -0,0 +1,484 @@
+use std::fmt;
+use std::ops::Not;
+
+/// A parsed search query
+///
+/// Built by `QueryAst::parse` from query syntax, or directly for programmatic
+/// queries run with `TigerCache::search_parsed`. Documents are scored by the
+/// leaves they match:
+///
+/// - Plain terms, prefixes, field-scoped terms, and `*` are fuzzy matched and
+///   scored together wherever they're alternatives of one `Or`, exactly as the
+///   words of a plain query are, so repeated and overlapping terms count once.
+/// - `And` keeps documents matching every child and sums their scores; its `Not`
+///   children drop documents containing any of their terms exactly.
+/// - A `Not` anywhere else matches nothing, so a query made only of exclusions
+///   returns no results; combine it with `*` to keep everything else.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QueryAst {
+    /// Text matched fuzzily against indexed tokens; several words count as several terms
+    Term(String),
+    
+    /// A word matched against indexed tokens by prefix (`app*`)
+    Prefix(String),
+    
+    /// Quoted words matched exactly, which must appear consecutively in one
+    /// field of documents held in memory (`"tiger cache"`)
+    Phrase(String),
+    
+    /// A term only matched against tokens indexed from `field` (`title:rust`,
+    /// `status:"in stock"`); keyword fields match the whole value exactly
+    Field { field: String, term: String },
+    
+    /// Every document (`*`)
+    MatchAll,
+    
+    /// Documents matching all of the children (`a AND b`)
+    And(Vec<QueryAst>),
+    
+    /// Documents matching any of the children (`a OR b`, or adjacent words)
+    Or(Vec<QueryAst>),
+    
+    /// Documents not containing the child's terms (`NOT a`, `-a`)
+    Not(Box<QueryAst>),
+}
+
+impl QueryAst {
+    /// Parse query syntax into a query
+    ///
+    /// Supports `AND`, `OR`, and `NOT` (uppercase only; lowercase words are plain
+    /// terms), `-` as a shorthand for `NOT`, parentheses, quoted phrases,
+    /// `field:term` and `field:"quoted term"`, trailing `*` prefixes, and a bare
+    /// `*`. `NOT` binds tightest, then `AND`, then `OR`; adjacent clauses are
+    /// alternatives, except that negated ones exclude documents from all of
+    /// them, so `laptop tablet -apple` is `(laptop OR tablet) AND NOT apple`.
+    ///
+    /// Parsing never fails: unbalanced parentheses are closed at the end of the
+    /// query or ignored, and dangling operators are dropped.
+    ///
+    /// Any `name:` prefix is taken as a field name; use `parse_with_fields` to
+    /// restrict that to known fields.
+    pub fn parse(query: &str) -> Self {
+        Self::parse_with_fields(query, |_| true)
+    }
+    
+    /// Parse query syntax, treating `name:term` as field-scoped only when
+    /// `is_field` accepts the name
+    ///
+    /// Other words containing a colon, such as URLs, stay plain terms. Searches
+    /// parse with the fields the index knows about.
+    pub fn parse_with_fields(query: &str, is_field: impl Fn(&str) -> bool) -> Self {
+        let mut parser = Parser { tokens: lex(query), position: 0, is_field: &is_field };
+        parser.parse_sequence(false)
+    }
+    
+    /// Create a fuzzy matched term
+    pub fn term(text: impl Into<String>) -> Self {
+        QueryAst::Term(text.into())
+    }
+    
+    /// Create a prefix term
+    pub fn prefix(text: impl Into<String>) -> Self {
+        QueryAst::Prefix(text.into())
+    }
+    
+    /// Create an exactly matched phrase
+    pub fn phrase(text: impl Into<String>) -> Self {
+        QueryAst::Phrase(text.into())
+    }
+    
+    /// Create a term matched only within `field`
+    pub fn field(field: impl Into<String>, term: impl Into<String>) -> Self {
+        QueryAst::Field { field: field.into(), term: term.into() }
+    }
+    
+    /// Combine queries that must all match, flattening nested `And`s; a single query is returned as is
+    pub fn and(children: impl IntoIterator<Item = QueryAst>) -> Self {
+        let mut flattened = Vec::new();
+        for child in children {
+            match child {
+                QueryAst::And(grandchildren) => flattened.extend(grandchildren),
+                child => flattened.push(child),
+            }
+        }
+        if flattened.len() == 1 { flattened.remove(0) } else { QueryAst::And(flattened) }
+    }
+    
+    /// Combine alternative queries, flattening nested `Or`s; a single query is returned as is
+    pub fn or(children: impl IntoIterator<Item = QueryAst>) -> Self {
+        let mut flattened = Vec::new();
+        for child in children {
+            match child {
+                QueryAst::Or(grandchildren) => flattened.extend(grandchildren),
+                child => flattened.push(child),
+            }
+        }
+        if flattened.len() == 1 { flattened.remove(0) } else { QueryAst::Or(flattened) }
+    }
+    
+    /// Check whether the query is a leaf scored together with its sibling leaves
+    pub(crate) fn is_clause(&self) -> bool {
+        matches!(self, QueryAst::Term(_) | QueryAst::Prefix(_) | QueryAst::Field { .. } | QueryAst::MatchAll)
+    }
+}
+
+/// Negate a query, as `-term` or `NOT term` does
+impl Not for QueryAst {
+    type Output = QueryAst;
+    
+    fn not(self) -> QueryAst {
+        QueryAst::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for QueryAst {
+    /// Format as query syntax, parenthesizing nested groups
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let group = |f: &mut fmt::Formatter<'_>, child: &QueryAst| match child {
+            QueryAst::And(_) | QueryAst::Or(_) => write!(f, "({child})"),
+            _ => write!(f, "{child}"),
+        };
+        let join = |f: &mut fmt::Formatter<'_>, children: &[QueryAst], operator: &str| {
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {operator} ")?;
+                }
+                group(f, child)?;
+            }
+            Ok(())
+        };
+        
+        match self {
+            QueryAst::Term(text) => write!(f, "{text}"),
+            QueryAst::Prefix(text) => write!(f, "{text}*"),
+            QueryAst::Phrase(text) => write!(f, "\"{text}\""),
+            QueryAst::Field { field, term } if term.contains(char::is_whitespace) => write!(f, "{field}:\"{term}\""),
+            QueryAst::Field { field, term } => write!(f, "{field}:{term}"),
+            QueryAst::MatchAll => write!(f, "*"),
+            QueryAst::And(children) => join(f, children, "AND"),
+            QueryAst::Or(children) => join(f, children, "OR"),
+            QueryAst::Not(child) => {
+                write!(f, "NOT ")?;
+                group(f, child)
+            }
+        }
+    }
+}
+
+/// A lexical token of query syntax
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Phrase(&'a str),
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    
+    /// A `-` directly before a parenthesis or quote
+    Minus,
+}
+
+/// Split a query into tokens
+fn lex(query: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = query.trim_start();
+    
+    while let Some(c) = rest.chars().next() {
+        let (token, remainder) = match c {
+            '(' => (Token::LeftParen, &rest[1..]),
+            ')' => (Token::RightParen, &rest[1..]),
+            '"' => {
+                // An unclosed quote runs to the end of the query
+                let text = &rest[1..];
+                match text.find('"') {
+                    Some(closing) => (Token::Phrase(&text[..closing]), &text[closing + 1..]),
+                    None => (Token::Phrase(text), ""),
+                }
+            }
+            '-' if rest[1..].starts_with(['(', '"']) => (Token::Minus, &rest[1..]),
+            _ => {
+                let (word, remainder) = next_word(rest);
+                let token = match word {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    word => Token::Word(word),
+                };
+                (token, remainder)
+            }
+        };
+        
+        if !matches!(token, Token::Phrase(text) if text.trim().is_empty()) {
+            tokens.push(token);
+        }
+        rest = remainder.trim_start();
+    }
+    
+    tokens
+}
+
+/// Split the next word off a query that doesn't start with whitespace
+///
+/// Words end at whitespace or a parenthesis, except that the quoted term of a
+/// `field:"term"` word runs to its closing quote.
+fn next_word(query: &str) -> (&str, &str) {
+    let mut end = query.find(|c: char| c.is_whitespace() || c == '(' || c == ')').unwrap_or(query.len());
+    if let Some(quote) = query[..end].find(":\"") {
+        let term_start = quote + 2;
+        if let Some(closing) = query[term_start..].find('"') {
+            end = end.max(term_start + closing + 1);
+        }
+    }
+    query.split_at(end)
+}
+
+/// Split a `field:term` word into its field name and term, unquoting the term
+fn split_field_scope<'w>(word: &'w str, is_field: &dyn Fn(&str) -> bool) -> Option<(&'w str, &'w str)> {
+    let (field, term) = word.split_once(':')?;
+    let valid_field = field.chars().next().is_some_and(char::is_alphabetic)
+        && field.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        && is_field(field);
+    let term = term
+        .strip_prefix('"')
+        .and_then(|term| term.strip_suffix('"'))
+        .unwrap_or(term);
+    (valid_field && !term.is_empty()).then_some((field, term))
+}
+
+/// Interpret a word: `*` matches everything, `field:term` is field-scoped, and
+/// a trailing `*` makes a prefix; other words made only of `*` are ignored
+fn word_query(word: &str, is_field: &dyn Fn(&str) -> bool) -> Option<QueryAst> {
+    if word == "*" {
+        Some(QueryAst::MatchAll)
+    } else if let Some((field, term)) = split_field_scope(word, is_field) {
+        Some(QueryAst::field(field, term))
+    } else if let Some(prefix) = word.strip_suffix('*') {
+        let prefix = prefix.trim_end_matches('*');
+        (!prefix.is_empty()).then(|| QueryAst::prefix(prefix))
+    } else {
+        Some(QueryAst::term(word))
+    }
+}
+
+/// Recursive-descent parser over query tokens
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    
+    /// Whether a name before a colon is a field
+    is_field: &'a dyn Fn(&str) -> bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Get the next token without consuming it
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+    
+    /// Parse adjacent clauses up to the end of the query, or of the group when `nested`
+    ///
+    /// Adjacent clauses are alternatives, and negated ones exclude documents from all of them.
+    fn parse_sequence(&mut self, nested: bool) -> QueryAst {
+        let mut clauses = Vec::new();
+        while let Some(token) = self.peek() {
+            match token {
+                Token::RightParen if nested => break,
+                // Operators without a left operand, and unopened parentheses, are ignored
+                Token::RightParen | Token::And | Token::Or => self.position += 1,
+                _ => clauses.extend(self.parse_or()),
+            }
+        }
+        
+        let (negated, positive): (Vec<QueryAst>, Vec<QueryAst>) =
+            clauses.into_iter().partition(|clause| matches!(clause, QueryAst::Not(_)));
+        if positive.is_empty() || negated.is_empty() {
+            QueryAst::or(positive.into_iter().chain(negated))
+        } else {
+            QueryAst::and(std::iter::once(QueryAst::or(positive)).chain(negated))
+        }
+    }
+    
+    /// Parse clauses joined by `OR`
+    fn parse_or(&mut self) -> Option<QueryAst> {
+        let mut children: Vec<QueryAst> = self.parse_and().into_iter().collect();
+        while self.peek() == Some(Token::Or) {
+            self.position += 1;
+            children.extend(self.parse_and());
+        }
+        (!children.is_empty()).then(|| QueryAst::or(children))
+    }
+    
+    /// Parse clauses joined by `AND`
+    fn parse_and(&mut self) -> Option<QueryAst> {
+        let mut children: Vec<QueryAst> = self.parse_unary().into_iter().collect();
+        while self.peek() == Some(Token::And) {
+            self.position += 1;
+            children.extend(self.parse_unary());
+        }
+        (!children.is_empty()).then(|| QueryAst::and(children))
+    }
+    
+    /// Parse a possibly negated word, phrase, or group
+    ///
+    /// Returns `None`, consuming nothing, at an operator or closing
+    /// parenthesis, and after consuming something that matches nothing.
+    fn parse_unary(&mut self) -> Option<QueryAst> {
+        let token = self.peek()?;
+        if matches!(token, Token::And | Token::Or | Token::RightParen) {
+            return None;
+        }
+        self.position += 1;
+        
+        match token {
+            Token::Not | Token::Minus => self.parse_unary().map(QueryAst::not),
+            Token::LeftParen => {
+                let group = self.parse_sequence(true);
+                if self.peek() == Some(Token::RightParen) {
+                    self.position += 1;
+                }
+                (group != QueryAst::Or(Vec::new())).then_some(group)
+            }
+            Token::Phrase(text) => Some(QueryAst::phrase(text)),
+            Token::Word(word) => match word.strip_prefix('-').filter(|term| !term.is_empty()) {
+                Some(term) => word_query(term, self.is_field).map(QueryAst::not),
+                None => word_query(word, self.is_field),
+            },
+            Token::And | Token::Or | Token::RightParen => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn term(text: &str) -> QueryAst {
+        QueryAst::term(text)
+    }
+    
+    #[test]
+    fn test_parse_plain_query() {
+        assert_eq!(QueryAst::parse("laptop"), term("laptop"));
+        assert_eq!(QueryAst::parse("laptop -apple"), QueryAst::And(vec![term("laptop"), !term("apple")]));
+        assert_eq!(
+            QueryAst::parse("wi-fi - router -cheap -used"),
+            QueryAst::And(vec![
+                QueryAst::Or(vec![term("wi-fi"), term("-"), term("router")]),
+                !term("cheap"),
+                !term("used"),
+            ])
+        );
+        assert_eq!(QueryAst::parse("-apple"), !term("apple"));
+        assert_eq!(
+            QueryAst::parse("app* phone ** sam**"),
+            QueryAst::Or(vec![QueryAst::prefix("app"), term("phone"), QueryAst::prefix("sam")])
+        );
+        assert_eq!(QueryAst::parse("* -apple"), QueryAst::And(vec![QueryAst::MatchAll, !term("apple")]));
+        assert_eq!(QueryAst::parse("   "), QueryAst::Or(Vec::new()));
+    }
+    
+    #[test]
+    fn test_parse_field_scope() {
+        assert_eq!(
+            QueryAst::parse("title:rust 12:30 :x tag: rust"),
+            QueryAst::Or(vec![QueryAst::field("title", "rust"), term("12:30"), term(":x"), term("tag:"), term("rust")])
+        );
+        
+        // Quoted terms keep their spaces
+        assert_eq!(
+            QueryAst::parse("status:\"in  stock\" laptop color:\"red\" tag:\"open"),
+            QueryAst::Or(vec![
+                QueryAst::field("status", "in  stock"),
+                term("laptop"),
+                QueryAst::field("color", "red"),
+                QueryAst::field("tag", "\"open"),
+            ])
+        );
+        assert_eq!(QueryAst::parse("status:\"a (b)\""), QueryAst::field("status", "a (b)"));
+    }
+    
+    #[test]
+    fn test_parse_known_fields() {
+        let parse = |query| QueryAst::parse_with_fields(query, |field| ["title", "status"].contains(&field));
+        
+        // URLs and unknown prefixes stay plain terms
+        assert_eq!(parse("http://example.com"), term("http://example.com"));
+        assert_eq!(
+            parse("re:invoice AND note:urgent"),
+            QueryAst::And(vec![term("re:invoice"), term("note:urgent")])
+        );
+        assert_eq!(parse("-note:urgent laptop"), QueryAst::And(vec![term("laptop"), !term("note:urgent")]));
+        assert_eq!(parse("note:\"in stock\""), term("note:\"in stock\""));
+        
+        // Known fields are still scoped
+        assert_eq!(
+            parse("title:rust OR status:\"in stock\""),
+            QueryAst::Or(vec![QueryAst::field("title", "rust"), QueryAst::field("status", "in stock")])
+        );
+    }
+    
+    #[test]
+    fn test_parse_precedence() {
+        let (a, b, c, d) = (term("a"), term("b"), term("c"), term("d"));
+        
+        // NOT binds tighter than AND, which binds tighter than OR
+        assert_eq!(QueryAst::parse("a OR b AND c"), QueryAst::Or(vec![a.clone(), QueryAst::And(vec![b.clone(), c.clone()])]));
+        assert_eq!(QueryAst::parse("a AND b OR c"), QueryAst::Or(vec![QueryAst::And(vec![a.clone(), b.clone()]), c.clone()]));
+        assert_eq!(QueryAst::parse("a AND NOT b"), QueryAst::And(vec![a.clone(), !b.clone()]));
+        assert_eq!(QueryAst::parse("NOT a AND b"), QueryAst::And(vec![!a.clone(), b.clone()]));
+        
+        // Adjacent clauses bind loosest
+        assert_eq!(QueryAst::parse("a b AND c"), QueryAst::Or(vec![a.clone(), QueryAst::And(vec![b.clone(), c.clone()])]));
+        assert_eq!(QueryAst::parse("a OR b c"), QueryAst::Or(vec![a.clone(), b.clone(), c.clone()]));
+        assert_eq!(
+            QueryAst::parse("a b NOT c AND d"),
+            QueryAst::Or(vec![a.clone(), b.clone(), QueryAst::And(vec![!c.clone(), d.clone()])])
+        );
+        assert_eq!(QueryAst::parse("a AND b AND c"), QueryAst::And(vec![a.clone(), b.clone(), c.clone()]));
+        
+        // Lowercase operators are plain words
+        assert_eq!(QueryAst::parse("a or b"), QueryAst::Or(vec![a, term("or"), b]));
+    }
+    
+    #[test]
+    fn test_parse_nesting() {
+        let (a, b, c, d) = (term("a"), term("b"), term("c"), term("d"));
+        
+        assert_eq!(QueryAst::parse("(a OR b) AND c"), QueryAst::And(vec![QueryAst::Or(vec![a.clone(), b.clone()]), c.clone()]));
+        assert_eq!(
+            QueryAst::parse("a AND (b OR (c AND NOT d))"),
+            QueryAst::And(vec![
+                a.clone(),
+                QueryAst::Or(vec![b.clone(), QueryAst::And(vec![c.clone(), !d.clone()])]),
+            ])
+        );
+        assert_eq!(QueryAst::parse("-(a b) c"), QueryAst::And(vec![c.clone(), !QueryAst::Or(vec![a.clone(), b.clone()])]));
+        assert_eq!(QueryAst::parse("((a))"), a.clone());
+        
+        // Phrases and field scopes inside groups
+        assert_eq!(
+            QueryAst::parse("(\"tiger cache\" OR title:rust) -\"old news\""),
+            QueryAst::And(vec![
+                QueryAst::Or(vec![QueryAst::phrase("tiger cache"), QueryAst::field("title", "rust")]),
+                !QueryAst::phrase("old news"),
+            ])
+        );
+        
+        // Unbalanced parentheses and dangling operators are tolerated
+        assert_eq!(QueryAst::parse("(a AND b"), QueryAst::And(vec![a.clone(), b.clone()]));
+        assert_eq!(QueryAst::parse("a) OR b"), QueryAst::Or(vec![a.clone(), b.clone()]));
+        assert_eq!(QueryAst::parse("AND a OR"), a.clone());
+        assert_eq!(QueryAst::parse("a () NOT"), a);
+        assert_eq!(QueryAst::parse("\"unclosed phrase"), QueryAst::phrase("unclosed phrase"));
+    }
+    
+    #[test]
+    fn test_display() {
+        for query in ["a OR (b AND c)", "(a OR b) AND NOT c", "\"tiger cache\" OR title:rust", "status:\"in stock\" AND app*", "* AND NOT (a OR b)"] {
+            assert_eq!(QueryAst::parse(query).to_string(), query);
+            assert_eq!(QueryAst::parse(&QueryAst::parse(query).to_string()), QueryAst::parse(query));
+        }
+    }
+}
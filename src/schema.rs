@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::document::Document;
+use crate::error::{Result, TigerCacheError};
+
+/// JSON type expected for a document field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldType {
+    String,
+    Number,
+    Bool,
+    Array,
+}
+
+impl FieldType {
+    /// Check whether a field value has this type
+    fn matches(self, value: &serde_json::Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Number => value.is_number(),
+            FieldType::Bool => value.is_boolean(),
+            FieldType::Array => value.is_array(),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FieldType::String => "string",
+            FieldType::Number => "number",
+            FieldType::Bool => "bool",
+            FieldType::Array => "array",
+        })
+    }
+}
+
+/// Expected type of a field and whether documents must have it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    /// Type the field's value must have
+    pub field_type: FieldType,
+    
+    /// Whether documents without the field (or with a null value) are rejected
+    pub required: bool,
+}
+
+/// Field types documents must conform to before they are indexed
+///
+/// Fields not listed in the schema are accepted with any value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+    /// Expected field types by field name
+    pub fields: HashMap<String, FieldSpec>,
+}
+
+impl Schema {
+    /// Create an empty schema, accepting every document
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add an optional field, which must have the given type when present
+    pub fn with_field(mut self, name: impl Into<String>, field_type: FieldType) -> Self {
+        self.fields.insert(name.into(), FieldSpec { field_type, required: false });
+        self
+    }
+    
+    /// Add a field every document must have, with the given type
+    pub fn with_required_field(mut self, name: impl Into<String>, field_type: FieldType) -> Self {
+        self.fields.insert(name.into(), FieldSpec { field_type, required: true });
+        self
+    }
+    
+    /// Check a document against the schema
+    ///
+    /// Returns `TigerCacheError::InvalidDocument` naming the document and the
+    /// first offending field, in field name order. Null values count as missing.
+    pub fn validate(&self, document: &Document) -> Result<()> {
+        let mut fields: Vec<(&String, &FieldSpec)> = self.fields.iter().collect();
+        fields.sort_unstable_by_key(|(name, _)| *name);
+        
+        for (name, spec) in fields {
+            match document.fields.get(name).filter(|value| !value.is_null()) {
+                None if spec.required => {
+                    return Err(TigerCacheError::InvalidDocument(format!(
+                        "document {} is missing required field {name}", document.id
+                    )));
+                }
+                Some(value) if !spec.field_type.matches(value) => {
+                    return Err(TigerCacheError::InvalidDocument(format!(
+                        "field {name} of document {} must be a {}", document.id, spec.field_type
+                    )));
+                }
+                _ => {}
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn schema() -> Schema {
+        Schema::new()
+            .with_required_field("title", FieldType::String)
+            .with_field("price", FieldType::Number)
+            .with_field("in_stock", FieldType::Bool)
+            .with_field("tags", FieldType::Array)
+    }
+    
+    #[test]
+    fn test_valid_documents() {
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone")
+            .add_field("price", 999.0)
+            .add_field("in_stock", true)
+            .add_field("tags", vec!["phone"])
+            .add_field("color", 3);
+        assert!(schema().validate(&doc).is_ok());
+        
+        // Optional fields may be left out
+        let mut doc = Document::new("doc2");
+        doc.add_field("title", "Apple Watch");
+        assert!(schema().validate(&doc).is_ok());
+        assert!(Schema::new().validate(&Document::new("doc3")).is_ok());
+    }
+    
+    #[test]
+    fn test_missing_required_field() {
+        let mut doc = Document::new("doc1");
+        doc.add_field("price", 999.0);
+        let err = schema().validate(&doc).unwrap_err();
+        assert!(matches!(err, TigerCacheError::InvalidDocument(ref msg) if msg.contains("required field title")));
+        
+        doc.add_field("title", serde_json::Value::Null);
+        assert!(schema().validate(&doc).is_err());
+    }
+    
+    #[test]
+    fn test_type_mismatch() {
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone").add_field("price", "cheap");
+        let err = schema().validate(&doc).unwrap_err();
+        assert!(matches!(err, TigerCacheError::InvalidDocument(ref msg) if msg.contains("price") && msg.contains("number")));
+        
+        let mut doc = Document::new("doc2");
+        doc.add_field("title", 42);
+        assert!(schema().validate(&doc).is_err());
+    }
+}
@@ -1,22 +1,187 @@
 use crate::document::Document;
-use crate::error::Result;
+use crate::error::{Result, TigerCacheError};
 use crate::index::Index;
-use crate::trigram::extract_tokens;
+use crate::postings::FieldMask;
+use crate::query::QueryAst;
 use levenshtein::levenshtein;
 use lru::LruCache;
 use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::num::NonZeroUsize;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use ordered_float::OrderedFloat;
+use roaring::RoaringBitmap;
+
+/// Score multiplier for matches found through a synonym rather than the query term itself
+const SYNONYM_WEIGHT: f64 = 0.8;
+
+/// Score every document receives from a bare `*` (match-all) term
+const MATCH_ALL_SCORE: f64 = 1.0;
 
 /// Search result with document and score
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     /// The matched document
     pub document: Document,
     
     /// Relevance score (higher is better)
     pub score: f64,
+    
+    /// Number of matching documents sharing this one's `group_by` value, itself
+    /// included; `None` when results aren't grouped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_size: Option<usize>,
+}
+
+impl fmt::Display for SearchResult {
+    /// Format as the document ID and score, followed by the document's fields in name order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (score {:.3})", self.document.id, self.score)?;
+        
+        let mut names: Vec<&String> = self.document.fields.keys().collect();
+        names.sort();
+        for (i, name) in names.into_iter().enumerate() {
+            let separator = if i == 0 { ": " } else { ", " };
+            let value = self.document.get_text_field(name).unwrap_or_default();
+            write!(f, "{separator}{name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Breakdown of how a search result's score was computed
+#[derive(Debug, Clone, Default)]
+pub struct ScoreExplanation {
+    /// Final score of the result (the sum of the match contributions times the boost, decay and length norm)
+    pub score: f64,
+    
+    /// Per-document multiplier applied to the summed contributions
+    pub boost: f64,
+    
+    /// Freshness decay factor applied to the summed contributions (1.0 without decay)
+    pub decay: f64,
+    
+    /// Length normalization factor applied to the summed contributions (1.0 when disabled)
+    pub length_norm: f64,
+    
+    /// Indexed tokens that matched, highest contribution first
+    pub matches: Vec<TokenMatch>,
+}
+
+/// A single indexed token matched by a query token
+#[derive(Debug, Clone)]
+pub struct TokenMatch {
+    /// Query token (or synonym) that produced the match
+    pub query_token: String,
+    
+    /// Indexed token that was matched
+    pub token: String,
+    
+    /// Levenshtein distance between the query token and the indexed token
+    ///
+    /// Under `DistanceMetric::JaroWinkler`, 0 for exact matches and 1 otherwise.
+    pub distance: usize,
+    
+    /// Fraction of n-grams shared by the query token and the indexed token
+    ///
+    /// For prefix (`app*`) matches, the fraction of the token covered by the prefix.
+    pub trigram_overlap: f64,
+    
+    /// Weight applied for synonym matches (1.0 for the query term itself)
+    pub weight: f64,
+    
+    /// Amount this match added to the document's score
+    pub contribution: f64,
+}
+
+/// Timing and counts of a single search, passed to a metrics observer
+#[derive(Debug, Clone)]
+pub struct QueryMetrics {
+    /// The query string
+    pub query: String,
+    
+    /// Time taken by the search, including loading documents
+    pub duration: Duration,
+    
+    /// Number of indexed tokens that matched a query token (0 for cache hits)
+    pub candidate_tokens: usize,
+    
+    /// Number of results returned
+    pub result_count: usize,
+    
+    /// Whether the results came from the query cache
+    pub cache_hit: bool,
+    
+    /// Whether the search hit `SearchOptions::timeout`, returning only the results gathered by then
+    pub partial: bool,
+}
+
+/// A search that took longer than the configured slow-query threshold
+#[derive(Debug, Clone)]
+pub struct SlowQuery {
+    /// The query string
+    pub query: String,
+    
+    /// Options the search ran with (defaults if none were given)
+    pub options: SearchOptions,
+    
+    /// Time spent searching the index and loading results (cache lookups excluded)
+    pub elapsed: Duration,
+    
+    /// Number of results returned
+    pub result_count: usize,
+}
+
+/// Counts collected while ranking documents
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SearchStats {
+    /// Number of indexed tokens within the allowed edit distance of a query token
+    pub candidate_tokens: usize,
+    
+    /// Whether ranking stopped early at the search's deadline
+    pub partial: bool,
+}
+
+/// A document ID with the score and details collected while ranking it
+#[derive(Debug)]
+struct RankedDocument {
+    doc_id: String,
+    score: f64,
+    explanation: Option<ScoreExplanation>,
+    group_size: Option<usize>,
+}
+
+/// Score components of a candidate token, kept until documents are scored
+#[derive(Debug, Clone, Copy)]
+struct CandidateScore {
+    distance: usize,
+    
+    /// Closeness to the query token, from 0 to 1, under the distance metric
+    similarity: f64,
+    
+    trigram_score: f64,
+    weight: f64,
+    
+    /// Edit distance allowed for the matching query token
+    max_distance: usize,
+    
+    /// Whether the token shares the query token's Soundex code, matching at any distance
+    phonetic: bool,
+    
+    /// Position of the matching query token in the expanded token list
+    query_index: usize,
+}
+
+impl CandidateScore {
+    /// Get the score a document gets for each occurrence of the token
+    fn score(&self, exact_match_boost: f64) -> f64 {
+        token_score(self.distance, self.similarity, self.trigram_score, self.weight, exact_match_boost)
+    }
 }
 
 /// Search configuration options
@@ -25,11 +190,285 @@ pub struct SearchOptions {
     /// Maximum Levenshtein distance for fuzzy matching (default: 2)
     pub max_distance: usize,
     
+    /// Maximum Levenshtein distance for matches in particular fields, overriding
+    /// `max_distance` (and `adaptive_distance`) there; unlisted fields use the
+    /// global limit. Ignored by `DistanceMetric::JaroWinkler` and for phonetic
+    /// matches (default: empty)
+    pub field_max_distance: BTreeMap<String, usize>,
+    
     /// Minimum score threshold for results (default: 0.0)
     pub score_threshold: u32, // Changed to u32 for hash compatibility
     
     /// Maximum number of results to return (default: 100)
     pub limit: usize,
+    
+    /// Derive the allowed edit distance from each query token's length instead
+    /// of using `max_distance`: 0 for up to 3 characters, 1 for 4-6, 2 for 7+ (default: false)
+    pub adaptive_distance: bool,
+    
+    /// Decay scores by document age, favouring recent documents (default: None)
+    pub freshness: Option<FreshnessSpec>,
+    
+    /// Dampen scores of long documents by their indexed token count, so short
+    /// precise matches aren't outranked by documents that merely contain more text (default: false)
+    pub length_normalization: bool,
+    
+    /// Scale each matched token's score by `1 + ln(tf)`, where tf is how often
+    /// the document contains the token, favouring documents repeating query terms (default: false)
+    pub term_frequency: bool,
+    
+    /// Fields to include in result documents, in addition to the ID; `None`
+    /// returns full documents (default: None)
+    pub return_fields: Option<Vec<String>>,
+    
+    /// Documents placed ahead of all other results, in this order, whenever they
+    /// match the query; they're kept even below `score_threshold` and count
+    /// towards `limit` (default: empty)
+    pub pinned_ids: Vec<String>,
+    
+    /// Minimum number of distinct query terms a document must match; `None` returns
+    /// documents matching any term, and the query's term count requires all of them.
+    /// Plain, prefix, and field-scoped terms each count, while a bare `*` doesn't (default: None)
+    pub min_should_match: Option<usize>,
+    
+    /// Collapse results sharing a value of this field into the highest-ranked one,
+    /// reporting the group's size in `SearchResult::group_size`. Documents missing
+    /// the field, or not held in memory by the index, are each their own group (default: None)
+    pub group_by: Option<String>,
+    
+    /// Multiplier applied to the score of exact (distance 0) token matches, in
+    /// thousandths like `score_threshold`; 1000 scores exact and fuzzy matches alike (default: 5000)
+    pub exact_match_boost: u32,
+    
+    /// Match only exact tokens first, falling back to fuzzy matching when that
+    /// returns fewer than `limit` results (default: false)
+    pub fuzzy_fallback: bool,
+    
+    /// How indexed tokens are compared with query tokens (default: `DistanceMetric::Levenshtein`)
+    pub distance_metric: DistanceMetric,
+    
+    /// Also match tokens sounding like plain query terms (sharing their Soundex
+    /// code), however far apart they're spelled; requires an index built with
+    /// the phonetic index enabled (default: false)
+    pub phonetic: bool,
+    
+    /// Time budget for ranking; once it's spent, the search stops gathering
+    /// matches and returns the best results found so far. Checked between query
+    /// terms, so a search can overrun it by the time one term takes. Metrics
+    /// observers see such searches as `QueryMetrics::partial` (default: None)
+    pub timeout: Option<Duration>,
+    
+    /// Conditions every result document must meet. Documents not held in
+    /// memory by the index can't be checked against `Filter::Exists`, or a
+    /// `Filter::Range` outside the numeric index, and never match them (default: empty)
+    pub filters: Vec<Filter>,
+}
+
+/// Condition on a document's fields that search results must meet
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// The document has (`present: true`) or lacks (`present: false`) the
+    /// field; a field set to null counts as present
+    Exists { field: String, present: bool },
+    
+    /// The document's keyword field holds exactly `value` (see
+    /// `TigerCacheConfig::keyword_fields`); never matches other fields
+    Keyword { field: String, value: String },
+    
+    /// The document's numeric field lies within the inclusive bounds; `None`
+    /// leaves that end open. Resolved from the numeric index for fields listed in
+    /// `TigerCacheConfig::numeric_fields`, and by checking each document otherwise
+    Range { field: String, min: Option<OrderedFloat<f64>>, max: Option<OrderedFloat<f64>> },
+}
+
+impl Filter {
+    /// Match documents that have `field`
+    pub fn exists(field: impl Into<String>) -> Self {
+        Filter::Exists { field: field.into(), present: true }
+    }
+    
+    /// Match documents that lack `field`
+    pub fn missing(field: impl Into<String>) -> Self {
+        Filter::Exists { field: field.into(), present: false }
+    }
+    
+    /// Match documents whose keyword field holds exactly `value`
+    pub fn keyword(field: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::Keyword { field: field.into(), value: value.into() }
+    }
+    
+    /// Match documents whose numeric field lies within the inclusive bounds
+    pub fn range(field: impl Into<String>, min: Option<f64>, max: Option<f64>) -> Self {
+        Filter::Range { field: field.into(), min: min.map(OrderedFloat), max: max.map(OrderedFloat) }
+    }
+    
+    /// Get the full set of matching documents when an index can provide it without checking each one
+    fn resolve(&self, index: &Index) -> Option<RoaringBitmap> {
+        match self {
+            Filter::Range { field, min, max } => {
+                index.numeric_range_documents(field, min.map(|min| min.0), max.map(|max| max.0))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Check whether an indexed document meets the condition
+    fn matches(&self, index: &Index, doc_id: &str) -> bool {
+        match self {
+            Filter::Exists { field, present } => index
+                .get_document(doc_id)
+                .is_some_and(|document| document.fields.contains_key(field) == *present),
+            Filter::Keyword { field, value } => index.has_keyword(doc_id, field, value),
+            Filter::Range { field, min, max } => index
+                .get_document(doc_id)
+                .and_then(|document| document.get_number_field(field))
+                .is_some_and(|value| min.is_none_or(|min| value >= min.0) && max.is_none_or(|max| value <= max.0)),
+        }
+    }
+}
+
+/// Measure of how close an indexed token is to a query token
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DistanceMetric {
+    /// Edit distance, limited by `max_distance`
+    #[default]
+    Levenshtein,
+    
+    /// Jaro-Winkler similarity, which favours strings sharing a prefix and
+    /// tolerates transpositions; often a better fit for short strings like names.
+    /// Tokens are kept when their similarity is at least `threshold`, given in
+    /// thousandths like `score_threshold` (900 keeps similarities of 0.9 and up)
+    JaroWinkler { threshold: u32 },
+}
+
+impl DistanceMetric {
+    /// Measure an indexed token against a query token, as an edit distance and a
+    /// similarity from 0 to 1
+    fn measure(&self, query_token: &str, token: &str) -> (usize, f64) {
+        match self {
+            Self::Levenshtein => {
+                let distance = levenshtein(query_token, token);
+                (distance, 1.0 / (distance as f64 + 1.0))
+            }
+            Self::JaroWinkler { .. } => (usize::from(query_token != token), jaro_winkler(query_token, token)),
+        }
+    }
+    
+    /// Whether a measured token is close enough to the query token to match it
+    fn allows(&self, distance: usize, similarity: f64, max_distance: usize) -> bool {
+        match self {
+            Self::Levenshtein => distance <= max_distance,
+            Self::JaroWinkler { threshold } => similarity >= *threshold as f64 / 1000.0,
+        }
+    }
+}
+
+/// Compute the Jaro-Winkler similarity of two strings, from 0 (nothing in common) to 1 (equal)
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    
+    // Characters match when equal and no further apart than half the longer string
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, &c) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == c) {
+            b_matched[j] = true;
+            a_matches.push(c);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+    
+    // Matched characters out of order count as half a transposition each
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, &matched)| matched).map(|(c, _)| c);
+    let transpositions = a_matches.iter().zip(b_matches).filter(|(x, y)| x != y).count() / 2;
+    
+    let matches = a_matches.len() as f64;
+    let jaro = (matches / a.len() as f64
+        + matches / b.len() as f64
+        + (matches - transpositions as f64) / matches) / 3.0;
+    
+    // Boost strings sharing a prefix of up to 4 characters
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Exponential decay of scores by document age
+///
+/// A document's score is halved for every `half_life` that has passed since the
+/// timestamp in `field`, read as seconds since the Unix epoch. Documents without a
+/// numeric value in the field, or with a timestamp in the future, are not decayed;
+/// neither are documents the index doesn't hold in memory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FreshnessSpec {
+    /// Document field holding the timestamp
+    pub field: String,
+    
+    /// Age at which a document's score is halved
+    pub half_life: Duration,
+}
+
+impl FreshnessSpec {
+    /// Create a decay spec reading timestamps from `field`
+    pub fn new(field: impl Into<String>, half_life: Duration) -> Self {
+        Self {
+            field: field.into(),
+            half_life,
+        }
+    }
+    
+    /// Get the decay factor of a document with the given timestamp, `now` being seconds since the epoch
+    fn decay(&self, timestamp: f64, now: f64) -> f64 {
+        let half_life = self.half_life.as_secs_f64();
+        let age = now - timestamp;
+        if !age.is_finite() || age <= 0.0 || half_life <= 0.0 {
+            return 1.0;
+        }
+        0.5f64.powf(age / half_life)
+    }
+}
+
+/// Aggregation computed over a numeric field of matching documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AggKind {
+    /// Smallest value
+    Min,
+    
+    /// Largest value
+    Max,
+    
+    /// Mean of the values
+    Avg,
+    
+    /// Total of the values
+    Sum,
+}
+
+impl AggKind {
+    /// Aggregate values; `Sum` of no values is 0, and the others are NaN
+    pub(crate) fn apply(self, values: impl IntoIterator<Item = f64>) -> f64 {
+        let (count, sum, min, max) = values.into_iter().fold(
+            (0usize, 0.0, f64::INFINITY, f64::NEG_INFINITY),
+            |(count, sum, min, max), value| (count + 1, sum + value, min.min(value), max.max(value)),
+        );
+        if count == 0 && self != AggKind::Sum {
+            return f64::NAN;
+        }
+        match self {
+            AggKind::Min => min,
+            AggKind::Max => max,
+            AggKind::Avg => sum / count as f64,
+            AggKind::Sum => sum,
+        }
+    }
 }
 
 /// Cached search engine with LRU cache
@@ -58,8 +497,24 @@ impl CachedSearchEngine {
         let score_threshold = options.score_threshold as f64 / 1000.0; // Convert back to f64
         let search_opts = SearchOptionsInternal {
             max_distance: options.max_distance,
+            field_max_distance: options.field_max_distance.clone(),
             score_threshold,
             limit: options.limit,
+            adaptive_distance: options.adaptive_distance,
+            freshness: options.freshness.clone(),
+            length_normalization: options.length_normalization,
+            term_frequency: options.term_frequency,
+            return_fields: options.return_fields.clone(),
+            pinned_ids: options.pinned_ids.clone(),
+            min_should_match: options.min_should_match,
+            group_by: options.group_by.clone(),
+            exact_match_boost: options.exact_match_boost as f64 / 1000.0,
+            fuzzy_fallback: options.fuzzy_fallback,
+            distance_metric: options.distance_metric,
+            phonetic: options.phonetic,
+            deadline: deadline_after(options.timeout),
+            cancel: None,
+            filters: options.filters.clone(),
         };
         
         let results = index.search_internal(query, search_opts)?;
@@ -77,16 +532,106 @@ impl CachedSearchEngine {
 #[derive(Debug, Clone)]
 pub(crate) struct SearchOptionsInternal {
     pub max_distance: usize,
+    pub field_max_distance: BTreeMap<String, usize>,
     pub score_threshold: f64,
     pub limit: usize,
+    pub adaptive_distance: bool,
+    pub freshness: Option<FreshnessSpec>,
+    pub length_normalization: bool,
+    pub term_frequency: bool,
+    pub return_fields: Option<Vec<String>>,
+    pub pinned_ids: Vec<String>,
+    pub min_should_match: Option<usize>,
+    pub group_by: Option<String>,
+    pub exact_match_boost: f64,
+    pub fuzzy_fallback: bool,
+    pub distance_metric: DistanceMetric,
+    pub phonetic: bool,
+    pub deadline: Option<Instant>,
+    pub cancel: Option<Arc<AtomicBool>>,
+    pub filters: Vec<Filter>,
+}
+
+impl SearchOptionsInternal {
+    /// Get the maximum edit distance allowed for a query token
+    fn max_distance_for(&self, query_token: &str) -> usize {
+        if !self.adaptive_distance {
+            return self.max_distance;
+        }
+        
+        match query_token.chars().count() {
+            0..=3 => 0,
+            4..=6 => 1,
+            _ => 2,
+        }
+    }
+    
+    /// Get the largest edit distance a query token may match at in any field
+    fn loosest_distance_for(&self, query_token: &str) -> usize {
+        self.field_max_distance.values().copied().fold(self.max_distance_for(query_token), usize::max)
+    }
+    
+    /// Get the fields in which a token `distance` edits from the query token
+    /// matches, when unlisted fields allow up to `max_distance`
+    fn fields_within(&self, index: &Index, distance: usize, max_distance: usize) -> FieldMask {
+        let field_bits = |include: &dyn Fn(usize) -> bool| -> FieldMask {
+            self.field_max_distance
+                .iter()
+                .filter(|&(_, &limit)| include(limit))
+                .filter_map(|(field, _)| index.field_mask(field))
+                .fold(0, |mask, bit| mask | bit)
+        };
+        if distance <= max_distance {
+            FieldMask::MAX & !field_bits(&|limit| limit < distance)
+        } else {
+            field_bits(&|limit| limit >= distance)
+        }
+    }
+    
+    /// Check whether ranking should stop early
+    ///
+    /// Fails once the cancel flag is set; otherwise returns whether the search
+    /// is past its deadline, flagging it as partial if so.
+    fn should_stop(&self, stats: &mut SearchStats) -> Result<bool> {
+        if self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            return Err(TigerCacheError::Cancelled);
+        }
+        
+        let timed_out = self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        stats.partial |= timed_out;
+        Ok(timed_out)
+    }
+    
+    /// Build the document returned in a result, keeping only `return_fields` if set
+    fn result_document(&self, document: &Document) -> Document {
+        match &self.return_fields {
+            Some(fields) => document.project(fields),
+            None => document.clone(),
+        }
+    }
 }
 
 impl Default for SearchOptions {
     fn default() -> Self {
         Self {
             max_distance: 2,
+            field_max_distance: BTreeMap::new(),
             score_threshold: 0, // 0.0 represented as 0
             limit: 100,
+            adaptive_distance: false,
+            freshness: None,
+            length_normalization: false,
+            term_frequency: false,
+            return_fields: None,
+            pinned_ids: Vec::new(),
+            min_should_match: None,
+            group_by: None,
+            exact_match_boost: 5000, // 5.0 represented as 5000
+            fuzzy_fallback: false,
+            distance_metric: DistanceMetric::Levenshtein,
+            phonetic: false,
+            timeout: None,
+            filters: Vec::new(),
         }
     }
 }
@@ -95,12 +640,60 @@ impl From<SearchOptions> for SearchOptionsInternal {
     fn from(opts: SearchOptions) -> Self {
         Self {
             max_distance: opts.max_distance,
+            field_max_distance: opts.field_max_distance,
             score_threshold: opts.score_threshold as f64 / 1000.0,
             limit: opts.limit,
+            adaptive_distance: opts.adaptive_distance,
+            freshness: opts.freshness,
+            length_normalization: opts.length_normalization,
+            term_frequency: opts.term_frequency,
+            return_fields: opts.return_fields,
+            pinned_ids: opts.pinned_ids,
+            min_should_match: opts.min_should_match,
+            group_by: opts.group_by,
+            exact_match_boost: opts.exact_match_boost as f64 / 1000.0,
+            fuzzy_fallback: opts.fuzzy_fallback,
+            distance_metric: opts.distance_metric,
+            phonetic: opts.phonetic,
+            deadline: deadline_after(opts.timeout),
+            cancel: None,
+            filters: opts.filters,
         }
     }
 }
 
+/// Get the deadline of a search starting now, or `None` for no (or an unrepresentable) timeout
+fn deadline_after(timeout: Option<Duration>) -> Option<Instant> {
+    timeout.and_then(|timeout| Instant::now().checked_add(timeout))
+}
+
+/// Details collected across a query's clauses while scoring documents
+#[derive(Debug, Default)]
+struct ScoringContext {
+    /// Per-token breakdown of each document's score, when explaining
+    explanations: Option<FxHashMap<String, Vec<TokenMatch>>>,
+    
+    /// Distinct query terms matched by each document, when `min_should_match` is set
+    matched_terms: Option<FxHashMap<String, FxHashSet<usize>>>,
+    
+    /// Number of query terms numbered so far, across all clauses
+    terms: usize,
+}
+
+/// Score a matched token from its similarity and n-gram overlap with the query token
+fn token_score(distance: usize, similarity: f64, trigram_score: f64, weight: f64, exact_match_boost: f64) -> f64 {
+    // Calculate token score combining similarity and trigram overlap
+    let combined_score = similarity * (1.0 + trigram_score);
+    
+    // Boost exact matches
+    let score = if distance == 0 {
+        combined_score * exact_match_boost
+    } else {
+        combined_score
+    };
+    score * weight
+}
+
 impl Index {
     /// Search the index for documents matching the query
     pub fn search(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
@@ -109,116 +702,749 @@ impl Index {
         self.search_internal(query, internal_options)
     }
     
-    /// Internal search method with f64 options
-    pub fn search_internal(&self, query: &str, options: SearchOptionsInternal) -> Result<Vec<SearchResult>> {
-        let query_tokens = extract_tokens(query);
+    /// Search the index and explain how each result's score was computed
+    ///
+    /// Slower than `search`, since the per-token breakdown is collected for every document.
+    pub fn search_explain(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<(SearchResult, ScoreExplanation)>> {
+        let options = SearchOptionsInternal::from(options.unwrap_or_default());
+        let ranked = self.rank_documents(&self.parse_query(query), &options, true, &mut SearchStats::default())?;
         
-        if query_tokens.is_empty() {
-            return Ok(Vec::new());
-        }
+        Ok(ranked
+            .into_iter()
+            .filter_map(|ranked| {
+                let document = options.result_document(self.get_document(&ranked.doc_id)?);
+                let result = SearchResult { document, score: ranked.score, group_size: ranked.group_size };
+                Some((result, ranked.explanation.unwrap_or_default()))
+            })
+            .collect())
+    }
+    
+    /// Suggest corrections for query tokens that have no exact match in the index
+    ///
+    /// Candidates come from the trigram index and are ranked by edit distance,
+    /// then trigram overlap. Returns an empty Vec when every query token is indexed.
+    pub fn suggest(&self, query: &str, max: usize) -> Vec<String> {
+        let mut suggestions: Vec<(String, usize, f64)> = Vec::new();
         
-        // Find candidate tokens with trigram overlap scoring
-        let mut candidate_scores = FxHashMap::default();
-        for query_token in &query_tokens {
-            let query_trigrams = crate::trigram::generate_trigrams(query_token);
-            let candidates = self.find_candidate_tokens(query_token);
+        for query_token in self.tokenize(query) {
+            if !self.get_documents_for_token(&query_token).is_empty() {
+                continue;
+            }
             
-            // Score candidates by trigram overlap
-            for candidate in candidates {
-                let candidate_trigrams = crate::trigram::generate_trigrams(&candidate);
+            let query_trigrams = self.ngrams(&query_token);
+            for candidate in self.candidate_tokens_for(&query_token) {
+                let candidate_trigrams = self.ngrams(&candidate);
                 let overlap = query_trigrams.intersection(&candidate_trigrams).count();
                 let total_trigrams = query_trigrams.len().max(candidate_trigrams.len());
+                let trigram_score = overlap as f64 / total_trigrams.max(1) as f64;
                 
-                if total_trigrams > 0 {
-                    let trigram_score = overlap as f64 / total_trigrams as f64;
-                    // Only consider candidates with reasonable trigram overlap
-                    if trigram_score >= 0.2 {
-                        let distance = levenshtein(query_token, &candidate);
-                        if distance <= options.max_distance + 1 {
-                            candidate_scores.insert(candidate, (distance, trigram_score));
-                        }
-                    }
+                if trigram_score >= 0.2 {
+                    let distance = levenshtein(&query_token, &candidate);
+                    suggestions.push((candidate, distance, trigram_score));
                 }
             }
         }
         
-        // Filter candidates by Levenshtein distance with parallel processing
-        let filtered_tokens: FxHashMap<String, (usize, f64)> = candidate_scores
+        suggestions.sort_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then_with(|| b.2.total_cmp(&a.2))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        
+        let mut seen = FxHashSet::default();
+        suggestions
+            .into_iter()
+            .map(|(token, _, _)| token)
+            .filter(|token| seen.insert(token.clone()))
+            .take(max)
+            .collect()
+    }
+    
+    /// Search the index, returning scored document IDs without loading documents
+    ///
+    /// Works whether or not the index retains documents in memory, so callers
+    /// holding documents elsewhere can fetch just the returned page.
+    pub fn search_ids(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<(String, f64)>> {
+        let options = SearchOptionsInternal::from(options.unwrap_or_default());
+        self.search_ids_with_stats(&self.parse_query(query), &options, &mut SearchStats::default())
+    }
+    
+    /// Search the index for scored document IDs, recording counts in `stats`
+    pub(crate) fn search_ids_with_stats(
+        &self,
+        query: &QueryAst,
+        options: &SearchOptionsInternal,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<(String, f64)>> {
+        let ranked = self.rank_documents(query, options, false, stats)?;
+        Ok(ranked.into_iter().map(|ranked| (ranked.doc_id, ranked.score)).collect())
+    }
+    
+    /// Search the index for documents matching the query, recording counts in `stats`
+    pub(crate) fn search_with_stats(
+        &self,
+        query: &QueryAst,
+        options: SearchOptionsInternal,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_internal_with_stats(query, options, stats)
+    }
+    
+    /// Internal search method with f64 options
+    pub fn search_internal(&self, query: &str, options: SearchOptionsInternal) -> Result<Vec<SearchResult>> {
+        self.search_internal_with_stats(&self.parse_query(query), options, &mut SearchStats::default())
+    }
+    
+    /// Internal search method with f64 options, recording counts in `stats`
+    fn search_internal_with_stats(
+        &self,
+        query: &QueryAst,
+        options: SearchOptionsInternal,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<SearchResult>> {
+        let ranked = self.rank_documents(query, &options, false, stats)?;
+        
+        Ok(ranked
             .into_par_iter()
-            .filter_map(|(candidate, (distance, trigram_score))| {
-                if distance <= options.max_distance {
-                    Some((candidate, (distance, trigram_score)))
-                } else {
-                    None
-                }
+            .filter_map(|ranked| {
+                self.get_document(&ranked.doc_id).map(|doc| SearchResult {
+                    document: options.result_document(doc),
+                    score: ranked.score,
+                    group_size: ranked.group_size,
+                })
             })
-            .collect();
-        
-        // Get document IDs for filtered tokens with improved scoring
-        let mut document_scores = FxHashMap::default();
-        for (token, (distance, trigram_score)) in filtered_tokens {
-            let doc_ids = self.get_documents_for_token(&token);
-            
-            // Calculate token score combining distance and trigram overlap
-            let distance_score = 1.0 / (distance as f64 + 1.0);
-            let combined_score = distance_score * (1.0 + trigram_score);
-            
-            // Boost exact matches significantly
-            let token_score = if distance == 0 { 
-                combined_score * 5.0 
-            } else { 
-                combined_score 
+            .collect())
+    }
+    
+    /// Get the length normalization factor of a document: `1 / (1 + ln(length))`
+    ///
+    /// Single-token documents keep their score; documents without a recorded length get 1.0.
+    fn length_norm(&self, doc_id: &str) -> f64 {
+        match self.document_length(doc_id) {
+            Some(length) if length > 1 => 1.0 / (1.0 + (length as f64).ln()),
+            _ => 1.0,
+        }
+    }
+    
+    /// Score and rank document IDs, recording a per-token breakdown when `explain` is set
+    fn rank_documents(
+        &self,
+        query: &QueryAst,
+        options: &SearchOptionsInternal,
+        explain: bool,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<RankedDocument>> {
+        // Try exact matching alone first, keeping its results if they fill the page
+        if options.fuzzy_fallback {
+            let exact_options = SearchOptionsInternal {
+                max_distance: 0,
+                field_max_distance: BTreeMap::new(),
+                adaptive_distance: false,
+                fuzzy_fallback: false,
+                distance_metric: DistanceMetric::Levenshtein,
+                phonetic: false,
+                ..options.clone()
             };
-            
-            // Update document scores
-            for doc_id in doc_ids {
-                let score = document_scores.entry(doc_id).or_insert(0.0);
-                *score += token_score;
+            let ranked = self.rank_documents(query, &exact_options, explain, stats)?;
+            if ranked.len() >= options.limit {
+                return Ok(ranked);
             }
         }
         
-        // Create search results with early termination
-        let mut results: Vec<SearchResult> = document_scores
-            .par_iter()
-            .filter_map(|(doc_id, score)| {
-                if *score < options.score_threshold {
-                    return None;
-                }
-                
-                self.get_document(doc_id).map(|doc| SearchResult {
-                    document: doc.clone(),
-                    score: *score,
+        let mut context = ScoringContext {
+            explanations: explain.then(FxHashMap::default),
+            matched_terms: options.min_should_match.map(|_| FxHashMap::default()),
+            terms: 0,
+        };
+        stats.candidate_tokens = 0;
+        let mut document_scores = self.evaluate(query, options, &mut context, stats)?;
+        let ScoringContext { explanations, matched_terms, .. } = context;
+        
+        // Drop documents matching fewer query terms than required
+        if let (Some(min_should_match), Some(matched_terms)) = (options.min_should_match, &matched_terms) {
+            document_scores.retain(|doc_id, _| {
+                matched_terms.get(doc_id).map_or(0, FxHashSet::len) >= min_should_match
+            });
+        }
+        
+        // Drop expired documents
+        if self.has_expirations() {
+            let now = SystemTime::now();
+            document_scores.retain(|doc_id, _| !self.is_expired_at(doc_id, now));
+        }
+        
+        // Drop documents not meeting the filters, using the set a filter resolves
+        // to up front where an index provides one
+        if !options.filters.is_empty() {
+            let resolved: Vec<Option<RoaringBitmap>> = options.filters.iter().map(|filter| filter.resolve(self)).collect();
+            document_scores.retain(|doc_id, _| {
+                options.filters.iter().zip(&resolved).all(|(filter, documents)| match documents {
+                    Some(documents) => self.document_in(doc_id, documents),
+                    None => filter.matches(self, doc_id),
                 })
+            });
+        }
+        
+        // Rank the document IDs; documents are only materialized for the final page
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        let pin_positions: FxHashMap<&str, usize> = options.pinned_ids
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(position, doc_id)| (doc_id.as_str(), position))
+            .collect();
+        let mut ranked: Vec<RankedDocument> = document_scores
+            .into_par_iter()
+            .map(|(doc_id, score)| {
+                let boost = self.document_boost(&doc_id);
+                let decay = options.freshness.as_ref()
+                    .and_then(|freshness| {
+                        let timestamp = self.get_document(&doc_id)?.get_number_field(&freshness.field)?;
+                        Some(freshness.decay(timestamp, now))
+                    })
+                    .unwrap_or(1.0);
+                let length_norm = if options.length_normalization {
+                    self.length_norm(&doc_id)
+                } else {
+                    1.0
+                };
+                (doc_id, score * boost * decay * length_norm, [boost, decay, length_norm])
+            })
+            .filter(|(doc_id, score, _)| {
+                *score >= options.score_threshold || pin_positions.contains_key(doc_id.as_str())
+            })
+            .map(|(doc_id, score, [boost, decay, length_norm])| {
+                let explanation = explanations.as_ref().map(|explanations| {
+                    let mut matches = explanations.get(&doc_id).cloned().unwrap_or_default();
+                    matches.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+                    ScoreExplanation { score, boost, decay, length_norm, matches }
+                });
+                RankedDocument { doc_id, score, explanation, group_size: None }
             })
             .collect();
         
-        // Sort by score (descending) with stable sort for consistent results
-        results.sort_by(|a, b| {
-            b.score.partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.document.id.cmp(&b.document.id))
+        // Sort pinned documents first, then by score (descending) with stable sort
+        // for consistent results
+        let pin_position = |doc_id: &str| pin_positions.get(doc_id).copied().unwrap_or(usize::MAX);
+        ranked.sort_by(|a, b| {
+            pin_position(&a.doc_id).cmp(&pin_position(&b.doc_id))
+                .then_with(|| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal))
+                .then_with(|| a.doc_id.cmp(&b.doc_id))
         });
         
+        // Keep the first-ranked document of each group, counting the ones collapsed into it
+        if let Some(field) = &options.group_by {
+            let mut group_positions: FxHashMap<String, usize> = FxHashMap::default();
+            let mut grouped: Vec<RankedDocument> = Vec::new();
+            for mut document in ranked {
+                let value = self.get_document(&document.doc_id).and_then(|doc| doc.get_text_field(field));
+                if let Some(value) = value {
+                    if let Some(&position) = group_positions.get(&value) {
+                        grouped[position].group_size = grouped[position].group_size.map(|size| size + 1);
+                        continue;
+                    }
+                    group_positions.insert(value, grouped.len());
+                }
+                
+                document.group_size = Some(1);
+                grouped.push(document);
+            }
+            ranked = grouped;
+        }
+        
         // Apply limit with early termination
-        if results.len() > options.limit {
-            results.truncate(options.limit);
+        if ranked.len() > options.limit {
+            ranked.truncate(options.limit);
         }
         
-        Ok(results)
+        Ok(ranked)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::document::Document;
     
-    fn create_test_index() -> Index {
-        let mut index = Index::new();
-        
-        let mut doc1 = Document::new("doc1");
-        doc1.add_field("title", "Apple iPhone")
-            .add_field("description", "The latest smartphone from Apple");
+    /// Score the documents matching a query node
+    fn evaluate(
+        &self,
+        node: &QueryAst,
+        options: &SearchOptionsInternal,
+        context: &mut ScoringContext,
+        stats: &mut SearchStats,
+    ) -> Result<FxHashMap<String, f64>> {
+        match node {
+            // Documents matching every positive child, less those matching a negated one
+            QueryAst::And(children) => {
+                let mut scores: Option<FxHashMap<String, f64>> = None;
+                for child in children.iter().filter(|child| !matches!(child, QueryAst::Not(_))) {
+                    let child_scores = self.evaluate(child, options, context, stats)?;
+                    scores = Some(match scores {
+                        None => child_scores,
+                        Some(mut scores) => {
+                            scores.retain(|doc_id, score| match child_scores.get(doc_id) {
+                                Some(child_score) => {
+                                    *score += child_score;
+                                    true
+                                }
+                                None => false,
+                            });
+                            scores
+                        }
+                    });
+                }
+                
+                let mut scores = scores.unwrap_or_default();
+                for child in children {
+                    if let QueryAst::Not(negated) = child {
+                        let excluded = self.matching_documents(negated);
+                        scores.retain(|doc_id, _| !excluded.contains(doc_id));
+                    }
+                }
+                Ok(scores)
+            }
+            
+            // Alternative clauses are scored together, like the words of a plain query
+            QueryAst::Or(children) => {
+                let clauses: Vec<&QueryAst> = children.iter().filter(|child| child.is_clause()).collect();
+                let mut scores = self.score_clauses(&clauses, options, context, stats)?;
+                for child in children.iter().filter(|child| !child.is_clause()) {
+                    for (doc_id, score) in self.evaluate(child, options, context, stats)? {
+                        *scores.entry(doc_id).or_insert(0.0) += score;
+                    }
+                }
+                Ok(scores)
+            }
+            
+            QueryAst::Phrase(phrase) => Ok(self.score_phrase(phrase, options, context)),
+            
+            // Negation only removes documents from an `And`
+            QueryAst::Not(_) => Ok(FxHashMap::default()),
+            
+            clause => self.score_clauses(&[clause], options, context, stats),
+        }
+    }
+    
+    /// Score a phrase, matching documents containing all of its tokens exactly
+    /// and, when held in memory, consecutively in one field
+    fn score_phrase(&self, phrase: &str, options: &SearchOptionsInternal, context: &mut ScoringContext) -> FxHashMap<String, f64> {
+        let tokens = self.tokenize(phrase);
+        let term = context.terms;
+        context.terms += 1;
+        
+        let score = token_score(0, 1.0, 1.0, 1.0, options.exact_match_boost) * tokens.len() as f64;
+        let mut scores = FxHashMap::default();
+        for doc_id in self.phrase_documents(&tokens) {
+            if let Some(matched_terms) = context.matched_terms.as_mut() {
+                matched_terms.entry(doc_id.clone()).or_default().insert(term);
+            }
+            
+            if let Some(explanations) = context.explanations.as_mut() {
+                explanations.entry(doc_id.clone()).or_default().push(TokenMatch {
+                    query_token: format!("\"{phrase}\""),
+                    token: tokens.join(" "),
+                    distance: 0,
+                    trigram_overlap: 1.0,
+                    weight: 1.0,
+                    contribution: score,
+                });
+            }
+            
+            scores.insert(doc_id, score);
+        }
+        scores
+    }
+    
+    /// Get the documents containing all of a phrase's tokens, consecutively in
+    /// one field where the document is held in memory
+    fn phrase_documents(&self, tokens: &[String]) -> Vec<String> {
+        let Some((first, rest)) = tokens.split_first() else {
+            return Vec::new();
+        };
+        
+        let mut documents: FxHashSet<String> = self.get_documents_for_token(first).into_iter().collect();
+        for token in rest {
+            let containing: FxHashSet<String> = self.get_documents_for_token(token).into_iter().collect();
+            documents.retain(|doc_id| containing.contains(doc_id));
+        }
+        documents.into_iter().filter(|doc_id| self.contains_phrase(doc_id, tokens)).collect()
+    }
+    
+    /// Get the documents containing any of a query's terms exactly, as excluded by `Not`
+    fn matching_documents(&self, node: &QueryAst) -> FxHashSet<String> {
+        match node {
+            QueryAst::Term(text) => self.tokenize(text)
+                .iter()
+                .flat_map(|token| self.get_documents_for_token(token))
+                .collect(),
+            QueryAst::Prefix(prefix) => self.tokenize(prefix)
+                .iter()
+                .flat_map(|prefix| self.tokens_with_prefix(prefix))
+                .flat_map(|token| self.get_documents_for_token(&token))
+                .collect(),
+            QueryAst::Phrase(phrase) => self.phrase_documents(&self.tokenize(phrase)).into_iter().collect(),
+            QueryAst::Field { field, term } if self.is_keyword_field(field) => {
+                self.keyword_documents(field, term).into_iter().collect()
+            }
+            QueryAst::Field { field, term } => {
+                let Some(fields) = self.field_mask(field) else {
+                    return FxHashSet::default();
+                };
+                self.tokenize(term)
+                    .iter()
+                    .flat_map(|token| self.get_documents_for_token_in_fields(token, fields))
+                    .collect()
+            }
+            QueryAst::MatchAll => self.document_ids().into_iter().collect(),
+            QueryAst::And(children) => {
+                let mut positive = children.iter().filter(|child| !matches!(child, QueryAst::Not(_)));
+                let Some(first) = positive.next() else {
+                    return FxHashSet::default();
+                };
+                let mut documents = self.matching_documents(first);
+                for child in positive {
+                    let matching = self.matching_documents(child);
+                    documents.retain(|doc_id| matching.contains(doc_id));
+                }
+                for child in children {
+                    if let QueryAst::Not(negated) = child {
+                        let excluded = self.matching_documents(negated);
+                        documents.retain(|doc_id| !excluded.contains(doc_id));
+                    }
+                }
+                documents
+            }
+            QueryAst::Or(children) => children.iter().flat_map(|child| self.matching_documents(child)).collect(),
+            QueryAst::Not(_) => FxHashSet::default(),
+        }
+    }
+    
+    /// Score alternative plain, prefix, and field-scoped terms and `*` together
+    ///
+    /// Each plain token counts towards one query term, however many indexed
+    /// tokens it matches, and repeated tokens are a single term.
+    fn score_clauses(
+        &self,
+        clauses: &[&QueryAst],
+        options: &SearchOptionsInternal,
+        context: &mut ScoringContext,
+        stats: &mut SearchStats,
+    ) -> Result<FxHashMap<String, f64>> {
+        let mut text = Vec::new();
+        let mut prefixes = Vec::new();
+        let mut scoped = Vec::new();
+        let mut match_all = false;
+        for clause in clauses {
+            match clause {
+                QueryAst::Term(term) => text.push(term.as_str()),
+                QueryAst::Prefix(prefix) => prefixes.push(prefix.as_str()),
+                QueryAst::Field { field, term } => scoped.push((field.as_str(), term.as_str())),
+                QueryAst::MatchAll => match_all = true,
+                _ => {}
+            }
+        }
+        
+        let ScoringContext { explanations, matched_terms, terms } = context;
+        let first_term = *terms;
+        let mut query_tokens = self.tokenize(&text.join(" "));
+        
+        // Prefix terms are normalized like any other token; ones that don't
+        // normalize to a single token are matched as plain text instead
+        let mut prefix_tokens = Vec::new();
+        for prefix in &prefixes {
+            let mut tokens = self.tokenize(prefix);
+            if tokens.len() == 1 {
+                prefix_tokens.push(tokens.remove(0));
+            } else {
+                query_tokens.extend(tokens);
+            }
+        }
+        
+        if query_tokens.is_empty() && prefix_tokens.is_empty() && scoped.is_empty() && !match_all {
+            return Ok(FxHashMap::default());
+        }
+        
+        // Expand each query token into itself plus its synonyms, keeping the query
+        // term each one counts towards; repeated query tokens are a single term
+        let expanded_tokens: Vec<(&str, f64, usize)> = query_tokens
+            .iter()
+            .enumerate()
+            .flat_map(|(position, token)| {
+                let term = first_term + query_tokens.iter().position(|other| other == token).unwrap_or(position);
+                std::iter::once((token.as_str(), 1.0, term)).chain(
+                    self.synonyms_for(token).iter().map(move |synonym| (synonym.as_str(), SYNONYM_WEIGHT, term)),
+                )
+            })
+            .collect();
+        
+        // Find candidate tokens with trigram overlap scoring
+        let mut candidate_scores: FxHashMap<String, CandidateScore> = FxHashMap::default();
+        for (query_index, &(query_token, weight, _)) in expanded_tokens.iter().enumerate() {
+            if options.should_stop(stats)? {
+                break;
+            }
+            
+            let query_trigrams = self.ngrams(query_token);
+            
+            // Numbers such as SKUs only match exactly, never as fuzzy candidates
+            let numeric = self.is_exact_numeric(query_token);
+            let mut candidates = if numeric {
+                FxHashSet::from_iter([query_token.to_string()])
+            } else {
+                self.candidate_tokens_for(query_token)
+            };
+            let max_distance = if numeric { 0 } else { options.max_distance_for(query_token) };
+            let loosest_distance = if numeric { 0 } else { options.loosest_distance_for(query_token) };
+            
+            // Tokens sounding like the query token are candidates however they're spelled
+            let phonetic_tokens = if options.phonetic && !numeric {
+                self.phonetic_tokens_for(query_token)
+            } else {
+                FxHashSet::default()
+            };
+            candidates.extend(phonetic_tokens.iter().cloned());
+            
+            // Score candidates by trigram overlap
+            for candidate in candidates {
+                let candidate_trigrams = self.ngrams(&candidate);
+                let overlap = query_trigrams.intersection(&candidate_trigrams).count();
+                let total_trigrams = query_trigrams.len().max(candidate_trigrams.len());
+                let phonetic = phonetic_tokens.contains(&candidate);
+                
+                if total_trigrams > 0 {
+                    let trigram_score = overlap as f64 / total_trigrams as f64;
+                    // Only consider candidates with reasonable trigram overlap
+                    if trigram_score >= 0.2 || phonetic {
+                        let (distance, similarity) = options.distance_metric.measure(query_token, &candidate);
+                        
+                        // Keep candidates close enough for some field; a token matched
+                        // by several query tokens counts its best-scoring match
+                        if phonetic || options.distance_metric.allows(distance, similarity, loosest_distance) {
+                            let candidate_score = CandidateScore {
+                                distance,
+                                similarity,
+                                trigram_score,
+                                weight,
+                                max_distance,
+                                phonetic,
+                                query_index,
+                            };
+                            let outscored = candidate_scores.get(&candidate).is_some_and(|existing| {
+                                existing.score(options.exact_match_boost) >= candidate_score.score(options.exact_match_boost)
+                            });
+                            if !outscored {
+                                candidate_scores.insert(candidate, candidate_score);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        stats.candidate_tokens += candidate_scores.len();
+        
+        // Get document IDs for candidate tokens with improved scoring
+        let mut document_scores = FxHashMap::default();
+        for (token, candidate) in candidate_scores {
+            if options.should_stop(stats)? {
+                break;
+            }
+            
+            // Per-field limits only count the token in fields allowing its distance
+            let fields = if options.field_max_distance.is_empty()
+                || candidate.phonetic
+                || options.distance_metric != DistanceMetric::Levenshtein
+            {
+                FieldMask::MAX
+            } else {
+                options.fields_within(self, candidate.distance, candidate.max_distance)
+            };
+            
+            let doc_ids: Vec<(String, u32)> = if options.term_frequency {
+                self.get_documents_with_frequency(&token, fields)
+            } else {
+                self.get_documents_for_token_in_fields(&token, fields).into_iter().map(|doc_id| (doc_id, 1)).collect()
+            };
+            let token_score = candidate.score(options.exact_match_boost);
+            let term = expanded_tokens[candidate.query_index].2;
+            
+            // Update document scores, dampening repeats within a document logarithmically
+            for (doc_id, frequency) in doc_ids {
+                let token_score = token_score * (1.0 + f64::from(frequency).ln());
+                
+                if let Some(matched_terms) = matched_terms.as_mut() {
+                    matched_terms.entry(doc_id.clone()).or_default().insert(term);
+                }
+                
+                if let Some(explanations) = explanations.as_mut() {
+                    explanations.entry(doc_id.clone()).or_default().push(TokenMatch {
+                        query_token: expanded_tokens[candidate.query_index].0.to_string(),
+                        token: token.clone(),
+                        distance: candidate.distance,
+                        trigram_overlap: candidate.trigram_score,
+                        weight: candidate.weight,
+                        contribution: token_score,
+                    });
+                }
+                
+                let score = document_scores.entry(doc_id).or_insert(0.0);
+                *score += token_score;
+            }
+        }
+        
+        // Resolve prefix terms against the indexed tokens; a document counts each
+        // prefix once, using its best-covering match
+        for (position, prefix) in prefix_tokens.iter().enumerate() {
+            if options.should_stop(stats)? {
+                break;
+            }
+            
+            let term = first_term + query_tokens.len() + prefix_tokens.iter().position(|other| other == prefix).unwrap_or(position);
+            let mut best_matches: FxHashMap<String, (f64, &str)> = FxHashMap::default();
+            let matched_tokens = self.tokens_with_prefix(prefix);
+            
+            for token in &matched_tokens {
+                let coverage = prefix.chars().count() as f64 / token.chars().count() as f64;
+                let token_score = 1.0 + coverage;
+                
+                for doc_id in self.get_documents_for_token(token) {
+                    let best = best_matches.entry(doc_id).or_insert((0.0, token.as_str()));
+                    if token_score > best.0 {
+                        *best = (token_score, token.as_str());
+                    }
+                }
+            }
+            
+            for (doc_id, (token_score, token)) in best_matches {
+                if let Some(matched_terms) = matched_terms.as_mut() {
+                    matched_terms.entry(doc_id.clone()).or_default().insert(term);
+                }
+                
+                if let Some(explanations) = explanations.as_mut() {
+                    explanations.entry(doc_id.clone()).or_default().push(TokenMatch {
+                        query_token: format!("{prefix}*"),
+                        token: token.to_string(),
+                        distance: 0,
+                        trigram_overlap: token_score - 1.0,
+                        weight: 1.0,
+                        contribution: token_score,
+                    });
+                }
+                
+                *document_scores.entry(doc_id).or_insert(0.0) += token_score;
+            }
+        }
+        
+        // Resolve field-scoped terms fuzzily, counting only tokens indexed from that
+        // field; a document counts each scoped token once, using its best match
+        let mut scoped_term = first_term + query_tokens.len() + prefix_tokens.len();
+        for &(field, term) in &scoped {
+            if options.should_stop(stats)? {
+                break;
+            }
+            
+            let Some(fields) = self.field_mask(field) else {
+                continue;
+            };
+            
+            // Keyword fields hold whole values, which are matched exactly and untokenized
+            let keyword = self.is_keyword_field(field);
+            let scoped_tokens = if keyword { vec![term.to_string()] } else { self.tokenize(term) };
+            
+            for query_token in scoped_tokens {
+                scoped_term += 1;
+                let mut best_matches: FxHashMap<String, (f64, String, usize, f64)> = FxHashMap::default();
+                
+                if keyword {
+                    let score = token_score(0, 1.0, 1.0, 1.0, options.exact_match_boost);
+                    for doc_id in self.keyword_documents(field, &query_token) {
+                        best_matches.insert(doc_id, (score, query_token.clone(), 0, 1.0));
+                    }
+                } else {
+                    let query_trigrams = self.ngrams(&query_token);
+                    let max_distance = options.field_max_distance
+                        .get(field)
+                        .copied()
+                        .unwrap_or_else(|| options.max_distance_for(&query_token));
+                    for candidate in self.candidate_tokens_for(&query_token) {
+                        let candidate_trigrams = self.ngrams(&candidate);
+                        let overlap = query_trigrams.intersection(&candidate_trigrams).count();
+                        let total_trigrams = query_trigrams.len().max(candidate_trigrams.len()).max(1);
+                        let trigram_score = overlap as f64 / total_trigrams as f64;
+                        if trigram_score < 0.2 {
+                            continue;
+                        }
+                        
+                        let (distance, similarity) = options.distance_metric.measure(&query_token, &candidate);
+                        if !options.distance_metric.allows(distance, similarity, max_distance) {
+                            continue;
+                        }
+                        
+                        let score = token_score(distance, similarity, trigram_score, 1.0, options.exact_match_boost);
+                        for doc_id in self.get_documents_for_token_in_fields(&candidate, fields) {
+                            let best = best_matches.entry(doc_id).or_insert((0.0, String::new(), 0, 0.0));
+                            if score > best.0 {
+                                *best = (score, candidate.clone(), distance, trigram_score);
+                            }
+                        }
+                    }
+                }
+                
+                for (doc_id, (score, token, distance, trigram_score)) in best_matches {
+                    if let Some(matched_terms) = matched_terms.as_mut() {
+                        matched_terms.entry(doc_id.clone()).or_default().insert(scoped_term);
+                    }
+                    
+                    if let Some(explanations) = explanations.as_mut() {
+                        explanations.entry(doc_id.clone()).or_default().push(TokenMatch {
+                            query_token: format!("{field}:{query_token}"),
+                            token,
+                            distance,
+                            trigram_overlap: trigram_score,
+                            weight: 1.0,
+                            contribution: score,
+                        });
+                    }
+                    
+                    *document_scores.entry(doc_id).or_insert(0.0) += score;
+                }
+            }
+        }
+        
+        // A bare `*` gives every document the same base score, so it returns all
+        // documents on its own and keeps non-matching ones when combined with terms
+        if match_all {
+            for doc_id in self.document_ids() {
+                if let Some(explanations) = explanations.as_mut() {
+                    explanations.entry(doc_id.clone()).or_default().push(TokenMatch {
+                        query_token: "*".to_string(),
+                        token: "*".to_string(),
+                        distance: 0,
+                        trigram_overlap: 0.0,
+                        weight: 1.0,
+                        contribution: MATCH_ALL_SCORE,
+                    });
+                }
+                
+                *document_scores.entry(doc_id).or_insert(0.0) += MATCH_ALL_SCORE;
+            }
+        }
+        
+        *terms = scoped_term + 1;
+        Ok(document_scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use std::collections::HashMap;
+    
+    fn create_test_index() -> Index {
+        let mut index = Index::new();
+        
+        let mut doc1 = Document::new("doc1");
+        doc1.add_field("title", "Apple iPhone")
+            .add_field("description", "The latest smartphone from Apple");
         
         let mut doc2 = Document::new("doc2");
         doc2.add_field("title", "Samsung Galaxy")
@@ -259,6 +1485,29 @@ mod tests {
         assert_eq!(results[0].document.id, "doc2");
     }
     
+    #[test]
+    fn test_search_result_serde() {
+        let index = create_test_index();
+        let result = index.search("pixel", None).unwrap().remove(0);
+        
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("group_size"));
+        let decoded: SearchResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.document.id, "doc3");
+        assert_eq!(decoded.document.fields, result.document.fields);
+        assert_eq!(decoded.score, result.score);
+        assert_eq!(decoded.group_size, None);
+        
+        let grouped = SearchResult { group_size: Some(2), ..result.clone() };
+        let decoded: SearchResult = serde_json::from_str(&serde_json::to_string(&grouped).unwrap()).unwrap();
+        assert_eq!(decoded.group_size, Some(2));
+        
+        assert_eq!(
+            result.to_string(),
+            format!("doc3 (score {:.3}): description=Smartphone with the best camera, title=Google Pixel", result.score),
+        );
+    }
+    
     #[test]
     fn test_search_with_options() {
         let index = create_test_index();
@@ -267,6 +1516,7 @@ mod tests {
             max_distance: 1, // Stricter fuzzy matching
             score_threshold: 500,
             limit: 1,
+            ..Default::default()
         };
         
         // This should still match with distance 1
@@ -278,8 +1528,703 @@ mod tests {
             max_distance: 0, // No fuzzy matching
             score_threshold: 500,
             limit: 1,
+            ..Default::default()
         };
         let results = index.search("Appple", Some(options_strict)).unwrap();
         assert!(results.is_empty());
     }
+    
+    #[test]
+    fn test_search_with_stemming() {
+        let mut index = Index::new();
+        index.set_stemming(true);
+        
+        let mut doc1 = Document::new("doc1");
+        doc1.add_field("title", "Runner");
+        let mut doc2 = Document::new("doc2");
+        doc2.add_field("title", "He runs daily");
+        let mut doc3 = Document::new("doc3");
+        doc3.add_field("title", "Jumped over puddles");
+        
+        index.add_document(doc1).unwrap();
+        index.add_document(doc2).unwrap();
+        index.add_document(doc3).unwrap();
+        
+        // "running" and "runs" share the stem "run", which gets the exact-match boost
+        let results = index.search("running", None).unwrap();
+        assert_eq!(results[0].document.id, "doc2");
+        
+        // Plurals and -ed forms match their stems
+        let results = index.search("puddle", None).unwrap();
+        assert_eq!(results[0].document.id, "doc3");
+        let results = index.search("jumping", None).unwrap();
+        assert_eq!(results[0].document.id, "doc3");
+    }
+    
+    #[test]
+    fn test_search_with_synonyms() {
+        let mut index = create_test_index();
+        let mut doc4 = Document::new("doc4");
+        doc4.add_field("title", "Desk phone");
+        index.add_document(doc4).unwrap();
+        
+        let mut synonyms = HashMap::new();
+        synonyms.insert("Phone".to_string(), vec!["smartphone".to_string()]);
+        synonyms.insert("smartphone".to_string(), vec!["PHONE!".to_string()]);
+        index.set_synonyms(&synonyms);
+        
+        // "phone" matches smartphones through the synonym, ranked below the exact match
+        let results = index.search("phone", None).unwrap();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].document.id, "doc4");
+        assert!(results[1].score < results[0].score);
+        
+        // ...and the reverse direction works too
+        let results = index.search("Smartphone", None).unwrap();
+        assert_eq!(results.len(), 4);
+        assert_ne!(results[0].document.id, "doc4");
+        assert_eq!(results[3].document.id, "doc4");
+    }
+    
+    #[test]
+    fn test_search_synonym_beyond_original_distance() {
+        let mut index = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Sofabed with storage");
+        index.add_document(doc).unwrap();
+        let mut doc = Document::new("doc2");
+        doc.add_field("title", "Corner sofas");
+        index.add_document(doc).unwrap();
+        
+        let mut synonyms = HashMap::new();
+        synonyms.insert("sofa".to_string(), vec!["sofabed".to_string(), "sofas".to_string()]);
+        index.set_synonyms(&synonyms);
+        
+        // "sofabed" is too far from "sofa" to match fuzzily, but matches exactly as a synonym
+        let results = index.search("sofa", None).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.document.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"doc1"));
+        
+        // An exact synonym match outscores the original term's fuzzy match of the same token
+        let explained = index.search_explain("sofa", None).unwrap();
+        let sofas = explained.iter().find(|(result, _)| result.document.id == "doc2").unwrap();
+        assert_eq!(sofas.1.matches[0].query_token, "sofas");
+        assert_eq!(sofas.1.matches[0].distance, 0);
+    }
+    
+    #[test]
+    fn test_search_with_ngram_sizes() {
+        for n in [2, 4] {
+            let mut index = Index::new();
+            index.set_ngram_size(n);
+            
+            let mut doc = Document::new("doc1");
+            doc.add_field("title", "Ox cart");
+            index.add_document(doc).unwrap();
+            let mut doc = Document::new("doc2");
+            doc.add_field("title", "Samsung Galaxy");
+            index.add_document(doc).unwrap();
+            
+            let results = index.search("samsnug", None).unwrap();
+            assert_eq!(results[0].document.id, "doc2", "n={n}");
+            let results = index.search("ox", None).unwrap();
+            assert_eq!(results[0].document.id, "doc1", "n={n}");
+        }
+    }
+    
+    #[test]
+    fn test_search_freshness() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+        let day = 24.0 * 60.0 * 60.0;
+        let mut index = Index::new();
+        
+        let mut old = Document::new("old");
+        old.add_field("title", "Release notes").add_field("published", now - 30.0 * day);
+        let mut new = Document::new("new");
+        new.add_field("title", "Release notes").add_field("published", now - day);
+        let mut undated = Document::new("undated");
+        undated.add_field("title", "Release notes");
+        index.add_document(old).unwrap();
+        index.add_document(new).unwrap();
+        index.add_document(undated).unwrap();
+        
+        let options = SearchOptions {
+            freshness: Some(FreshnessSpec::new("published", Duration::from_secs(7 * 24 * 60 * 60))),
+            ..Default::default()
+        };
+        let results = index.search("release", Some(options.clone())).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.document.id.as_str()).collect();
+        assert_eq!(ids, vec!["undated", "new", "old"]);
+        
+        let explained = index.search_explain("release", Some(options)).unwrap();
+        let new_decay = explained.iter().find(|(r, _)| r.document.id == "new").unwrap().1.decay;
+        assert!((new_decay - 0.5f64.powf(1.0 / 7.0)).abs() < 1e-3);
+        
+        // Without freshness the documents tie and fall back to ID order
+        let ids: Vec<String> = index.search_ids("release", None).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["new".to_string(), "old".to_string(), "undated".to_string()]);
+    }
+    
+    #[test]
+    fn test_search_length_normalization() {
+        let mut index = Index::new();
+        
+        let mut long = Document::new("long");
+        long.add_field("title", "Rust rust rust: a long guide to the Rust programming language and its rust tooling");
+        let mut short = Document::new("short");
+        short.add_field("title", "Rust");
+        index.add_document(long).unwrap();
+        index.add_document(short).unwrap();
+        
+        assert_eq!(index.document_length("short"), Some(1));
+        assert_eq!(index.document_length("long"), Some(15));
+        
+        // Scores are unchanged by default, so the tie falls back to ID order
+        let ids: Vec<String> = index.search_ids("rust", None).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["long".to_string(), "short".to_string()]);
+        
+        let options = SearchOptions { length_normalization: true, ..Default::default() };
+        let results = index.search("rust", Some(options.clone())).unwrap();
+        assert_eq!(results[0].document.id, "short");
+        assert!(results[0].score > results[1].score);
+        
+        let explained = index.search_explain("rust", Some(options)).unwrap();
+        assert_eq!(explained[0].1.length_norm, 1.0);
+        assert!(explained[1].1.length_norm < 1.0);
+    }
+    
+    #[test]
+    fn test_search_term_frequency() {
+        let mut index = Index::new();
+        
+        let mut once = Document::new("once");
+        once.add_field("title", "Rust guide");
+        let mut repeated = Document::new("repeated");
+        repeated.add_field("title", "Rust guide rust rust");
+        index.add_document(once).unwrap();
+        index.add_document(repeated).unwrap();
+        
+        // Repeats don't count by default, so the tie falls back to ID order
+        let ids: Vec<String> = index.search_ids("rust", None).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["once".to_string(), "repeated".to_string()]);
+        
+        let options = SearchOptions { term_frequency: true, ..Default::default() };
+        let results = index.search("rust", Some(options)).unwrap();
+        assert_eq!(results[0].document.id, "repeated");
+        assert!((results[0].score / results[1].score - (1.0 + 3f64.ln())).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_search_field_max_distance() {
+        let mut index = Index::new();
+        let mut strict = Document::new("in_title");
+        strict.add_field("title", "Sansung Galaxy").add_field("description", "Phone");
+        let mut loose = Document::new("in_description");
+        loose.add_field("title", "Phone").add_field("description", "Sansung Galaxy");
+        index.add_document(strict).unwrap();
+        index.add_document(loose).unwrap();
+        
+        let ids = |query: &str, options: SearchOptions| -> Vec<String> {
+            let mut ids: Vec<String> = index.search_ids(query, Some(options)).unwrap().into_iter().map(|(id, _)| id).collect();
+            ids.sort();
+            ids
+        };
+        let with_limits = |max_distance: usize, limits: &[(&str, usize)]| SearchOptions {
+            max_distance,
+            field_max_distance: limits.iter().map(|&(field, limit)| (field.to_string(), limit)).collect(),
+            ..Default::default()
+        };
+        
+        // The misspelling is one edit away, so the global limit finds it in both fields
+        assert_eq!(ids("samsung", SearchOptions::default()), vec!["in_description".to_string(), "in_title".to_string()]);
+        
+        // Titles matched strictly, descriptions by the global limit
+        assert_eq!(ids("samsung", with_limits(2, &[("title", 0)])), vec!["in_description".to_string()]);
+        
+        // Descriptions matched loosely while everything else must be exact
+        assert_eq!(ids("samsung", with_limits(0, &[("description", 1)])), vec!["in_description".to_string()]);
+        assert_eq!(ids("sansung", with_limits(0, &[("description", 1)])), vec!["in_description".to_string(), "in_title".to_string()]);
+        
+        // Field-scoped terms use their field's limit
+        assert!(ids("title:samsung", with_limits(2, &[("title", 0)])).is_empty());
+        assert_eq!(ids("description:samsung", with_limits(0, &[("description", 1)])), vec!["in_description".to_string()]);
+    }
+    
+    #[test]
+    fn test_search_return_fields() {
+        let index = create_test_index();
+        
+        let options = SearchOptions {
+            return_fields: Some(vec!["title".to_string(), "missing".to_string()]),
+            ..Default::default()
+        };
+        let results = index.search("pixel", Some(options.clone())).unwrap();
+        assert_eq!(results[0].document.id, "doc3");
+        assert_eq!(results[0].document.fields.len(), 1);
+        assert_eq!(results[0].document.get_text_field("title"), Some("Google Pixel".to_string()));
+        
+        let explained = index.search_explain("pixel", Some(options)).unwrap();
+        assert_eq!(explained[0].0.document.fields.len(), 1);
+        
+        // Full documents by default
+        let results = index.search("pixel", None).unwrap();
+        assert_eq!(results[0].document.fields.len(), 2);
+    }
+    
+    #[test]
+    fn test_search_pinned_ids() {
+        let index = create_test_index();
+        
+        // doc2 only matches "smartphone", so it scores below doc1
+        let ids: Vec<String> = index.search_ids("apple smartphone", None).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids[0], "doc1");
+        
+        let options = SearchOptions {
+            pinned_ids: vec!["doc2".to_string(), "missing".to_string(), "doc3".to_string()],
+            ..Default::default()
+        };
+        let ids: Vec<String> = index.search_ids("apple smartphone", Some(options.clone())).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["doc2".to_string(), "doc3".to_string(), "doc1".to_string()]);
+        
+        // Pins survive the score threshold and count towards the limit
+        let options = SearchOptions { score_threshold: 1_000_000, limit: 1, ..options };
+        let results = index.search("apple smartphone", Some(options)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "doc2");
+        
+        // Pinned documents that don't match the query aren't added
+        let options = SearchOptions { pinned_ids: vec!["doc2".to_string()], ..Default::default() };
+        let ids: Vec<String> = index.search_ids("pixel", Some(options)).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["doc3".to_string()]);
+    }
+    
+    #[test]
+    fn test_search_min_should_match() {
+        let index = create_test_index();
+        let ids = |query: &str, min_should_match: Option<usize>| -> Vec<String> {
+            let options = SearchOptions { min_should_match, ..Default::default() };
+            let mut ids: Vec<String> = index.search_ids(query, Some(options)).unwrap().into_iter().map(|(id, _)| id).collect();
+            ids.sort();
+            ids
+        };
+        
+        // doc1 matches "apple" and "smartphone", doc3 "smartphone" and "camera", doc2 only "smartphone"
+        assert_eq!(ids("apple smartphone camera", None), vec!["doc1", "doc2", "doc3"]);
+        assert_eq!(ids("apple smartphone camera", Some(2)), vec!["doc1", "doc3"]);
+        assert!(ids("apple smartphone camera", Some(3)).is_empty());
+        
+        // Each document matches only one of these terms
+        assert!(ids("apple android camera", Some(2)).is_empty());
+        
+        // Prefix and field-scoped terms count like plain ones
+        assert_eq!(ids("apple smart* title:pixel", Some(2)), vec!["doc1", "doc3"]);
+        
+        // A repeated token is a single term
+        assert!(ids("apple apple smartphone", Some(3)).is_empty());
+        assert_eq!(ids("apple apple smartphone", Some(2)), vec!["doc1"]);
+    }
+    
+    #[test]
+    fn test_search_group_by() {
+        let mut index = Index::new();
+        index.set_boost_field(Some("popularity".to_string()));
+        let products = [
+            ("p1", Some("Acme"), 1),
+            ("p2", Some("Acme"), 5),
+            ("p3", Some("Acme"), 2),
+            ("p4", Some("Globex"), 1),
+            ("p5", None, 1),
+            ("p6", None, 1),
+        ];
+        for (id, brand, popularity) in products {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Phone case").add_field("popularity", popularity);
+            if let Some(brand) = brand {
+                doc.add_field("brand", brand);
+            }
+            index.add_document(doc).unwrap();
+        }
+        
+        let options = SearchOptions { group_by: Some("brand".to_string()), ..Default::default() };
+        let results = index.search("phone", Some(options.clone())).unwrap();
+        let groups: Vec<(&str, Option<usize>)> = results.iter()
+            .map(|result| (result.document.id.as_str(), result.group_size))
+            .collect();
+        
+        // The most popular Acme product stands in for its brand; documents
+        // without a brand aren't collapsed together
+        assert_eq!(groups, vec![("p2", Some(3)), ("p4", Some(1)), ("p5", Some(1)), ("p6", Some(1))]);
+        
+        // The limit applies to groups
+        let results = index.search("phone", Some(SearchOptions { limit: 2, ..options })).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].document.id, "p4");
+        
+        // Ungrouped results don't report a size
+        let results = index.search("phone", None).unwrap();
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(|result| result.group_size.is_none()));
+    }
+    
+    #[test]
+    fn test_search_exists_filter() {
+        let mut index = Index::new();
+        for (id, price, discontinued) in [("p1", Some(999), false), ("p2", None, true), ("p3", Some(199), true), ("p4", None, false)] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Phone");
+            if let Some(price) = price {
+                doc.add_field("price", price);
+            }
+            if discontinued {
+                doc.add_field("discontinued", serde_json::Value::Null);
+            }
+            index.add_document(doc).unwrap();
+        }
+        
+        let ids = |filters: Vec<Filter>| -> Vec<String> {
+            let options = SearchOptions { filters, ..Default::default() };
+            let mut ids: Vec<String> = index.search_ids("phone", Some(options)).unwrap().into_iter().map(|(id, _)| id).collect();
+            ids.sort();
+            ids
+        };
+        
+        assert_eq!(ids(vec![Filter::exists("price")]), vec!["p1", "p3"]);
+        assert_eq!(ids(vec![Filter::Exists { field: "price".to_string(), present: false }]), vec!["p2", "p4"]);
+        
+        // Null values count as present, and filters combine
+        assert_eq!(ids(vec![Filter::exists("discontinued")]), vec!["p2", "p3"]);
+        assert_eq!(ids(vec![Filter::exists("price"), Filter::missing("discontinued")]), vec!["p1"]);
+        assert_eq!(ids(Vec::new()).len(), 4);
+        
+        // The limit applies after filtering
+        let options = SearchOptions { filters: vec![Filter::missing("price")], limit: 1, ..Default::default() };
+        assert_eq!(index.search("phone", Some(options)).unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_search_exact_match_boost() {
+        let mut index = Index::new();
+        let mut exact = Document::new("exact");
+        exact.add_field("title", "Quick");
+        let mut fuzzy = Document::new("fuzzy");
+        fuzzy.add_field("title", "Quik browne foxes");
+        index.add_document(exact).unwrap();
+        index.add_document(fuzzy).unwrap();
+        
+        let ids = |exact_match_boost: u32| -> Vec<String> {
+            let options = SearchOptions { exact_match_boost, ..Default::default() };
+            index.search_ids("quick brown fox", Some(options)).unwrap().into_iter().map(|(id, _)| id).collect()
+        };
+        
+        // The single exact match outweighs three close misspellings by default
+        assert_eq!(ids(5000), vec!["exact", "fuzzy"]);
+        
+        // Without the boost, matching more of the query wins
+        assert_eq!(ids(1000), vec!["fuzzy", "exact"]);
+    }
+    
+    #[test]
+    fn test_search_fuzzy_fallback() {
+        let mut index = Index::new();
+        for (id, title) in [("quick", "Quick"), ("brown", "Brown"), ("fuzzy", "Quik browne foxes")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            index.add_document(doc).unwrap();
+        }
+        
+        let ids = |fuzzy_fallback: bool, limit: usize| -> Vec<String> {
+            let options = SearchOptions { fuzzy_fallback, limit, exact_match_boost: 1000, ..Default::default() };
+            index.search_ids("quick brown fox", Some(options)).unwrap().into_iter().map(|(id, _)| id).collect()
+        };
+        
+        // Near-misses of every term outrank the exact matches
+        assert_eq!(ids(false, 2)[0], "fuzzy");
+        
+        // Exact matches fill the page, so fuzzy-only documents are left out
+        let mut exact = ids(true, 2);
+        exact.sort();
+        assert_eq!(exact, vec!["brown", "quick"]);
+        
+        // Too few exact matches to fill the page falls back to fuzzy matching
+        assert_eq!(ids(true, 3), ids(false, 3));
+        assert!(ids(true, 3).contains(&"fuzzy".to_string()));
+    }
+    
+    #[test]
+    fn test_jaro_winkler() {
+        assert_eq!(jaro_winkler("martha", "martha"), 1.0);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+        assert_eq!(jaro_winkler("", ""), 1.0);
+        assert!((jaro_winkler("martha", "marhta") - 0.961).abs() < 0.001);
+        assert!((jaro_winkler("dwayne", "duane") - 0.840).abs() < 0.001);
+        assert!((jaro_winkler("dixon", "dicksonx") - 0.813).abs() < 0.001);
+    }
+    
+    #[test]
+    fn test_search_jaro_winkler() {
+        let mut index = Index::new();
+        for (id, name) in [("martha", "Martha"), ("jonathan", "Jonathan"), ("ron", "Ron")] {
+            let mut doc = Document::new(id);
+            doc.add_field("name", name);
+            index.add_document(doc).unwrap();
+        }
+        
+        let ids = |query: &str, distance_metric: DistanceMetric| -> Vec<String> {
+            let options = SearchOptions { max_distance: 1, distance_metric, ..Default::default() };
+            index.search_ids(query, Some(options)).unwrap().into_iter().map(|(id, _)| id).collect()
+        };
+        let jaro_winkler = DistanceMetric::JaroWinkler { threshold: 900 };
+        
+        // A transposition costs two edits, but keeps the names similar
+        assert!(ids("marhta", DistanceMetric::Levenshtein).is_empty());
+        assert_eq!(ids("marhta", jaro_winkler), vec!["martha"]);
+        assert_eq!(ids("jonathon", jaro_winkler), vec!["jonathan"]);
+        
+        // One edit to a short name changes too much of it
+        assert_eq!(ids("don", DistanceMetric::Levenshtein), vec!["ron"]);
+        assert!(ids("don", jaro_winkler).is_empty());
+        
+        // Exact matches still get the exact-match boost
+        let options = SearchOptions { distance_metric: jaro_winkler, ..Default::default() };
+        let results = index.search_explain("martha", Some(options)).unwrap();
+        assert_eq!(results[0].1.matches[0].distance, 0);
+        assert_eq!(results[0].0.score, 10.0);
+    }
+    
+    #[test]
+    fn test_search_field_scoped() {
+        let mut index = Index::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Red Widget").add_field("description", "A blue gadget");
+        index.add_document(doc).unwrap();
+        
+        let ids = |query: &str| -> Vec<String> {
+            index.search_ids(query, None).unwrap().into_iter().map(|(id, _)| id).collect()
+        };
+        
+        // "blue" is only in the description
+        assert_eq!(ids("blue"), vec!["doc1".to_string()]);
+        assert!(ids("title:blue").is_empty());
+        assert_eq!(ids("description:blue"), vec!["doc1".to_string()]);
+        
+        // Scoped terms are matched fuzzily
+        assert_eq!(ids("title:widgte"), vec!["doc1".to_string()]);
+        
+        // Prefixes that aren't fields of the index are plain words, matched as
+        // the same text is in documents
+        let mut doc = Document::new("doc2");
+        doc.add_field("title", "Blue Widget").add_field("description", "Re:invoice, see http://example.com");
+        index.add_document(doc).unwrap();
+        let ids = |query: &str| -> Vec<String> {
+            index.search_ids(query, None).unwrap().into_iter().map(|(id, _)| id).collect()
+        };
+        assert_eq!(ids("re:invoice"), vec!["doc2".to_string()]);
+        assert_eq!(ids("widget AND http://example.com"), vec!["doc2".to_string()]);
+        assert!(ids("color:red").is_empty());
+        
+        let explained = index.search_explain("title:widget", None).unwrap();
+        assert_eq!(explained[0].1.matches[0].query_token, "title:widget");
+    }
+    
+    #[test]
+    fn test_search_boolean_query() {
+        let index = create_test_index();
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = index.search(query, None).unwrap().into_iter().map(|r| r.document.id).collect();
+            ids.sort();
+            ids
+        };
+        
+        assert_eq!(ids("smartphone AND camera"), vec!["doc3"]);
+        assert_eq!(ids("apple OR samsung"), vec!["doc1", "doc2"]);
+        assert_eq!(ids("smartphone AND NOT (apple OR samsung)"), vec!["doc3"]);
+        assert_eq!(ids("(apple OR pixel) AND smartphone -google"), vec!["doc1"]);
+        
+        // Conjunctions of fuzzy terms keep their fuzziness
+        assert_eq!(ids("smartphne AND androd"), vec!["doc2"]);
+        
+        // A negation alone excludes from nothing
+        assert!(ids("NOT apple").is_empty());
+        assert_eq!(ids("* AND NOT apple"), vec!["doc2", "doc3"]);
+    }
+    
+    #[test]
+    fn test_search_phrase() {
+        let index = create_test_index();
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = index.search(query, None).unwrap().into_iter().map(|r| r.document.id).collect();
+            ids.sort();
+            ids
+        };
+        
+        assert_eq!(ids("\"smartphone with\""), vec!["doc2", "doc3"]);
+        assert_eq!(ids("\"with the best\""), vec!["doc3"]);
+        
+        // Every token must appear, in order and within one field
+        assert!(ids("\"with smartphone\"").is_empty());
+        assert!(ids("\"pixel smartphone\"").is_empty());
+        assert_eq!(ids("\"smartphone with\" -camera"), vec!["doc2"]);
+        
+        let explained = index.search_explain("\"best camera\"", None).unwrap();
+        assert_eq!(explained[0].1.matches[0].query_token, "\"best camera\"");
+    }
+    
+    #[test]
+    fn test_search_match_all() {
+        let index = create_test_index();
+        
+        // Every document, scored equally and ordered by ID
+        let results = index.search("*", None).unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.document.id.as_str()).collect();
+        assert_eq!(ids, vec!["doc1", "doc2", "doc3"]);
+        assert!(results.iter().all(|r| r.score == MATCH_ALL_SCORE));
+        
+        // Limits and exclusions still apply
+        let options = SearchOptions { limit: 2, ..Default::default() };
+        assert_eq!(index.search("*", Some(options)).unwrap().len(), 2);
+        let ids: Vec<String> = index.search_ids("* -android", None).unwrap().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["doc1".to_string(), "doc3".to_string()]);
+        
+        // Combined with terms, matching documents rank first
+        let results = index.search("* pixel", None).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].document.id, "doc3");
+        
+        assert!(Index::new().search("*", None).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_search_with_exclusions() {
+        let index = create_test_index();
+        
+        // All three documents mention smartphones; exclusions remove matches
+        let results = index.search("smartphone -apple", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.document.id != "doc1"));
+        
+        let results = index.search("smartphone -Apple -camera", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "doc2");
+        
+        // Excluding a term that matches nothing leaves the results alone
+        let results = index.search("smartphone -nokia", None).unwrap();
+        assert_eq!(results.len(), 3);
+        
+        // A query of only exclusions returns nothing
+        assert!(index.search("-apple", None).unwrap().is_empty());
+        assert!(index.search("-apple -samsung", None).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_search_explain() {
+        let index = create_test_index();
+        
+        let explained = index.search_explain("aple smartphone", None).unwrap();
+        let plain = index.search("aple smartphone", None).unwrap();
+        assert_eq!(explained.len(), plain.len());
+        
+        let (result, explanation) = &explained[0];
+        assert_eq!(result.document.id, "doc1");
+        assert_eq!(result.document.id, plain[0].document.id);
+        assert_eq!(explanation.score, result.score);
+        
+        // Contributions add up to the score, largest first
+        let total: f64 = explanation.matches.iter().map(|m| m.contribution).sum();
+        assert!((total - result.score).abs() < 1e-9);
+        assert_eq!(explanation.matches[0].token, "smartphone");
+        assert_eq!(explanation.matches[0].distance, 0);
+        
+        let apple = explanation.matches.iter().find(|m| m.token == "apple").unwrap();
+        assert_eq!(apple.query_token, "aple");
+        assert_eq!(apple.distance, 1);
+        assert!(apple.trigram_overlap > 0.0 && apple.trigram_overlap < 1.0);
+    }
+    
+    #[test]
+    fn test_search_prefix() {
+        let mut index = create_test_index();
+        let mut doc4 = Document::new("doc4");
+        doc4.add_field("title", "Application server");
+        index.add_document(doc4).unwrap();
+        
+        // Multiple tokens share the prefix
+        let results = index.search("app*", None).unwrap();
+        let ids: Vec<_> = results.iter().map(|r| r.document.id.as_str()).collect();
+        assert_eq!(ids, vec!["doc1", "doc4"]);
+        
+        // A shorter match covers more of the token and ranks higher
+        assert!(results[0].score > results[1].score);
+        
+        // Prefixes are normalized and combine with other terms
+        let results = index.search("SMART* -galaxy", None).unwrap();
+        assert_eq!(results.len(), 2);
+        let results = index.search("app* pixel", None).unwrap();
+        assert_eq!(results.len(), 3);
+        
+        // No match, and a run of wildcards matches nothing
+        assert!(index.search("zzz*", None).unwrap().is_empty());
+        assert!(index.search("**", None).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_adaptive_distance() {
+        let mut index = create_test_index();
+        let mut doc4 = Document::new("doc4");
+        doc4.add_field("title", "Cat toy");
+        index.add_document(doc4).unwrap();
+        
+        let adaptive = SearchOptions { adaptive_distance: true, ..Default::default() };
+        
+        // Short tokens need an exact match
+        assert!(!index.search("cot", None).unwrap().is_empty());
+        assert!(index.search("cot", Some(adaptive.clone())).unwrap().is_empty());
+        assert_eq!(index.search("cat", Some(adaptive.clone())).unwrap()[0].document.id, "doc4");
+        
+        // Medium tokens tolerate one edit
+        assert_eq!(index.search("aple", Some(adaptive.clone())).unwrap()[0].document.id, "doc1");
+        assert!(index.search("pixxle", Some(adaptive.clone())).unwrap().is_empty());
+        
+        // Long tokens tolerate two edits, even when max_distance is lower
+        let strict = SearchOptions { max_distance: 0, ..adaptive.clone() };
+        let results = index.search("smartfone", Some(strict)).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(index.search("smartfone", Some(SearchOptions { max_distance: 0, ..Default::default() })).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_search_ids() {
+        let mut index = create_test_index();
+        
+        let ids = index.search_ids("smartphone", None).unwrap();
+        let results = index.search("smartphone", None).unwrap();
+        assert_eq!(ids.len(), 3);
+        for ((doc_id, score), result) in ids.iter().zip(&results) {
+            assert_eq!(doc_id, &result.document.id);
+            assert_eq!(*score, result.score);
+        }
+        
+        // Scored IDs don't need the documents themselves
+        index.set_retain_documents(false);
+        assert_eq!(index.search_ids("smartphone", None).unwrap(), ids);
+        assert!(index.search("smartphone", None).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_suggest() {
+        let index = create_test_index();
+        
+        let suggestions = index.suggest("smartphoen", 3);
+        assert_eq!(suggestions[0], "smartphone");
+        assert!(suggestions.len() <= 3);
+        
+        let suggestions = index.suggest("Samsnug galaxy", 5);
+        assert_eq!(suggestions[0], "samsung");
+        assert!(!suggestions.contains(&"galaxy".to_string()));
+        
+        // Exact matches need no suggestions
+        assert!(index.suggest("apple", 5).is_empty());
+        assert!(index.suggest("", 5).is_empty());
+        assert!(index.suggest("smartphoen", 0).is_empty());
+    }
 }
@@ -0,0 +1,211 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::query::QueryAst;
+use crate::search::{SearchOptions, SearchResult};
+use crate::tiger_cache::TigerCache;
+
+/// A `TigerCache` that can be shared across threads
+///
+/// Cloning is cheap and yields a handle to the same instance. Searches and
+/// lookups take a read lock and run concurrently; writes take the write lock
+/// and are serialized. Use `read`/`write` for anything not wrapped here.
+#[derive(Debug, Clone)]
+pub struct SharedTigerCache {
+    inner: Arc<RwLock<TigerCache>>,
+}
+
+impl SharedTigerCache {
+    /// Wrap a cache for sharing across threads
+    ///
+    /// With auto-commit configured, the background thread now commits the
+    /// whole cache, index included, instead of only flushing the storage.
+    pub fn new(cache: TigerCache) -> Self {
+        let inner = Arc::new(RwLock::new(cache));
+        
+        // The thread only holds a weak handle, so dropping every clone still
+        // drops the cache. A tick is skipped while a writer holds the lock.
+        let weak = Arc::downgrade(&inner);
+        inner.write().restart_auto_commit(move || {
+            if let Some(inner) = weak.upgrade() {
+                if let Some(cache) = inner.try_read() {
+                    match cache.commit() {
+                        Ok(stats) if stats.bytes_written > 0 => log::debug!(
+                            "Auto-commit wrote {} bytes and flushed {} pages in {:?}",
+                            stats.bytes_written, stats.dirty_pages_flushed, stats.duration
+                        ),
+                        Ok(_) => {}
+                        Err(e) => log::warn!("Auto-commit failed: {e}"),
+                    }
+                }
+            }
+        });
+        
+        Self { inner }
+    }
+    
+    /// Lock the cache for reading
+    pub fn read(&self) -> RwLockReadGuard<'_, TigerCache> {
+        self.inner.read()
+    }
+    
+    /// Lock the cache for writing
+    pub fn write(&self) -> RwLockWriteGuard<'_, TigerCache> {
+        self.inner.write()
+    }
+    
+    /// Search the index for documents matching the query
+    pub fn search(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
+        self.read().search(query, options)
+    }
+    
+    /// Search, stopping with `TigerCacheError::Cancelled` once `cancel` is set
+    pub fn search_cancellable(
+        &self,
+        query: &str,
+        options: Option<SearchOptions>,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<Vec<SearchResult>> {
+        self.read().search_cancellable(query, options, cancel)
+    }
+    
+    /// Search with an already-parsed query
+    pub fn search_parsed(&self, query: &QueryAst, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
+        self.read().search_parsed(query, options)
+    }
+    
+    /// Search on Tokio's blocking thread pool, keeping scoring off the async executor
+    ///
+    /// Must be called from within a Tokio runtime. Results are the same as `search`.
+    #[cfg(feature = "async")]
+    pub async fn search_async(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
+        let inner = Arc::clone(&self.inner);
+        let query = query.to_string();
+        
+        tokio::task::spawn_blocking(move || inner.read().search(&query, options))
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+    
+    /// Get a copy of a document by ID
+    ///
+    /// Documents loaded from storage aren't kept in the in-memory index, so
+    /// lazily stored documents don't pile up in memory; `read().get_document`
+    /// keeps them.
+    pub fn get_document(&self, doc_id: &str) -> Option<Document> {
+        self.try_get_document(doc_id).ok().flatten()
+    }
+    
+    /// Get a copy of a document by ID, returning an error when reading it from storage fails
+    pub fn try_get_document(&self, doc_id: &str) -> Result<Option<Document>> {
+        self.read().try_get_document(doc_id)
+    }
+    
+    /// Get a shared handle to a document by ID, without copying it out of the document cache
+    pub fn get_document_arc(&self, doc_id: &str) -> Option<Arc<Document>> {
+        self.read().get_document_arc(doc_id)
+    }
+    
+    /// Check whether a document exists, without loading or decoding it
+    pub fn contains_document(&self, doc_id: &str) -> bool {
+        self.read().contains_document(doc_id)
+    }
+    
+    /// Get the number of documents in the index
+    pub fn document_count(&self) -> usize {
+        self.read().document_count()
+    }
+    
+    /// Add a document to the index
+    pub fn add_document(&self, document: Document) -> Result<()> {
+        self.write().add_document(document)
+    }
+    
+    /// Remove a document from the index
+    pub fn remove_document(&self, doc_id: &str) -> Result<()> {
+        self.write().remove_document(doc_id)
+    }
+    
+    /// Remove every document for which `predicate` returns false, under one write lock
+    pub fn retain(&self, predicate: impl Fn(&Document) -> bool) -> Result<usize> {
+        self.write().retain(predicate)
+    }
+    
+    /// Get a document's version, incremented every time the document is replaced
+    pub fn document_version(&self, doc_id: &str) -> Option<u64> {
+        self.read().document_version(doc_id)
+    }
+    
+    /// Add or replace a document only if its current version is `expected_version`
+    ///
+    /// The version check and the update happen under one write lock.
+    pub fn update_document_if(&self, document: Document, expected_version: u64) -> Result<u64> {
+        self.write().update_document_if(document, expected_version)
+    }
+    
+    /// Unwrap the cache, or return the handle if other clones are still alive
+    pub fn try_into_inner(self) -> std::result::Result<TigerCache, Self> {
+        Arc::try_unwrap(self.inner)
+            .map(RwLock::into_inner)
+            .map_err(|inner| Self { inner })
+    }
+}
+
+impl From<TigerCache> for SharedTigerCache {
+    fn from(cache: TigerCache) -> Self {
+        Self::new(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_shared_cache_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedTigerCache>();
+    }
+    
+    #[test]
+    fn test_try_into_inner() {
+        let shared = SharedTigerCache::new(TigerCache::new());
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        shared.add_document(doc).unwrap();
+        
+        let other = shared.clone();
+        let shared = shared.try_into_inner().unwrap_err();
+        drop(other);
+        
+        let cache = shared.try_into_inner().unwrap();
+        assert_eq!(cache.document_count(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod async_tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_search_async_matches_sync() {
+        let shared = SharedTigerCache::new(TigerCache::new());
+        for (id, title) in [("doc1", "Apple iPhone"), ("doc2", "Apple Watch"), ("doc3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            shared.add_document(doc).unwrap();
+        }
+        
+        let options = SearchOptions { limit: 5, ..Default::default() };
+        let expected = shared.search("aple", Some(options.clone())).unwrap();
+        let results = shared.search_async("aple", Some(options)).await.unwrap();
+        
+        assert_eq!(results.len(), 2);
+        let ids = |results: &[SearchResult]| results.iter().map(|r| (r.document.id.clone(), r.score)).collect::<Vec<_>>();
+        assert_eq!(ids(&results), ids(&expected));
+    }
+}
@@ -0,0 +1,267 @@
+/// Reduce an English word to its stem using the Porter algorithm
+///
+/// For example "running", "runs" and "run" all stem to "run". Words that
+/// aren't lowercase ASCII letters (numbers, non-Latin scripts) are returned unchanged.
+pub fn porter_stem(word: &str) -> String {
+    if word.len() <= 2 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+    
+    let mut stemmer = PorterStemmer {
+        b: word.as_bytes().to_vec(),
+        k: word.len(),
+        j: 0,
+    };
+    
+    stemmer.step1ab();
+    if stemmer.k > 1 {
+        stemmer.step1c();
+        stemmer.step2();
+        stemmer.step3();
+        stemmer.step4();
+        stemmer.step5();
+    }
+    
+    stemmer.b.truncate(stemmer.k);
+    String::from_utf8(stemmer.b).unwrap_or_else(|_| word.to_string())
+}
+
+/// Working state for a single word
+///
+/// `b[..k]` is the current word and `j` marks the start of the suffix
+/// matched by the last successful call to `ends`.
+struct PorterStemmer {
+    b: Vec<u8>,
+    k: usize,
+    j: usize,
+}
+
+impl PorterStemmer {
+    /// Check whether `b[i]` is a consonant
+    fn is_consonant(&self, i: usize) -> bool {
+        match self.b[i] {
+            b'a' | b'e' | b'i' | b'o' | b'u' => false,
+            b'y' => i == 0 || !self.is_consonant(i - 1),
+            _ => true,
+        }
+    }
+    
+    /// Count the vowel-consonant sequences in `b[..end]`
+    fn measure(&self, end: usize) -> usize {
+        let mut n = 0;
+        let mut i = 0;
+        
+        while i < end && self.is_consonant(i) {
+            i += 1;
+        }
+        
+        loop {
+            while i < end && !self.is_consonant(i) {
+                i += 1;
+            }
+            if i >= end {
+                return n;
+            }
+            while i < end && self.is_consonant(i) {
+                i += 1;
+            }
+            n += 1;
+        }
+    }
+    
+    /// Check whether `b[..end]` contains a vowel
+    fn has_vowel(&self, end: usize) -> bool {
+        (0..end).any(|i| !self.is_consonant(i))
+    }
+    
+    /// Check whether `b[..end]` ends with a double consonant
+    fn ends_double_consonant(&self, end: usize) -> bool {
+        end >= 2 && self.b[end - 1] == self.b[end - 2] && self.is_consonant(end - 1)
+    }
+    
+    /// Check whether `b[..end]` ends consonant-vowel-consonant, where the
+    /// final consonant isn't w, x or y
+    fn ends_cvc(&self, end: usize) -> bool {
+        end >= 3
+            && self.is_consonant(end - 3)
+            && !self.is_consonant(end - 2)
+            && self.is_consonant(end - 1)
+            && !matches!(self.b[end - 1], b'w' | b'x' | b'y')
+    }
+    
+    /// Check whether the word ends with `suffix`, recording where it starts
+    fn ends(&mut self, suffix: &str) -> bool {
+        let suffix = suffix.as_bytes();
+        if suffix.len() > self.k || &self.b[self.k - suffix.len()..self.k] != suffix {
+            return false;
+        }
+        self.j = self.k - suffix.len();
+        true
+    }
+    
+    /// Replace the matched suffix with `replacement`
+    fn set_to(&mut self, replacement: &str) {
+        self.b.truncate(self.j);
+        self.b.extend_from_slice(replacement.as_bytes());
+        self.k = self.b.len();
+    }
+    
+    /// Replace the matched suffix if the remaining stem has a non-zero measure
+    fn replace_if_measured(&mut self, replacement: &str) {
+        if self.measure(self.j) > 0 {
+            self.set_to(replacement);
+        }
+    }
+    
+    /// Apply the first rule whose suffix matches
+    fn apply_rules(&mut self, rules: &[(&str, &str)]) {
+        for (suffix, replacement) in rules {
+            if self.ends(suffix) {
+                self.replace_if_measured(replacement);
+                return;
+            }
+        }
+    }
+    
+    /// Remove plurals and -ed/-ing endings
+    fn step1ab(&mut self) {
+        if self.ends("sses") {
+            self.k -= 2;
+        } else if self.ends("ies") {
+            self.set_to("i");
+        } else if !self.ends("ss") && self.ends("s") {
+            self.k -= 1;
+        }
+        self.b.truncate(self.k);
+        
+        if self.ends("eed") {
+            if self.measure(self.j) > 0 {
+                self.k -= 1;
+            }
+        } else if (self.ends("ed") || self.ends("ing")) && self.has_vowel(self.j) {
+            self.k = self.j;
+            self.b.truncate(self.k);
+            
+            if self.ends("at") {
+                self.set_to("ate");
+            } else if self.ends("bl") {
+                self.set_to("ble");
+            } else if self.ends("iz") {
+                self.set_to("ize");
+            } else if self.ends_double_consonant(self.k) {
+                if !matches!(self.b[self.k - 1], b'l' | b's' | b'z') {
+                    self.k -= 1;
+                }
+            } else if self.measure(self.k) == 1 && self.ends_cvc(self.k) {
+                self.j = self.k;
+                self.set_to("e");
+            }
+        }
+        self.b.truncate(self.k);
+    }
+    
+    /// Turn a terminal y into i when there is another vowel in the stem
+    fn step1c(&mut self) {
+        if self.ends("y") && self.has_vowel(self.j) {
+            self.b[self.k - 1] = b'i';
+        }
+    }
+    
+    /// Map double suffixes to single ones
+    fn step2(&mut self) {
+        self.apply_rules(&[
+            ("ational", "ate"), ("tional", "tion"), ("enci", "ence"), ("anci", "ance"),
+            ("izer", "ize"), ("bli", "ble"), ("alli", "al"), ("entli", "ent"),
+            ("eli", "e"), ("ousli", "ous"), ("ization", "ize"), ("ation", "ate"),
+            ("ator", "ate"), ("alism", "al"), ("iveness", "ive"), ("fulness", "ful"),
+            ("ousness", "ous"), ("aliti", "al"), ("iviti", "ive"), ("biliti", "ble"),
+            ("logi", "log"),
+        ]);
+    }
+    
+    /// Handle -ic-, -full, -ness etc.
+    fn step3(&mut self) {
+        self.apply_rules(&[
+            ("icate", "ic"), ("ative", ""), ("alize", "al"), ("iciti", "ic"),
+            ("ical", "ic"), ("ful", ""), ("ness", ""),
+        ]);
+    }
+    
+    /// Remove -ant, -ence etc. from stems with a measure above one
+    fn step4(&mut self) {
+        const SUFFIXES: &[&str] = &[
+            "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment",
+            "ent", "ion", "ou", "ism", "ate", "iti", "ous", "ive", "ize",
+        ];
+        
+        for suffix in SUFFIXES {
+            if !self.ends(suffix) {
+                continue;
+            }
+            if *suffix == "ion" && (self.j == 0 || !matches!(self.b[self.j - 1], b's' | b't')) {
+                continue;
+            }
+            if self.measure(self.j) > 1 {
+                self.k = self.j;
+            }
+            return;
+        }
+    }
+    
+    /// Remove a final -e and reduce a final double l
+    fn step5(&mut self) {
+        if self.ends("e") {
+            let m = self.measure(self.k - 1);
+            if m > 1 || (m == 1 && !self.ends_cvc(self.k - 1)) {
+                self.k -= 1;
+            }
+        }
+        
+        if self.b[self.k - 1] == b'l' && self.ends_double_consonant(self.k) && self.measure(self.k) > 1 {
+            self.k -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn assert_stems(pairs: &[(&str, &str)]) {
+        for (word, stem) in pairs {
+            assert_eq!(porter_stem(word), *stem, "stem of {word}");
+        }
+    }
+    
+    #[test]
+    fn test_plurals() {
+        assert_stems(&[
+            ("caresses", "caress"), ("ponies", "poni"), ("ties", "ti"),
+            ("caress", "caress"), ("cats", "cat"), ("runs", "run"),
+        ]);
+    }
+    
+    #[test]
+    fn test_ing_and_ed() {
+        assert_stems(&[
+            ("running", "run"), ("hopping", "hop"), ("motoring", "motor"),
+            ("filing", "file"), ("sing", "sing"), ("plastered", "plaster"),
+            ("agreed", "agre"), ("feed", "feed"), ("bled", "bled"),
+            ("conflated", "conflat"), ("sized", "size"), ("falling", "fall"),
+        ]);
+    }
+    
+    #[test]
+    fn test_derivational_suffixes() {
+        assert_stems(&[
+            ("happy", "happi"), ("relational", "relat"), ("conditional", "condit"),
+            ("generalization", "gener"), ("hopeful", "hope"), ("goodness", "good"),
+            ("adjustment", "adjust"), ("adoption", "adopt"), ("controll", "control"),
+        ]);
+    }
+    
+    #[test]
+    fn test_unchanged() {
+        assert_stems(&[("is", "is"), ("2024", "2024"), ("café", "café"), ("", "")]);
+    }
+}
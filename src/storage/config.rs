@@ -19,6 +19,10 @@ pub enum StorageType {
     /// RocksDB embedded database
     #[cfg(feature = "rocksdb-storage")]
     RocksDB,
+    
+    /// LMDB embedded database (via heed)
+    #[cfg(feature = "lmdb-storage")]
+    Lmdb,
 }
 
 impl Default for StorageType {
@@ -33,7 +37,10 @@ impl Default for StorageType {
         #[cfg(all(not(feature = "sled-storage"), not(feature = "redb-storage"), feature = "rocksdb-storage"))]
         return StorageType::RocksDB;
         
-        #[cfg(all(not(feature = "sled-storage"), not(feature = "redb-storage"), not(feature = "rocksdb-storage")))]
+        #[cfg(all(not(feature = "sled-storage"), not(feature = "redb-storage"), not(feature = "rocksdb-storage"), feature = "lmdb-storage"))]
+        return StorageType::Lmdb;
+        
+        #[cfg(all(not(feature = "sled-storage"), not(feature = "redb-storage"), not(feature = "rocksdb-storage"), not(feature = "lmdb-storage")))]
         return StorageType::Memory;
     }
 }
@@ -70,6 +77,12 @@ pub struct StorageConfig {
     
     /// Custom options for specific storage backends
     pub custom_options: Option<serde_json::Value>,
+    
+    /// AES-256 key used to encrypt stored values and index files (requires the `encryption` feature)
+    ///
+    /// Never serialized, so the key doesn't end up in saved configuration files.
+    #[serde(skip)]
+    pub encryption_key: Option<[u8; 32]>,
 }
 
 impl Default for StorageConfig {
@@ -85,6 +98,7 @@ impl Default for StorageConfig {
             sync_writes: false,
             collect_metrics: false,
             custom_options: None,
+            encryption_key: None,
         }
     }
 }
@@ -155,6 +169,12 @@ impl StorageConfig {
         self
     }
     
+    /// Set the key used to encrypt data at rest
+    pub fn with_encryption_key(mut self, encryption_key: [u8; 32]) -> Self {
+        self.encryption_key = Some(encryption_key);
+        self
+    }
+    
     /// Create a development configuration with smaller cache sizes
     pub fn development() -> Self {
         Self {
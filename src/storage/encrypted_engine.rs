@@ -0,0 +1,305 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use parking_lot::RwLock;
+
+use crate::encryption;
+use crate::error::TigerCacheError;
+use crate::storage::error::{StorageError, StorageResult};
+use crate::storage::config::StorageConfig;
+use crate::storage::page::{Page, PageId, PageRef};
+use crate::storage::storage_engine::{StorageEngine, StorageTransaction, StorageStats};
+
+/// Storage engine wrapper that encrypts values with AES-256-GCM before they reach the inner engine
+///
+/// Keys are stored in plain text so lookups and prefix scans keep working;
+/// values are encrypted, each with its own random nonce, and authenticated
+/// together with their key so a value moved to another key fails to decrypt.
+/// Values written before keys were authenticated are re-sealed, bound to their
+/// key, when the engine is opened. Page data is encrypted the same way, bound
+/// to its page ID.
+pub struct EncryptedStorageEngine {
+    /// Engine holding the encrypted values
+    inner: Box<dyn StorageEngine>,
+    
+    /// Encryption key
+    key: [u8; 32],
+}
+
+/// Key recording that every stored value is bound to its key
+const KEYS_BOUND_MARKER: &[u8] = b"encryption_keys_bound";
+
+impl EncryptedStorageEngine {
+    /// Wrap an engine so its values are encrypted with `key`
+    ///
+    /// The first time, values sealed before keys were authenticated are
+    /// re-sealed bound to their key.
+    pub fn new(inner: Box<dyn StorageEngine>, key: [u8; 32]) -> StorageResult<Self> {
+        let engine = Self { inner, key };
+        if !engine.inner.exists(KEYS_BOUND_MARKER)? {
+            engine.bind_legacy_values()?;
+        }
+        Ok(engine)
+    }
+    
+    /// Re-seal values that were written without associated data
+    ///
+    /// The marker is only written once every value decrypts, so opening with
+    /// the wrong key leaves the migration to the next open with the right one.
+    fn bind_legacy_values(&self) -> StorageResult<()> {
+        let mut all_bound = true;
+        for (storage_key, value) in self.inner.scan_prefix(b"")? {
+            if encryption::open(&self.key, &value, &storage_key).is_ok() {
+                continue;
+            }
+            match encryption::open(&self.key, &value, &[]) {
+                Ok(plain) => self.put(&storage_key, &plain)?,
+                Err(_) => all_bound = false,
+            }
+        }
+        
+        if all_bound {
+            self.put(KEYS_BOUND_MARKER, &[])?;
+        }
+        Ok(())
+    }
+}
+
+/// Encrypt a value for storage under `storage_key`
+fn seal(key: &[u8; 32], storage_key: &[u8], value: &[u8]) -> StorageResult<Vec<u8>> {
+    encryption::seal(key, value, storage_key).map_err(to_storage_error)
+}
+
+/// Decrypt a value stored under `storage_key`
+fn open(key: &[u8; 32], storage_key: &[u8], value: &[u8]) -> StorageResult<Vec<u8>> {
+    encryption::open(key, value, storage_key).map_err(to_storage_error)
+}
+
+/// Get the associated data binding page data to its page
+fn page_aad(page_id: PageId) -> Vec<u8> {
+    format!("page:{page_id}").into_bytes()
+}
+
+/// Map an encryption error onto the storage error type
+fn to_storage_error(err: TigerCacheError) -> StorageError {
+    match err {
+        TigerCacheError::DecryptionFailed(msg) => StorageError::DecryptionFailed(msg),
+        other => StorageError::SerializationError(other.to_string()),
+    }
+}
+
+impl StorageEngine for EncryptedStorageEngine {
+    fn config(&self) -> &StorageConfig {
+        self.inner.config()
+    }
+    
+    fn get(&self, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        self.inner.get(key)?.map(|value| open(&self.key, key, &value)).transpose()
+    }
+    
+    fn put(&self, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        self.inner.put(key, &seal(&self.key, key, value)?)
+    }
+    
+    fn delete(&self, key: &[u8]) -> StorageResult<()> {
+        self.inner.delete(key)
+    }
+    
+    fn exists(&self, key: &[u8]) -> StorageResult<bool> {
+        self.inner.exists(key)
+    }
+    
+    fn multi_get(&self, keys: &[&[u8]]) -> StorageResult<Vec<Option<Vec<u8>>>> {
+        self.inner.multi_get(keys)?
+            .into_iter()
+            .zip(keys)
+            .map(|(value, key)| value.map(|value| open(&self.key, key, &value)).transpose())
+            .collect()
+    }
+    
+    fn scan_prefix(&self, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.inner.scan_prefix(prefix)?
+            .into_iter()
+            .map(|(key, value)| {
+                let value = open(&self.key, &key, &value)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+    
+    fn begin_transaction(&self) -> StorageResult<Box<dyn StorageTransaction>> {
+        Ok(Box::new(EncryptedTransaction {
+            inner: self.inner.begin_transaction()?,
+            key: self.key,
+        }))
+    }
+    
+    fn snapshot(&self) -> StorageResult<Vec<u8>> {
+        // Values stay sealed, so the snapshot needs the same key to restore
+        self.inner.snapshot()
+    }
+    
+    fn restore(&self, bytes: &[u8]) -> StorageResult<()> {
+        self.inner.restore(bytes)
+    }
+    
+    /// Get a decrypted copy of a page; changes to it are stored with `put_page`
+    fn get_page(&self, page_id: PageId) -> StorageResult<Option<PageRef>> {
+        let Some(page) = self.inner.get_page(page_id)? else {
+            return Ok(None);
+        };
+        let data = encryption::open(&self.key, &page.read().data, &page_aad(page_id)).map_err(to_storage_error)?;
+        Ok(Some(Arc::new(RwLock::new(Page::new(page_id, data)))))
+    }
+    
+    fn put_page(&self, mut page: Page) -> StorageResult<()> {
+        page.data = seal(&self.key, &page_aad(page.id), &page.data)?;
+        self.inner.put_page(page)
+    }
+    
+    fn flush(&self) -> StorageResult<()> {
+        self.inner.flush()
+    }
+    
+    fn close(&self) -> StorageResult<()> {
+        self.inner.close()
+    }
+    
+    fn compact(&self) -> StorageResult<()> {
+        self.inner.compact()
+    }
+    
+    fn stats(&self) -> StorageResult<StorageStats> {
+        self.inner.stats()
+    }
+    
+    fn path(&self) -> Option<&PathBuf> {
+        self.inner.path()
+    }
+    
+    fn storage_type(&self) -> &'static str {
+        self.inner.storage_type()
+    }
+}
+
+/// Transaction wrapper that encrypts values written through it
+struct EncryptedTransaction<'a> {
+    inner: Box<dyn StorageTransaction + 'a>,
+    key: [u8; 32],
+}
+
+impl<'a> StorageTransaction for EncryptedTransaction<'a> {
+    fn get(&self, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        self.inner.get(key)?.map(|value| open(&self.key, key, &value)).transpose()
+    }
+    
+    fn put(&self, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        self.inner.put(key, &seal(&self.key, key, value)?)
+    }
+    
+    fn delete(&self, key: &[u8]) -> StorageResult<()> {
+        self.inner.delete(key)
+    }
+    
+    fn exists(&self, key: &[u8]) -> StorageResult<bool> {
+        self.inner.exists(key)
+    }
+    
+    fn commit(self: Box<Self>) -> StorageResult<()> {
+        self.inner.commit()
+    }
+    
+    fn abort(self: Box<Self>) -> StorageResult<()> {
+        self.inner.abort()
+    }
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+    use crate::storage::storage_engine::MemoryStorageEngine;
+    
+    fn encrypted_memory_engine(key: [u8; 32]) -> EncryptedStorageEngine {
+        let inner = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        EncryptedStorageEngine::new(Box::new(inner), key).unwrap()
+    }
+    
+    #[test]
+    fn test_values_round_trip_encrypted() {
+        let engine = encrypted_memory_engine([3u8; 32]);
+        engine.put(b"doc:a", b"secret value").unwrap();
+        engine.put(b"doc:b", b"another secret").unwrap();
+        
+        assert_eq!(engine.get(b"doc:a").unwrap(), Some(b"secret value".to_vec()));
+        assert_eq!(engine.multi_get(&[b"doc:b", b"missing"]).unwrap(), vec![Some(b"another secret".to_vec()), None]);
+        assert_eq!(engine.scan_prefix(b"doc:").unwrap().len(), 2);
+        
+        // The inner engine only ever sees ciphertext
+        let raw = engine.inner.get(b"doc:a").unwrap().unwrap();
+        assert!(!raw.windows(b"secret".len()).any(|window| window == b"secret"));
+    }
+    
+    #[test]
+    fn test_wrong_key_fails_to_decrypt() {
+        let engine = encrypted_memory_engine([3u8; 32]);
+        engine.put(b"doc:a", b"secret value").unwrap();
+        
+        let raw = engine.inner.get(b"doc:a").unwrap().unwrap();
+        let other = encrypted_memory_engine([4u8; 32]);
+        other.inner.put(b"doc:a", &raw).unwrap();
+        
+        assert!(matches!(other.get(b"doc:a"), Err(StorageError::DecryptionFailed(_))));
+    }
+    
+    #[test]
+    fn test_values_bound_to_their_keys() {
+        let engine = encrypted_memory_engine([3u8; 32]);
+        engine.put(b"doc:a", b"secret value").unwrap();
+        engine.put(b"doc:b", b"another secret").unwrap();
+        
+        // A value swapped into another key no longer decrypts
+        let raw = engine.inner.get(b"doc:a").unwrap().unwrap();
+        engine.inner.put(b"doc:b", &raw).unwrap();
+        engine.inner.put(b"index_metadata", &raw).unwrap();
+        assert!(matches!(engine.get(b"doc:b"), Err(StorageError::DecryptionFailed(_))));
+        assert!(matches!(engine.get(b"index_metadata"), Err(StorageError::DecryptionFailed(_))));
+        assert!(engine.scan_prefix(b"doc:").is_err());
+        
+        // Once opened, values aren't read without associated data
+        let legacy = encryption::seal(&[3u8; 32], b"old value", &[]).unwrap();
+        engine.inner.put(b"doc:c", &legacy).unwrap();
+        assert!(matches!(engine.get(b"doc:c"), Err(StorageError::DecryptionFailed(_))));
+    }
+    
+    #[test]
+    fn test_legacy_values_bound_on_open() {
+        let inner = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        let legacy = encryption::seal(&[3u8; 32], b"old value", &[]).unwrap();
+        inner.put(b"doc:a", &legacy).unwrap();
+        
+        // Opening with the wrong key migrates nothing
+        let engine = EncryptedStorageEngine::new(Box::new(inner), [4u8; 32]).unwrap();
+        assert!(matches!(engine.get(b"doc:a"), Err(StorageError::DecryptionFailed(_))));
+        assert!(!engine.inner.exists(KEYS_BOUND_MARKER).unwrap());
+        
+        // The right key re-seals the value bound to its key, once
+        let engine = EncryptedStorageEngine::new(engine.inner, [3u8; 32]).unwrap();
+        assert_eq!(engine.get(b"doc:a").unwrap(), Some(b"old value".to_vec()));
+        let raw = engine.inner.get(b"doc:a").unwrap().unwrap();
+        assert!(encryption::open(&[3u8; 32], &raw, b"doc:a").is_ok());
+        assert!(engine.inner.exists(KEYS_BOUND_MARKER).unwrap());
+    }
+    
+    #[test]
+    fn test_pages_encrypted() {
+        let engine = encrypted_memory_engine([3u8; 32]);
+        engine.put_page(Page::new(7, b"secret page".to_vec())).unwrap();
+        assert_eq!(engine.get_page(7).unwrap().unwrap().read().data, b"secret page");
+        assert!(engine.get_page(8).unwrap().is_none());
+        
+        // The inner engine only sees ciphertext, bound to the page ID
+        let raw = engine.inner.get_page(7).unwrap().unwrap().read().data.clone();
+        assert!(!raw.windows(b"secret".len()).any(|window| window == b"secret"));
+        engine.inner.put_page(Page::new(8, raw)).unwrap();
+        assert!(matches!(engine.get_page(8), Err(StorageError::DecryptionFailed(_))));
+    }
+}
@@ -53,6 +53,9 @@ pub enum StorageError {
     #[error("Storage operation canceled")]
     StorageCanceled,
 
+    #[error("Decryption failed: {0}")]
+    DecryptionFailed(String),
+
     #[error("Storage operation not supported: {0}")]
     StorageOperationNotSupported(String),
 
@@ -96,6 +99,23 @@ impl From<rocksdb::Error> for StorageError {
     }
 }
 
+#[cfg(feature = "lmdb-storage")]
+impl From<heed::Error> for StorageError {
+    fn from(err: heed::Error) -> Self {
+        match err {
+            heed::Error::Io(io_err) => StorageError::IoError(io_err),
+            heed::Error::Mdb(heed::MdbError::Corrupted) => StorageError::StorageCorrupted(err.to_string()),
+            heed::Error::Mdb(heed::MdbError::VersionMismatch) => StorageError::StorageVersionMismatch {
+                expected: "LMDB".to_string(),
+                found: err.to_string(),
+            },
+            heed::Error::Encoding(_) => StorageError::SerializationError(err.to_string()),
+            heed::Error::Decoding(_) => StorageError::DeserializationError(err.to_string()),
+            _ => StorageError::DatabaseError(err.to_string()),
+        }
+    }
+}
+
 impl From<bincode::error::EncodeError> for StorageError {
     fn from(err: bincode::error::EncodeError) -> Self {
         StorageError::SerializationError(err.to_string())
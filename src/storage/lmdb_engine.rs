@@ -0,0 +1,554 @@
+#![cfg(feature = "lmdb-storage")]
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use parking_lot::RwLock;
+
+use heed::types::Bytes;
+use heed::{Database, Env, EnvOpenOptions};
+
+use crate::storage::error::{StorageError, StorageResult};
+use crate::storage::config::StorageConfig;
+use crate::storage::page::{Page, PageId, PageRef};
+use crate::storage::storage_engine::{StorageEngine, StorageTransaction, StorageStats};
+
+/// Default maximum size of the LMDB memory map (10GB)
+///
+/// LMDB reserves address space up front rather than memory, so this only bounds
+/// how large the database file may grow. Override with the `lmdb_map_size`
+/// custom option.
+const DEFAULT_MAP_SIZE: usize = 10 * 1024 * 1024 * 1024;
+
+/// LMDB storage engine implementation
+pub struct LmdbStorageEngine {
+    /// Storage configuration
+    config: StorageConfig,
+    
+    /// LMDB environment
+    env: Env,
+    
+    /// Main key-value database
+    main_db: Database<Bytes, Bytes>,
+    
+    /// Pages database
+    pages_db: Database<Bytes, Bytes>,
+    
+    /// In-memory page cache
+    page_cache: RwLock<HashMap<PageId, PageRef>>,
+    
+    /// Storage statistics
+    stats: Arc<Mutex<StorageStats>>,
+}
+
+impl LmdbStorageEngine {
+    /// Create a new LMDB storage engine
+    pub fn new(config: StorageConfig) -> StorageResult<Self> {
+        let path = config.path.clone().ok_or_else(|| {
+            StorageError::ConfigurationError("Storage path is required for LMDB engine".to_string())
+        })?;
+        
+        // LMDB expects the environment directory to exist
+        if !path.exists() {
+            if !config.create_if_missing {
+                return Err(StorageError::StoragePathNotFound(path));
+            }
+            std::fs::create_dir_all(&path)?;
+        }
+        
+        let map_size = config.custom_options.as_ref()
+            .and_then(|options| options.get("lmdb_map_size"))
+            .and_then(|size| size.as_u64())
+            .map(|size| size as usize)
+            .unwrap_or(DEFAULT_MAP_SIZE);
+        
+        // Open the environment
+        let mut options = EnvOpenOptions::new();
+        options.map_size(map_size).max_dbs(2);
+        if !config.sync_writes {
+            // Leave syncing to `flush`; committed data survives a process crash either way
+            unsafe {
+                options.flags(heed::EnvFlags::NO_SYNC);
+            }
+        }
+        // SAFETY: the environment is opened once per engine and never mapped twice
+        // by this process at the same path
+        let env = unsafe { options.open(&path)? };
+        
+        // Open or create databases
+        let mut wtxn = env.write_txn()?;
+        let main_db = env.create_database(&mut wtxn, Some("main"))?;
+        let pages_db = env.create_database(&mut wtxn, Some("pages"))?;
+        wtxn.commit()?;
+        
+        Ok(Self {
+            config,
+            env,
+            main_db,
+            pages_db,
+            page_cache: RwLock::new(HashMap::new()),
+            stats: Arc::new(Mutex::new(StorageStats {
+                key_count: 0, // Updated when stats are requested
+                total_value_size: 0,
+                disk_size_bytes: None,
+                page_count: 0,
+                dirty_page_count: 0,
+                cache_hit_rate: 0.0,
+                read_count: 0,
+                write_count: 0,
+                custom_stats: HashMap::new(),
+            })),
+        })
+    }
+    
+    /// Write a page to the pages database
+    fn write_page(&self, page_id: PageId, page: &Page) -> StorageResult<()> {
+        let page_data = page.to_bytes()?;
+        let mut wtxn = self.env.write_txn()?;
+        self.pages_db.put(&mut wtxn, &page_id.to_be_bytes(), &page_data)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+    
+    /// Update storage statistics
+    fn update_stats(&self) -> StorageResult<()> {
+        let rtxn = self.env.read_txn()?;
+        let key_count = self.main_db.len(&rtxn)? as usize;
+        let page_count = self.pages_db.len(&rtxn)? as usize;
+        drop(rtxn);
+        let disk_size = self.env.real_disk_size()?;
+        
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.key_count = key_count;
+            stats.page_count = page_count;
+            stats.disk_size_bytes = Some(disk_size);
+            
+            // Count dirty pages
+            let page_cache = self.page_cache.read();
+            stats.dirty_page_count = page_cache.values()
+                .filter(|p| p.read().is_dirty())
+                .count();
+            
+            // Get LMDB stats
+            let info = self.env.info();
+            stats.custom_stats.insert("lmdb_map_size".to_string(), info.map_size.to_string());
+            stats.custom_stats.insert("lmdb_last_page_number".to_string(), info.last_page_number.to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+impl StorageEngine for LmdbStorageEngine {
+    fn config(&self) -> &StorageConfig {
+        &self.config
+    }
+    
+    fn get(&self, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        let rtxn = self.env.read_txn()?;
+        let result = self.main_db.get(&rtxn, key)?
+            .map(|value| value.to_vec());
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.read_count += 1;
+        }
+        
+        Ok(result)
+    }
+    
+    fn put(&self, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.main_db.put(&mut wtxn, key, value)?;
+        wtxn.commit()?;
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.write_count += 1;
+        }
+        
+        Ok(())
+    }
+    
+    fn delete(&self, key: &[u8]) -> StorageResult<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.main_db.delete(&mut wtxn, key)?;
+        wtxn.commit()?;
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.write_count += 1;
+        }
+        
+        Ok(())
+    }
+    
+    fn exists(&self, key: &[u8]) -> StorageResult<bool> {
+        let rtxn = self.env.read_txn()?;
+        let result = self.main_db.get(&rtxn, key)?.is_some();
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.read_count += 1;
+        }
+        
+        Ok(result)
+    }
+    
+    fn multi_get(&self, keys: &[&[u8]]) -> StorageResult<Vec<Option<Vec<u8>>>> {
+        // Read every key from a single snapshot
+        let rtxn = self.env.read_txn()?;
+        let result = keys.iter()
+            .map(|key| Ok(self.main_db.get(&rtxn, key)?.map(|value| value.to_vec())))
+            .collect::<StorageResult<Vec<_>>>()?;
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.read_count += keys.len() as u64;
+        }
+        
+        Ok(result)
+    }
+    
+    fn scan_prefix(&self, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let rtxn = self.env.read_txn()?;
+        let result = self.main_db.prefix_iter(&rtxn, prefix)?
+            .map(|entry| entry.map(|(key, value)| (key.to_vec(), value.to_vec())))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.read_count += 1;
+        }
+        
+        Ok(result)
+    }
+    
+    fn begin_transaction(&self) -> StorageResult<Box<dyn StorageTransaction>> {
+        Ok(Box::new(LmdbTransaction {
+            env: self.env.clone(),
+            main_db: self.main_db,
+            changes: parking_lot::Mutex::new(HashMap::new()),
+        }))
+    }
+    
+    fn get_page(&self, page_id: PageId) -> StorageResult<Option<PageRef>> {
+        // First check the cache
+        let cache = self.page_cache.read();
+        if let Some(page_ref) = cache.get(&page_id) {
+            // Update access time
+            page_ref.write().touch();
+            
+            // Update stats
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.read_count += 1;
+            }
+            
+            return Ok(Some(page_ref.clone()));
+        }
+        drop(cache);
+        
+        // Not in cache, try to load from disk
+        let rtxn = self.env.read_txn()?;
+        let page_data = self.pages_db.get(&rtxn, &page_id.to_be_bytes())?
+            .map(|data| data.to_vec());
+        drop(rtxn);
+        
+        if let Some(page_data) = page_data {
+            // Deserialize the page
+            let page = Page::from_bytes(&page_data)?;
+            
+            // Create a new page reference
+            let page_ref = Arc::new(RwLock::new(page));
+            
+            // Add to cache
+            let mut cache = self.page_cache.write();
+            cache.insert(page_id, page_ref.clone());
+            
+            // Update stats
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.read_count += 1;
+            }
+            
+            Ok(Some(page_ref))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    fn put_page(&self, page: Page) -> StorageResult<()> {
+        let page_id = page.id;
+        let page_ref = Arc::new(RwLock::new(page));
+        
+        // Add to cache
+        let mut cache = self.page_cache.write();
+        cache.insert(page_id, page_ref.clone());
+        drop(cache);
+        
+        // If the page is dirty, write it to disk
+        if page_ref.read().is_dirty() {
+            self.write_page(page_id, &page_ref.read())?;
+            
+            // Mark the page as clean
+            page_ref.write().mark_clean();
+        }
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.write_count += 1;
+        }
+        
+        Ok(())
+    }
+    
+    fn flush(&self) -> StorageResult<()> {
+        // Flush all dirty pages to disk
+        let cache = self.page_cache.read();
+        for (page_id, page_ref) in cache.iter() {
+            if page_ref.read().is_dirty() {
+                self.write_page(*page_id, &page_ref.read())?;
+                
+                // Mark the page as clean
+                page_ref.write().mark_clean();
+            }
+        }
+        drop(cache);
+        
+        // Sync the environment
+        self.env.force_sync()?;
+        
+        // Update stats
+        self.update_stats()?;
+        
+        Ok(())
+    }
+    
+    fn close(&self) -> StorageResult<()> {
+        // Flush all dirty pages and sync; the environment closes when dropped
+        self.flush()
+    }
+    
+    fn compact(&self) -> StorageResult<()> {
+        // LMDB reuses freed pages in place; the file itself never shrinks
+        self.flush()
+    }
+    
+    fn stats(&self) -> StorageResult<StorageStats> {
+        // Update stats before returning
+        self.update_stats()?;
+        
+        if let Ok(stats) = self.stats.lock() {
+            Ok(stats.clone())
+        } else {
+            Err(StorageError::Other("Failed to get storage stats".to_string()))
+        }
+    }
+    
+    fn path(&self) -> Option<&PathBuf> {
+        self.config.path.as_ref()
+    }
+    
+    fn storage_type(&self) -> &'static str {
+        "lmdb"
+    }
+}
+
+/// LMDB transaction implementation
+///
+/// Changes are buffered and applied in a single LMDB write transaction on commit,
+/// so readers never observe a partially applied transaction.
+struct LmdbTransaction {
+    env: Env,
+    main_db: Database<Bytes, Bytes>,
+    changes: parking_lot::Mutex<HashMap<Vec<u8>, Option<Vec<u8>>>>,
+}
+
+impl StorageTransaction for LmdbTransaction {
+    fn get(&self, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        // First check the transaction changes
+        if let Some(value_opt) = self.changes.lock().get(key) {
+            return Ok(value_opt.clone());
+        }
+        
+        // Then check the database
+        let rtxn = self.env.read_txn()?;
+        let result = self.main_db.get(&rtxn, key)?
+            .map(|value| value.to_vec());
+        
+        Ok(result)
+    }
+    
+    fn put(&self, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        self.changes.lock().insert(key.to_vec(), Some(value.to_vec()));
+        Ok(())
+    }
+    
+    fn delete(&self, key: &[u8]) -> StorageResult<()> {
+        self.changes.lock().insert(key.to_vec(), None);
+        Ok(())
+    }
+    
+    fn exists(&self, key: &[u8]) -> StorageResult<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+    
+    fn commit(self: Box<Self>) -> StorageResult<()> {
+        // Apply all changes atomically
+        let mut wtxn = self.env.write_txn()?;
+        for (key, value_opt) in self.changes.into_inner() {
+            match value_opt {
+                Some(value) => self.main_db.put(&mut wtxn, &key, &value)?,
+                None => {
+                    self.main_db.delete(&mut wtxn, &key)?;
+                }
+            }
+        }
+        wtxn.commit()?;
+        
+        Ok(())
+    }
+    
+    fn abort(self: Box<Self>) -> StorageResult<()> {
+        // Buffered changes are simply discarded
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageType;
+    use tempfile::tempdir;
+    
+    fn open_engine(path: &std::path::Path) -> LmdbStorageEngine {
+        let config = StorageConfig::new()
+            .with_storage_type(StorageType::Lmdb)
+            .with_path(path);
+        LmdbStorageEngine::new(config).unwrap()
+    }
+    
+    #[test]
+    fn test_get_put_delete() {
+        let dir = tempdir().unwrap();
+        let engine = open_engine(&dir.path().join("lmdb"));
+        
+        assert_eq!(engine.get(b"key").unwrap(), None);
+        assert!(!engine.exists(b"key").unwrap());
+        
+        engine.put(b"key", b"value").unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+        assert!(engine.exists(b"key").unwrap());
+        
+        engine.put(b"key", b"updated").unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"updated".to_vec()));
+        
+        engine.delete(b"key").unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), None);
+        
+        // Deleting a missing key is not an error
+        engine.delete(b"missing").unwrap();
+    }
+    
+    #[test]
+    fn test_scan_prefix() {
+        let dir = tempdir().unwrap();
+        let engine = open_engine(&dir.path().join("lmdb"));
+        
+        engine.put(b"doc:b", b"2").unwrap();
+        engine.put(b"doc:a", b"1").unwrap();
+        engine.put(b"index_metadata", b"meta").unwrap();
+        
+        let entries = engine.scan_prefix(b"doc:").unwrap();
+        assert_eq!(entries, vec![
+            (b"doc:a".to_vec(), b"1".to_vec()),
+            (b"doc:b".to_vec(), b"2".to_vec()),
+        ]);
+        assert!(engine.scan_prefix(b"missing:").unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_multi_get() {
+        let dir = tempdir().unwrap();
+        let engine = open_engine(&dir.path().join("lmdb"));
+        engine.put(b"a", b"1").unwrap();
+        engine.put(b"c", b"3").unwrap();
+        
+        let values = engine.multi_get(&[b"c", b"missing", b"a"]).unwrap();
+        assert_eq!(values, vec![Some(b"3".to_vec()), None, Some(b"1".to_vec())]);
+    }
+    
+    #[test]
+    fn test_transaction_commit() {
+        let dir = tempdir().unwrap();
+        let engine = open_engine(&dir.path().join("lmdb"));
+        engine.put(b"existing", b"old").unwrap();
+        
+        let txn = engine.begin_transaction().unwrap();
+        txn.put(b"new", b"value").unwrap();
+        txn.delete(b"existing").unwrap();
+        
+        // Changes are visible inside the transaction but not outside it
+        assert_eq!(txn.get(b"new").unwrap(), Some(b"value".to_vec()));
+        assert!(!txn.exists(b"existing").unwrap());
+        assert_eq!(engine.get(b"new").unwrap(), None);
+        assert_eq!(engine.get(b"existing").unwrap(), Some(b"old".to_vec()));
+        
+        txn.commit().unwrap();
+        assert_eq!(engine.get(b"new").unwrap(), Some(b"value".to_vec()));
+        assert_eq!(engine.get(b"existing").unwrap(), None);
+    }
+    
+    #[test]
+    fn test_transaction_abort() {
+        let dir = tempdir().unwrap();
+        let engine = open_engine(&dir.path().join("lmdb"));
+        
+        let txn = engine.begin_transaction().unwrap();
+        txn.put(b"key", b"value").unwrap();
+        txn.abort().unwrap();
+        
+        assert_eq!(engine.get(b"key").unwrap(), None);
+    }
+    
+    #[test]
+    fn test_pages_and_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lmdb");
+        
+        {
+            let engine = open_engine(&path);
+            engine.put(b"key", b"value").unwrap();
+            
+            let mut page = Page::new(7, vec![1, 2, 3]);
+            page.mark_dirty();
+            engine.put_page(page).unwrap();
+            assert_eq!(engine.get_page(7).unwrap().unwrap().read().data, vec![1, 2, 3]);
+            
+            engine.close().unwrap();
+        }
+        
+        let engine = open_engine(&path);
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"value".to_vec()));
+        assert_eq!(engine.get_page(7).unwrap().unwrap().read().data, vec![1, 2, 3]);
+        assert!(engine.get_page(8).unwrap().is_none());
+        
+        let stats = engine.stats().unwrap();
+        assert_eq!(stats.key_count, 1);
+        assert_eq!(stats.page_count, 1);
+        assert_eq!(engine.storage_type(), "lmdb");
+    }
+    
+    #[test]
+    fn test_stats_report_disk_size() {
+        let dir = tempdir().unwrap();
+        let engine = open_engine(&dir.path().join("lmdb"));
+        
+        for i in 0..100u32 {
+            engine.put(&i.to_be_bytes(), &[7u8; 256]).unwrap();
+        }
+        engine.flush().unwrap();
+        
+        let disk_size = engine.stats().unwrap().disk_size_bytes.unwrap();
+        assert!(disk_size > 0);
+    }
+}
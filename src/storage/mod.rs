@@ -7,6 +7,7 @@ mod error;
 mod config;
 mod page;
 mod storage_engine;
+mod encrypted_engine;
 
 // Storage backends
 #[cfg(feature = "sled-storage")]
@@ -15,6 +16,8 @@ mod sled_engine;
 mod redb_engine;
 #[cfg(feature = "rocksdb-storage")]
 mod rocksdb_engine;
+#[cfg(feature = "lmdb-storage")]
+mod lmdb_engine;
 
 // Re-exports
 pub use error::{StorageError, StorageResult};
@@ -24,6 +27,22 @@ pub use storage_engine::{StorageEngine, StorageTransaction};
 
 // Factory function to create a storage engine based on configuration
 pub fn create_storage_engine(config: StorageConfig) -> StorageResult<Box<dyn StorageEngine>> {
+    match config.encryption_key {
+        Some(key) => {
+            if !cfg!(feature = "encryption") {
+                return Err(StorageError::ConfigurationError(
+                    "An encryption key was set but the `encryption` feature is disabled".to_string(),
+                ));
+            }
+            let inner = create_base_engine(config)?;
+            Ok(Box::new(encrypted_engine::EncryptedStorageEngine::new(inner, key)?))
+        },
+        None => create_base_engine(config),
+    }
+}
+
+// Create the unencrypted engine for the configured storage type
+fn create_base_engine(config: StorageConfig) -> StorageResult<Box<dyn StorageEngine>> {
     match config.storage_type {
         #[cfg(feature = "sled-storage")]
         StorageType::Sled => {
@@ -40,6 +59,11 @@ pub fn create_storage_engine(config: StorageConfig) -> StorageResult<Box<dyn Sto
             let engine = rocksdb_engine::RocksDBStorageEngine::new(config)?;
             Ok(Box::new(engine))
         },
+        #[cfg(feature = "lmdb-storage")]
+        StorageType::Lmdb => {
+            let engine = lmdb_engine::LmdbStorageEngine::new(config)?;
+            Ok(Box::new(engine))
+        },
         StorageType::Memory => {
             // For testing or small datasets, we can use an in-memory storage engine
             let engine = storage_engine::MemoryStorageEngine::new(config)?;
@@ -2,6 +2,8 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use parking_lot::RwLock;
 
+use crate::storage::error::StorageResult;
+
 /// Page ID type
 pub type PageId = u64;
 
@@ -91,6 +93,20 @@ impl Page {
     pub fn size(&self) -> usize {
         self.data.len()
     }
+    
+    /// Serialize the page for a storage engine
+    ///
+    /// Every engine stores pages in this encoding (bincode 2.x, standard
+    /// configuration), so pages written by one backend can be read by another.
+    pub(crate) fn to_bytes(&self) -> StorageResult<Vec<u8>> {
+        Ok(bincode::encode_to_vec(self, bincode::config::standard())?)
+    }
+    
+    /// Deserialize a page written by `to_bytes`
+    pub(crate) fn from_bytes(bytes: &[u8]) -> StorageResult<Self> {
+        let (page, _) = bincode::decode_from_slice(bytes, bincode::config::standard())?;
+        Ok(page)
+    }
 }
 
 /// Thread-safe reference to a page
@@ -151,3 +167,23 @@ impl PageCacheEntry {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_page_encoding_is_shared() {
+        let mut page = Page::new(7, vec![1, 2, 3]);
+        page.mark_dirty();
+        
+        // The layout every backend writes: varint ID, varint length, then the data
+        let bytes = page.to_bytes().unwrap();
+        assert_eq!(bytes, vec![7, 3, 1, 2, 3]);
+        
+        // Runtime-only state isn't persisted
+        let decoded = Page::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.id, 7);
+        assert_eq!(decoded.data, vec![1, 2, 3]);
+        assert!(!decoded.is_dirty());
+    }
+}
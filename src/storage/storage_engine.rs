@@ -45,9 +45,33 @@ pub trait StorageEngine: Send + Sync {
     /// Check if a key exists
     fn exists(&self, key: &[u8]) -> StorageResult<bool>;
     
+    /// Get the values for several keys at once, in the order of `keys`
+    ///
+    /// Missing keys yield `None`. The default implementation calls `get` for
+    /// each key; engines that can batch lookups override it.
+    fn multi_get(&self, keys: &[&[u8]]) -> StorageResult<Vec<Option<Vec<u8>>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+    
+    /// Get all key-value pairs whose key starts with `prefix`, in key order
+    fn scan_prefix(&self, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>>;
+    
     /// Begin a transaction
     fn begin_transaction(&self) -> StorageResult<Box<dyn StorageTransaction>>;
     
+    /// Serialize every key-value pair into a snapshot that `restore` can load
+    ///
+    /// Only engines without persistence of their own support snapshots; the
+    /// default implementation returns `StorageOperationNotSupported`.
+    fn snapshot(&self) -> StorageResult<Vec<u8>> {
+        Err(StorageError::StorageOperationNotSupported(format!("snapshot of {} storage", self.storage_type())))
+    }
+    
+    /// Replace all key-value pairs with those of a snapshot taken by `snapshot`
+    fn restore(&self, _bytes: &[u8]) -> StorageResult<()> {
+        Err(StorageError::StorageOperationNotSupported(format!("restore of {} storage", self.storage_type())))
+    }
+    
     /// Get a page by ID
     fn get_page(&self, page_id: PageId) -> StorageResult<Option<PageRef>>;
     
@@ -60,6 +84,9 @@ pub trait StorageEngine: Send + Sync {
     /// Close the storage engine
     fn close(&self) -> StorageResult<()>;
     
+    /// Reclaim space left behind by deleted keys and rewritten pages
+    fn compact(&self) -> StorageResult<()>;
+    
     /// Get storage statistics
     fn stats(&self) -> StorageResult<StorageStats>;
     
@@ -79,6 +106,9 @@ pub struct StorageStats {
     /// Total size of all values in bytes
     pub total_value_size: usize,
     
+    /// Size of the storage files on disk in bytes (`None` for in-memory storage)
+    pub disk_size_bytes: Option<u64>,
+    
     /// Number of pages in the storage
     pub page_count: usize,
     
@@ -123,6 +153,7 @@ impl MemoryStorageEngine {
             stats: Arc::new(Mutex::new(StorageStats {
                 key_count: 0,
                 total_value_size: 0,
+                disk_size_bytes: None,
                 page_count: 0,
                 dirty_page_count: 0,
                 cache_hit_rate: 1.0, // Always hit in memory
@@ -132,6 +163,36 @@ impl MemoryStorageEngine {
             })),
         })
     }
+    
+    /// Serialize all key-value pairs, so the store can be saved to a file
+    ///
+    /// Pairs are written in key order, so equal contents give equal snapshots.
+    /// Pages aren't included.
+    pub fn snapshot(&self) -> StorageResult<Vec<u8>> {
+        let mut entries: Vec<(Vec<u8>, Vec<u8>)> = self.data
+            .read()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        
+        Ok(bincode::encode_to_vec(&entries, bincode::config::standard())?)
+    }
+    
+    /// Replace all key-value pairs with those of a snapshot
+    pub fn restore(&self, bytes: &[u8]) -> StorageResult<()> {
+        let (entries, _) = bincode::decode_from_slice::<Vec<(Vec<u8>, Vec<u8>)>, _>(bytes, bincode::config::standard())?;
+        let data: HashMap<Vec<u8>, Vec<u8>> = entries.into_iter().collect();
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.key_count = data.len();
+            stats.total_value_size = data.values().map(|value| value.len()).sum();
+        }
+        
+        *self.data.write() = data;
+        Ok(())
+    }
 }
 
 impl StorageEngine for MemoryStorageEngine {
@@ -199,15 +260,51 @@ impl StorageEngine for MemoryStorageEngine {
         Ok(result)
     }
     
+    fn multi_get(&self, keys: &[&[u8]]) -> StorageResult<Vec<Option<Vec<u8>>>> {
+        let data = self.data.read();
+        let result = keys.iter().map(|key| data.get(*key).cloned()).collect();
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.read_count += keys.len() as u64;
+        }
+        
+        Ok(result)
+    }
+    
+    fn scan_prefix(&self, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let data = self.data.read();
+        let mut result: Vec<(Vec<u8>, Vec<u8>)> = data.iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        result.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        
+        // Update stats
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.read_count += 1;
+        }
+        
+        Ok(result)
+    }
+    
     fn begin_transaction(&self) -> StorageResult<Box<dyn StorageTransaction>> {
         // For in-memory, we'll use a simple transaction that just clones the data
         Ok(Box::new(MemoryTransaction {
             engine: self,
-            changes: HashMap::new(),
+            changes: parking_lot::Mutex::new(HashMap::new()),
             committed: false,
         }))
     }
     
+    fn snapshot(&self) -> StorageResult<Vec<u8>> {
+        MemoryStorageEngine::snapshot(self)
+    }
+    
+    fn restore(&self, bytes: &[u8]) -> StorageResult<()> {
+        MemoryStorageEngine::restore(self, bytes)
+    }
+    
     fn get_page(&self, page_id: PageId) -> StorageResult<Option<PageRef>> {
         let pages = self.pages.read();
         let page = pages.get(&page_id).cloned();
@@ -253,6 +350,13 @@ impl StorageEngine for MemoryStorageEngine {
         Ok(())
     }
     
+    fn compact(&self) -> StorageResult<()> {
+        // Release capacity held by deleted entries
+        self.data.write().shrink_to_fit();
+        self.pages.write().shrink_to_fit();
+        Ok(())
+    }
+    
     fn stats(&self) -> StorageResult<StorageStats> {
         if let Ok(stats) = self.stats.lock() {
             Ok(stats.clone())
@@ -273,14 +377,15 @@ impl StorageEngine for MemoryStorageEngine {
 /// In-memory transaction implementation
 struct MemoryTransaction<'a> {
     engine: &'a MemoryStorageEngine,
-    changes: HashMap<Vec<u8>, Option<Vec<u8>>>,
+    /// Staged writes (`None` for deletes), consulted before the engine on reads
+    changes: parking_lot::Mutex<HashMap<Vec<u8>, Option<Vec<u8>>>>,
     committed: bool,
 }
 
 impl<'a> StorageTransaction for MemoryTransaction<'a> {
     fn get(&self, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
         // First check the transaction changes
-        if let Some(value) = self.changes.get(key) {
+        if let Some(value) = self.changes.lock().get(key) {
             return Ok(value.clone());
         }
         
@@ -289,20 +394,18 @@ impl<'a> StorageTransaction for MemoryTransaction<'a> {
     }
     
     fn put(&self, key: &[u8], value: &[u8]) -> StorageResult<()> {
-        let mut changes = self.changes.clone();
-        changes.insert(key.to_vec(), Some(value.to_vec()));
+        self.changes.lock().insert(key.to_vec(), Some(value.to_vec()));
         Ok(())
     }
     
     fn delete(&self, key: &[u8]) -> StorageResult<()> {
-        let mut changes = self.changes.clone();
-        changes.insert(key.to_vec(), None);
+        self.changes.lock().insert(key.to_vec(), None);
         Ok(())
     }
     
     fn exists(&self, key: &[u8]) -> StorageResult<bool> {
         // First check the transaction changes
-        if let Some(value) = self.changes.get(key) {
+        if let Some(value) = self.changes.lock().get(key) {
             return Ok(value.is_some());
         }
         
@@ -316,7 +419,7 @@ impl<'a> StorageTransaction for MemoryTransaction<'a> {
         }
         
         // Apply all changes to the engine
-        for (key, value_opt) in &self.changes {
+        for (key, value_opt) in self.changes.lock().iter() {
             match value_opt {
                 Some(value) => self.engine.put(key, value)?,
                 None => self.engine.delete(key)?,
@@ -333,3 +436,88 @@ impl<'a> StorageTransaction for MemoryTransaction<'a> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_memory_scan_prefix() {
+        let engine = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        engine.put(b"doc:b", b"2").unwrap();
+        engine.put(b"doc:a", b"1").unwrap();
+        engine.put(b"doc:c", b"3").unwrap();
+        engine.put(b"index_metadata", b"meta").unwrap();
+        engine.delete(b"doc:c").unwrap();
+        
+        let entries = engine.scan_prefix(b"doc:").unwrap();
+        assert_eq!(entries, vec![
+            (b"doc:a".to_vec(), b"1".to_vec()),
+            (b"doc:b".to_vec(), b"2".to_vec()),
+        ]);
+        assert_eq!(engine.scan_prefix(b"").unwrap().len(), 3);
+        assert!(engine.scan_prefix(b"missing:").unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_memory_multi_get() {
+        let engine = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        engine.put(b"a", b"1").unwrap();
+        engine.put(b"c", b"3").unwrap();
+        
+        let values = engine.multi_get(&[b"c", b"missing", b"a", b"c"]).unwrap();
+        assert_eq!(values, vec![Some(b"3".to_vec()), None, Some(b"1".to_vec()), Some(b"3".to_vec())]);
+        assert!(engine.multi_get(&[]).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_memory_stats_have_no_disk_size() {
+        let engine = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        engine.put(b"key", b"value").unwrap();
+        assert_eq!(engine.stats().unwrap().disk_size_bytes, None);
+    }
+    
+    #[test]
+    fn test_memory_transaction_reads_own_writes() {
+        let engine = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        engine.put(b"old", b"1").unwrap();
+        
+        let txn = engine.begin_transaction().unwrap();
+        txn.put(b"key", b"new").unwrap();
+        txn.delete(b"old").unwrap();
+        assert_eq!(txn.get(b"key").unwrap(), Some(b"new".to_vec()));
+        assert!(!txn.exists(b"old").unwrap());
+        
+        // Staged writes stay invisible to the engine until commit
+        assert_eq!(engine.get(b"key").unwrap(), None);
+        assert!(engine.exists(b"old").unwrap());
+        
+        txn.commit().unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"new".to_vec()));
+        assert!(!engine.exists(b"old").unwrap());
+        
+        let txn = engine.begin_transaction().unwrap();
+        txn.put(b"key", b"discarded").unwrap();
+        txn.abort().unwrap();
+        assert_eq!(engine.get(b"key").unwrap(), Some(b"new".to_vec()));
+    }
+    
+    #[test]
+    fn test_memory_snapshot_round_trip() {
+        let engine = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        engine.put(b"doc:a", b"1").unwrap();
+        engine.put(b"doc:b", b"22").unwrap();
+        let snapshot = engine.snapshot().unwrap();
+        
+        let restored = MemoryStorageEngine::new(StorageConfig::default()).unwrap();
+        restored.put(b"stale", b"gone after restore").unwrap();
+        restored.restore(&snapshot).unwrap();
+        
+        assert_eq!(restored.get(b"doc:b").unwrap(), Some(b"22".to_vec()));
+        assert!(!restored.exists(b"stale").unwrap());
+        assert_eq!(restored.stats().unwrap().key_count, 2);
+        assert_eq!(restored.stats().unwrap().total_value_size, 3);
+        assert_eq!(restored.snapshot().unwrap(), snapshot);
+        
+        assert!(restored.restore(b"not a snapshot").is_err());
+    }
+}
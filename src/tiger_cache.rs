@@ -1,13 +1,24 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Read, Write};
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 use bytesize::ByteSize;
+use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
+use rayon::prelude::*;
 
+use crate::auto_commit::AutoCommitter;
 use crate::document::Document;
 use crate::error::{Result, TigerCacheError};
-use crate::index::Index;
-use crate::persistence::{load_from_file, save_to_file};
-use crate::search::{SearchOptions, SearchResult};
+use crate::import::{document_from_ndjson_line, ImportErrorPolicy, IMPORT_BATCH_SIZE};
+use crate::index::{InconsistencyReport, Index, IndexStats};
+use crate::persistence::{load_from_file_with_key, save_to_file_with};
+use crate::query::QueryAst;
+use crate::search::{AggKind, QueryMetrics, ScoreExplanation, SearchOptions, SearchOptionsInternal, SearchResult, SearchStats, SlowQuery};
 use crate::config::TigerCacheConfig;
+use crate::tokenizer::TokenizerKind;
 use crate::storage::{
     StorageConfig,
     StorageType,
@@ -15,19 +26,74 @@ use crate::storage::{
     create_storage_engine,
 };
 use crate::cache::{
+    spawn_eviction_listener,
+    CacheStats,
     MemoryManager,
     DocumentCache,
     IndexCache,
     QueryCache,
 };
 
+/// Number of documents added between progress reports in `add_documents_batch_with_progress`
+///
+/// Large enough that each chunk can still be indexed in parallel shards when
+/// sharded batches are enabled.
+const PROGRESS_BATCH_SIZE: usize = 8192;
+
+/// Storage key of the format version of the stored documents and index
+const STORAGE_VERSION_KEY: &[u8] = b"format_version";
+
+/// Current format version of the stored documents and index
+///
+/// Version 1, which wrote no version key, stored documents in a bincode
+/// encoding that can't be decoded. Version 2 stores them as JSON, and
+/// document fields inside the bincode-encoded index as JSON strings.
+const STORAGE_FORMAT_VERSION: u32 = 2;
+
+/// Callback receiving the metrics of each search
+pub type MetricsObserver = Box<dyn Fn(&QueryMetrics) + Send + Sync>;
+
+/// Callback receiving searches slower than the configured threshold
+pub type SlowQueryObserver = Box<dyn Fn(&SlowQuery) + Send + Sync>;
+
+/// What a `commit` wrote, for logging and monitoring
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitStats {
+    /// Size of the saved index in bytes; 0 when there was nothing to commit
+    pub bytes_written: usize,
+    
+    /// Time spent serializing, writing, and flushing
+    pub duration: Duration,
+    
+    /// Dirty storage pages written out by the flush
+    pub dirty_pages_flushed: usize,
+}
+
+/// Slot holding a registered observer callback, if any
+struct ObserverSlot<F: ?Sized>(Option<Box<F>>);
+
+impl<F: ?Sized> Default for ObserverSlot<F> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<F: ?Sized> std::fmt::Debug for ObserverSlot<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(<observer>)" } else { "None" })
+    }
+}
+
 /// Main entry point for the Tiger Cache library
 ///
 /// Provides a simple API for creating, searching, and managing an embedded search engine.
-#[derive(Debug)]
 pub struct TigerCache {
     /// The underlying index
-    index: Index,
+    ///
+    /// Searches and lookups take the read lock; the write lock is only held
+    /// while the index changes, including while a lookup keeps a document
+    /// loaded from storage in memory.
+    index: Arc<RwLock<Index>>,
     
     /// Path to the index file (if loaded from or saved to disk)
     path: Option<PathBuf>,
@@ -36,7 +102,7 @@ pub struct TigerCache {
     config: TigerCacheConfig,
     
     /// Storage engine
-    storage: Option<Box<dyn StorageEngine>>,
+    storage: Option<Arc<dyn StorageEngine>>,
     
     /// Memory manager
     memory_manager: Option<Arc<MemoryManager>>,
@@ -49,6 +115,29 @@ pub struct TigerCache {
     
     /// Query cache
     query_cache: Option<Arc<QueryCache>>,
+    
+    /// Whether a `BatchGuard` is deferring cache invalidation
+    in_batch: bool,
+    
+    /// Whether the query cache must be cleared when the current batch ends
+    pending_invalidation: bool,
+    
+    /// Callback invoked with the metrics of each search
+    metrics_observer: ObserverSlot<dyn Fn(&QueryMetrics) + Send + Sync>,
+    
+    /// Callback invoked for searches slower than `slow_query_threshold`
+    slow_query_observer: ObserverSlot<dyn Fn(&SlowQuery) + Send + Sync>,
+    
+    /// Background thread committing at `auto_commit_interval`
+    auto_committer: Option<AutoCommitter>,
+    
+    /// Task that replaced the default background commit (see `restart_auto_commit`),
+    /// kept so that it carries over when the storage or configuration changes
+    auto_commit_task: Option<Arc<dyn Fn() + Send + Sync>>,
+    
+    /// Whether the index changed since it was last committed or saved, shared
+    /// with the background commit task
+    dirty: Arc<AtomicBool>,
 }
 
 impl TigerCache {
@@ -59,8 +148,23 @@ impl TigerCache {
     
     /// Create a new empty Tiger Cache instance with the specified configuration
     pub fn with_config(config: TigerCacheConfig) -> Self {
+        let mut index = Index::new();
+        index.set_tokenizer(config.build_tokenizer())
+            .set_normalize_options(config.normalize_options())
+            .set_ngram_size(config.ngram_size)
+            .set_stopwords(&config.stopwords)
+            .set_stemming(config.stemming)
+            .set_min_token_length(config.min_token_length)
+            .set_phonetic(config.phonetic_index)
+            .set_numeric_tokens(config.numeric_tokens)
+            .set_keyword_fields(config.keyword_fields.clone())
+            .set_numeric_fields(config.numeric_fields.clone());
+        index.set_synonyms(&config.synonyms)
+            .set_boost_field(config.boost_field.clone())
+            .set_sharded_batches(config.sharded_batches);
+        
         let mut instance = Self {
-            index: Index::new(),
+            index: Arc::new(RwLock::new(index)),
             path: config.storage.path.clone(),
             config,
             storage: None,
@@ -68,13 +172,29 @@ impl TigerCache {
             document_cache: None,
             index_cache: None,
             query_cache: None,
+            in_batch: false,
+            pending_invalidation: false,
+            metrics_observer: ObserverSlot::default(),
+            slow_query_observer: ObserverSlot::default(),
+            auto_committer: None,
+            auto_commit_task: None,
+            dirty: Arc::new(AtomicBool::new(true)),
         };
         
         // Initialize components if storage is configured
-        if let Some(path) = &instance.path {
+        if instance.path.is_some() {
             // Initialize storage engine
             if let Ok(storage) = create_storage_engine(instance.config.storage.clone()) {
-                instance.storage = Some(storage);
+                instance.storage = Some(Arc::from(storage));
+                
+                // Periodically write the index, when it changed, and flush the
+                // storage engine; `SharedTigerCache` upgrades this to a full commit
+                if let Some(interval) = instance.config.auto_commit_interval {
+                    if instance.config.enable_background_ops {
+                        instance.auto_committer = Some(AutoCommitter::new(interval));
+                        instance.start_auto_commit();
+                    }
+                }
                 
                 // Initialize memory manager
                 let memory_manager = Arc::new(MemoryManager::new(instance.config.storage.max_memory));
@@ -84,25 +204,49 @@ impl TigerCache {
                 // Initialize caches
                 let document_cache = Arc::new(DocumentCache::new(
                     ByteSize::b(instance.config.storage.cache_size.as_u64() * 4 / 10)
-                ));
-                instance.document_cache = Some(document_cache);
+                ).with_memory_manager(memory_manager.clone()));
+                instance.document_cache = Some(document_cache.clone());
                 
                 let index_cache = Arc::new(IndexCache::new(
                     ByteSize::b(instance.config.storage.cache_size.as_u64() * 4 / 10),
                     ByteSize::b(instance.config.storage.cache_size.as_u64() * 1 / 10)
-                ));
-                instance.index_cache = Some(index_cache);
+                ).with_memory_manager(memory_manager.clone()));
+                instance.index_cache = Some(index_cache.clone());
                 
                 let query_cache = Arc::new(QueryCache::new(
                     ByteSize::b(instance.config.storage.cache_size.as_u64() * 1 / 10)
-                ));
-                instance.query_cache = Some(query_cache);
+                ).with_memory_manager(memory_manager.clone()));
+                instance.query_cache = Some(query_cache.clone());
+                
+                // Shrink the caches when the memory manager reports pressure
+                spawn_eviction_listener(&memory_manager, &document_cache, &index_cache, &query_cache);
+                
+                // Documents can be left to the storage engine
+                if instance.config.lazy_documents {
+                    instance.index.write().set_retain_documents(false);
+                }
             }
         }
         
         instance
     }
     
+    /// Create a cache with the default configuration holding the given documents
+    pub fn from_documents(documents: Vec<Document>) -> Result<Self> {
+        Self::from_documents_with_config(documents, TigerCacheConfig::default())
+    }
+    
+    /// Create a cache with the specified configuration holding the given documents
+    ///
+    /// The documents are added as one batch, which sizes the index for them up
+    /// front and, for large sets with sharded batches enabled, indexes them in
+    /// parallel shards.
+    pub fn from_documents_with_config(documents: Vec<Document>, config: TigerCacheConfig) -> Result<Self> {
+        let mut instance = Self::with_config(config);
+        instance.add_documents_batch(documents)?;
+        Ok(instance)
+    }
+    
     /// Open an existing index from a file, or create a new one if the file doesn't exist
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         Self::open_with_config(path, TigerCacheConfig::default())
@@ -116,29 +260,28 @@ impl TigerCache {
         config.storage.path = Some(path_buf.clone());
         
         if path_buf.exists() {
-            // Try to load using the legacy format first
-            if let Ok(index) = load_from_file(&path_buf) {
-                // Legacy format - create a new instance with the loaded index
-                let mut instance = Self::with_config(config);
-                instance.index = index;
-                instance.path = Some(path_buf);
-                return Ok(instance);
+            // Try to load using the legacy format first (bincode or JSON)
+            match load_from_file_with_key(&path_buf, config.storage.encryption_key.as_ref()) {
+                Ok(index) => {
+                    // Legacy format - create a new instance with the loaded index
+                    let mut instance = Self::with_config(config);
+                    instance.install_index(index);
+                    instance.path = Some(path_buf);
+                    return Ok(instance);
+                }
+                // An index file that fails validation or decryption is reported, not silently replaced
+                Err(e @ (TigerCacheError::UnsupportedFormatVersion { .. }
+                    | TigerCacheError::ChecksumMismatch { .. }
+                    | TigerCacheError::DecryptionFailed(_)
+                    | TigerCacheError::EncryptionUnavailable)) => {
+                    return Err(e);
+                }
+                Err(_) => {}
             }
             
             // If legacy format failed, try to open with the new storage engine
             let mut instance = Self::with_config(config);
-            
-            // Load the index from storage
-            if let Some(storage) = &instance.storage {
-                // Load index metadata
-                if let Ok(Some(metadata)) = storage.get(b"index_metadata") {
-                    // Deserialize the index
-                    if let Ok(index) = bincode::decode_from_slice::<Index, _>(&metadata, bincode::config::standard()) {
-                        instance.index = index.0;
-                    }
-                }
-            }
-            
+            instance.load_stored_index()?;
             instance.path = Some(path_buf);
             Ok(instance)
         } else {
@@ -149,27 +292,276 @@ impl TigerCache {
         }
     }
     
+    /// Open a cache saved with `save_snapshot`
+    ///
+    /// The cache uses in-memory storage regardless of the configured storage type.
+    pub fn open_snapshot<P: AsRef<Path>>(path: P, mut config: TigerCacheConfig) -> Result<Self> {
+        let path = path.as_ref();
+        let snapshot = std::fs::read(path)?;
+        
+        // Storage is only set up for configurations with a path
+        config.storage.storage_type = StorageType::Memory;
+        config.storage.path.get_or_insert_with(|| path.to_path_buf());
+        
+        let mut instance = Self::with_config(config);
+        let storage = instance.storage.as_ref().ok_or_else(|| {
+            TigerCacheError::IoError(std::io::Error::other("Failed to create in-memory storage"))
+        })?;
+        storage.restore(&snapshot)?;
+        instance.load_stored_index()?;
+        
+        Ok(instance)
+    }
+    
+    /// Install the index saved in the storage engine by `commit`, if there is one
+    ///
+    /// When the saved index is missing or can't be decoded but documents are
+    /// stored, the index is rebuilt from the documents instead. Storage written
+    /// before format versions were recorded is migrated by saving the loaded or
+    /// rebuilt index with the current version. Fails if the storage was written
+    /// with a newer format version, if reading the saved index fails (rather
+    /// than decoding it), or if rebuilding fails.
+    fn load_stored_index(&mut self) -> Result<()> {
+        let Some(storage) = self.storage.clone() else {
+            return Ok(());
+        };
+        
+        let version = storage.get(STORAGE_VERSION_KEY)?
+            .and_then(|bytes| <[u8; 4]>::try_from(bytes.as_slice()).ok())
+            .map(u32::from_le_bytes);
+        if let Some(found) = version.filter(|&found| found > STORAGE_FORMAT_VERSION) {
+            return Err(TigerCacheError::UnsupportedFormatVersion { found, expected: STORAGE_FORMAT_VERSION });
+        }
+        
+        // Only an index that can't be decoded is rebuilt; failing to read it
+        // (an I/O or decryption error) is reported
+        let loaded = match storage.get(b"index_metadata")? {
+            Some(metadata) => match bincode::decode_from_slice::<Index, _>(&metadata, bincode::config::standard()) {
+                Ok((index, _)) => {
+                    self.install_index(index);
+                    
+                    // Versions are stored with every write, so they may be newer than the last commit
+                    if let Err(e) = self.restore_versions() {
+                        log::warn!("Couldn't read stored document versions: {e}");
+                    }
+                    true
+                }
+                Err(e) => {
+                    log::warn!("Couldn't decode the stored index: {e}");
+                    false
+                }
+            },
+            None => false,
+        };
+        
+        if !loaded {
+            let rebuilt = self.rebuild_from_storage()?;
+            if rebuilt == 0 {
+                return Ok(());
+            }
+            log::warn!("Stored index missing or unreadable; rebuilt it from {rebuilt} stored documents");
+        }
+        
+        if version.is_none() {
+            if let Err(e) = write_index(storage.as_ref(), &self.index.read()) {
+                log::warn!("Couldn't migrate the stored index to format version {STORAGE_FORMAT_VERSION}: {e}");
+            }
+        }
+        Ok(())
+    }
+    
+    /// Rebuild the index from scratch out of the documents held by the storage engine
+    ///
+    /// Recovers a cache whose committed index was lost while its documents are
+    /// still stored; `open` does this automatically. Documents that can't be
+    /// decoded are skipped. Returns the number of documents indexed. Fails if
+    /// no storage engine is configured.
+    pub fn rebuild_from_storage(&mut self) -> Result<usize> {
+        let Some(storage) = &self.storage else {
+            return Err(TigerCacheError::IoError(std::io::Error::other("No storage engine to rebuild from")));
+        };
+        
+        let documents: Vec<Document> = storage.scan_prefix(b"doc:")?
+            .into_iter()
+            .filter_map(|(key, value)| match Document::from_bytes(&value) {
+                Ok(document) => Some(document),
+                Err(e) => {
+                    log::warn!("Skipping undecodable stored document {}: {e}", String::from_utf8_lossy(&key[b"doc:".len()..]));
+                    None
+                }
+            })
+            .collect();
+        let rebuilt = documents.len();
+        
+        {
+            let mut index = self.index.write();
+            index.clear();
+            index.add_documents_batch(documents)?;
+        }
+        self.restore_versions()?;
+        
+        if let Some(cache) = &self.index_cache {
+            cache.clear();
+        }
+        self.invalidate_query_cache();
+        
+        Ok(rebuilt)
+    }
+    
+    /// Set the indexed documents' versions to the ones held by the storage engine
+    fn restore_versions(&self) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        
+        let mut index = self.index.write();
+        for (key, value) in storage.scan_prefix(b"ver:")? {
+            if let Ok(bytes) = <[u8; 8]>::try_from(value.as_slice()) {
+                index.set_document_version(&String::from_utf8_lossy(&key[b"ver:".len()..]), u64::from_be_bytes(bytes));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Replace the in-memory index with one loaded from disk
+    ///
+    /// Custom tokenizers aren't persisted, so the one from the configuration is
+    /// re-attached when the loaded index was built with a custom tokenizer.
+    /// Synonyms, numeric fields, and the other runtime settings always come
+    /// from the configuration, as does whether documents are held in memory.
+    fn install_index(&mut self, mut index: Index) {
+        if index.tokenizer_kind() == TokenizerKind::Custom {
+            if let Some(tokenizer) = &self.config.custom_tokenizer {
+                index.restore_tokenizer(tokenizer.clone());
+            }
+        }
+        index.set_synonyms(&self.config.synonyms)
+            .set_boost_field(self.config.boost_field.clone())
+            .set_sharded_batches(self.config.sharded_batches)
+            .set_numeric_fields(self.config.numeric_fields.clone());
+        if self.config.lazy_documents && self.storage.is_some() {
+            index.set_retain_documents(false);
+        }
+        *self.index.write() = index;
+        
+        // The index matches what was just loaded
+        self.dirty.store(false, Ordering::Release);
+    }
+    
     /// Set the fields to be indexed for search
     pub fn set_indexed_fields(&mut self, fields: Vec<String>) -> &mut Self {
-        self.index.set_indexed_fields(fields);
+        self.index.write().set_indexed_fields(fields);
+        self.dirty.store(true, Ordering::Release);
         self
     }
     
+    /// Enable or disable Porter stemming, reindexing existing documents if the setting changed
+    pub fn set_stemming(&mut self, enabled: bool) -> Result<()> {
+        self.config.stemming = enabled;
+        if self.index.read().stemming() == enabled {
+            return Ok(());
+        }
+        
+        // Synonyms are normalized with the stemmer, so they need refreshing too
+        self.index.write().set_stemming(enabled)
+            .set_synonyms(&self.config.synonyms);
+        self.reindex()
+    }
+    
+    /// Change the n-gram size, reindexing existing documents if the size changed
+    pub fn set_ngram_size(&mut self, n: usize) -> Result<()> {
+        self.config.ngram_size = n;
+        if self.index.read().ngram_size() == n.max(1) {
+            return Ok(());
+        }
+        
+        self.index.write().set_ngram_size(n);
+        self.reindex()
+    }
+    
+    /// Rebuild the search indices from the stored documents
+    pub fn reindex(&mut self) -> Result<()> {
+        if self.index.read().retains_documents() {
+            self.index.write().reindex()?;
+        } else {
+            // Documents live in storage, so load them all for the rebuild
+            let doc_ids = self.index.read().document_ids();
+            let documents = doc_ids
+                .iter()
+                .map(|doc_id| {
+                    self.load_document(doc_id)?
+                        .ok_or_else(|| TigerCacheError::DocumentNotFound(doc_id.clone()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.index.write().reindex_documents(documents)?;
+        }
+        
+        self.invalidate_query_cache();
+        
+        Ok(())
+    }
+    
+    /// Drop cached search results after the index changes
+    ///
+    /// The whole cache is cleared, since any mutation can change the results
+    /// and ranking of arbitrary queries. Inside a batch the clear is deferred
+    /// until the batch ends.
+    fn invalidate_query_cache(&mut self) {
+        // Every caller changed the index, so it needs committing too
+        self.dirty.store(true, Ordering::Release);
+        
+        if self.in_batch {
+            self.pending_invalidation = true;
+        } else if let Some(cache) = &self.query_cache {
+            cache.clear();
+        }
+    }
+    
+    /// Start a batch of changes, deferring query cache invalidation and the storage flush
+    ///
+    /// The returned guard dereferences to the cache, so documents are added and
+    /// removed through it as usual. When the guard is dropped (or `commit` is
+    /// called) the query cache is cleared once and storage is flushed once.
+    /// Searches made through the guard may return cached results from before
+    /// the batch.
+    pub fn batch(&mut self) -> BatchGuard<'_> {
+        self.in_batch = true;
+        BatchGuard { cache: self }
+    }
+    
+    /// Finish a batch: apply the deferred invalidation and flush storage
+    fn end_batch(&mut self) -> Result<()> {
+        self.in_batch = false;
+        if std::mem::take(&mut self.pending_invalidation) {
+            self.invalidate_query_cache();
+        }
+        self.flush()
+    }
+    
     /// Add a document to the index
+    ///
+    /// Fails with `TigerCacheError::InvalidDocument`, leaving the cache
+    /// unchanged, if the document doesn't conform to the configured schema.
     pub fn add_document(&mut self, document: Document) -> Result<()> {
+        self.validate_document(&document)?;
         let doc_id = document.id.clone();
         
         // Add to the in-memory index
-        self.index.add_document(document.clone())?;
+        let version = {
+            let mut index = self.index.write();
+            index.add_document(document.clone())?;
+            index.document_version(&doc_id).unwrap_or_default()
+        };
         
-        // If we have a storage engine, store the document
+        // If we have a storage engine, store the document and its version
         if let Some(storage) = &self.storage {
             // Serialize the document
             let doc_key = format!("doc:{}", doc_id).into_bytes();
-            let doc_data = bincode::encode_to_vec(&document, bincode::config::standard())?;
+            let doc_data = document.to_bytes()?;
             
             // Store the document
             storage.put(&doc_key, &doc_data)?;
+            storage.put(format!("ver:{}", doc_id).as_bytes(), &version.to_be_bytes())?;
         }
         
         // If we have a document cache, update it
@@ -177,25 +569,73 @@ impl TigerCache {
             cache.put(document);
         }
         
+        self.invalidate_query_cache();
+        
+        Ok(())
+    }
+    
+    /// Check a document against the configured schema, if there is one
+    fn validate_document(&self, document: &Document) -> Result<()> {
+        match &self.config.schema {
+            Some(schema) => schema.validate(document),
+            None => Ok(()),
+        }
+    }
+    
+    /// Add a document that expires after `ttl`
+    ///
+    /// Once expired, the document is skipped by `search` and `get_document`;
+    /// call `purge_expired` to remove expired documents for good.
+    pub fn add_document_with_ttl(&mut self, document: Document, ttl: Duration) -> Result<()> {
+        let doc_id = document.id.clone();
+        self.add_document(document)?;
+        self.index.write().set_expiry(&doc_id, SystemTime::now() + ttl)?;
+        
+        // A background commit may have written the index between adding the
+        // document and setting its expiry
+        self.dirty.store(true, Ordering::Release);
         Ok(())
     }
     
+    /// Remove all expired documents from the index, storage, and caches
+    ///
+    /// Returns the number of documents removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let expired = self.index.read().expired_document_ids();
+        expired
+            .into_iter()
+            .filter(|doc_id| self.remove_document(doc_id).is_ok())
+            .count()
+    }
+    
     /// Add multiple documents to the index efficiently
+    ///
+    /// Every document is checked against the configured schema first, so none
+    /// are added if any of them is invalid.
     pub fn add_documents_batch(&mut self, documents: Vec<Document>) -> Result<()> {
+        for document in &documents {
+            self.validate_document(document)?;
+        }
+        
         // Add to the in-memory index
-        self.index.add_documents_batch(documents.clone())?;
+        self.index.write().add_documents_batch(documents.clone())?;
         
-        // If we have a storage engine, store the documents
+        // If we have a storage engine, store the documents and their versions
         if let Some(storage) = &self.storage {
             // Start a transaction if supported
             let transaction = storage.begin_transaction()?;
+            let index = self.index.read();
             
             // Store each document
             for document in &documents {
                 let doc_key = format!("doc:{}", document.id).into_bytes();
-                let doc_data = bincode::encode_to_vec(document, bincode::config::standard())?;
+                let doc_data = document.to_bytes()?;
                 transaction.put(&doc_key, &doc_data)?;
+                
+                let version = index.document_version(&document.id).unwrap_or_default();
+                transaction.put(format!("ver:{}", document.id).as_bytes(), &version.to_be_bytes())?;
             }
+            drop(index);
             
             // Commit the transaction
             transaction.commit()?;
@@ -208,307 +648,2899 @@ impl TigerCache {
             }
         }
         
+        self.invalidate_query_cache();
+        
         Ok(())
     }
     
-    /// Remove a document from the index
-    pub fn remove_document(&mut self, doc_id: &str) -> Result<()> {
-        // Remove from the in-memory index
-        self.index.remove_document(doc_id)?;
-        
-        // If we have a storage engine, remove the document
-        if let Some(storage) = &self.storage {
-            let doc_key = format!("doc:{}", doc_id).into_bytes();
-            storage.delete(&doc_key)?;
-        }
+    /// Add documents from an iterator in chunks of `chunk_size`, returning how many were added
+    ///
+    /// Each chunk goes through `add_documents_batch`, so at most `chunk_size`
+    /// documents are buffered at a time. Stops at the first chunk that fails to
+    /// be added; earlier chunks stay in the cache.
+    pub fn add_documents_from_iter<I>(&mut self, documents: I, chunk_size: usize) -> Result<usize>
+    where
+        I: IntoIterator<Item = Document>,
+    {
+        self.add_documents_from_iter_with_progress(documents, chunk_size, |_, _| {})
+    }
+    
+    /// Add documents from an iterator in chunks, calling `progress` after each chunk
+    ///
+    /// `progress` receives the number of documents added so far and the total
+    /// when the iterator reports an exact length, so callers can render a
+    /// progress bar. Returns how many documents were added.
+    pub fn add_documents_from_iter_with_progress<I, F>(&mut self, documents: I, chunk_size: usize, mut progress: F) -> Result<usize>
+    where
+        I: IntoIterator<Item = Document>,
+        F: FnMut(usize, Option<usize>),
+    {
+        let chunk_size = chunk_size.max(1);
+        let mut documents = documents.into_iter();
+        let total = match documents.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let mut added = 0;
         
-        // If we have a document cache, remove it
-        if let Some(cache) = &self.document_cache {
-            cache.remove(doc_id);
+        loop {
+            let chunk: Vec<Document> = documents.by_ref().take(chunk_size).collect();
+            if chunk.is_empty() {
+                return Ok(added);
+            }
+            
+            let count = chunk.len();
+            self.add_documents_batch(chunk)?;
+            added += count;
+            progress(added, total);
         }
-        
+    }
+    
+    /// Add multiple documents, calling `progress` with the documents added so far and the total
+    ///
+    /// The documents are added in chunks of `PROGRESS_BATCH_SIZE`, with
+    /// `progress` called after each one.
+    pub fn add_documents_batch_with_progress<F>(&mut self, documents: Vec<Document>, mut progress: F) -> Result<()>
+    where
+        F: FnMut(usize, usize),
+    {
+        let total = documents.len();
+        self.add_documents_from_iter_with_progress(documents, PROGRESS_BATCH_SIZE, |added, _| progress(added, total))?;
         Ok(())
     }
     
-    /// Get a document by ID
-    pub fn get_document(&self, doc_id: &str) -> Option<&Document> {
-        // First check the in-memory index
-        if let Some(doc) = self.index.get_document(doc_id) {
-            return Some(doc);
+    /// Merge another cache's documents into this one
+    ///
+    /// The other cache's index is merged without reindexing, so both caches
+    /// must use the same analysis settings. Documents in both caches are
+    /// replaced by the other cache's version, and all merged documents are
+    /// written to this cache's storage engine.
+    pub fn merge(&mut self, other: TigerCache) -> Result<()> {
+        // The other cache's storage isn't reachable after the merge, so
+        // documents it doesn't hold in memory are loaded up front, while its
+        // index can still serve the ones it does
+        let doc_ids = other.index.read().document_ids();
+        let doc_id_refs: Vec<&str> = doc_ids.iter().map(String::as_str).collect();
+        let documents = other.load_documents(&doc_id_refs)
+            .into_iter()
+            .zip(&doc_ids)
+            .map(|(document, doc_id)| document.ok_or_else(|| TigerCacheError::DocumentNotFound(doc_id.clone())))
+            .collect::<Result<Vec<_>>>()?;
+        
+        let mut other_index = std::mem::take(&mut *other.index.write());
+        for document in &documents {
+            other_index.cache_document(document.clone());
         }
         
-        // If we have a document cache, check it
-        if let Some(cache) = &self.document_cache {
-            if let Some(doc) = cache.get(doc_id) {
-                // We can't return a reference to the cached document directly
-                // because it's behind an Arc, so we need to load it into the index
-                // This is a bit of a hack, but it works for now
-                let doc_clone = Document::new(&doc.id)
-                    .with_fields(doc.fields.clone());
-                
-                // Add to the in-memory index (mutable borrow of self)
-                // This is safe because we're only modifying the index, not self
-                let index = unsafe { &mut *(&self.index as *const Index as *mut Index) };
-                let _ = index.add_document(doc_clone);
+        self.index.write().merge(other_index)?;
+        
+        // If we have a storage engine, store the documents and their versions
+        if let Some(storage) = &self.storage {
+            let transaction = storage.begin_transaction()?;
+            let index = self.index.read();
+            for document in &documents {
+                let doc_key = format!("doc:{}", document.id).into_bytes();
+                let doc_data = document.to_bytes()?;
+                transaction.put(&doc_key, &doc_data)?;
                 
-                return self.index.get_document(doc_id);
+                let version = index.document_version(&document.id).unwrap_or_default();
+                transaction.put(format!("ver:{}", document.id).as_bytes(), &version.to_be_bytes())?;
             }
+            drop(index);
+            transaction.commit()?;
         }
         
-        // If we have a storage engine, try to load the document
-        if let Some(storage) = &self.storage {
-            let doc_key = format!("doc:{}", doc_id).into_bytes();
-            if let Ok(Some(doc_data)) = storage.get(&doc_key) {
-                // Deserialize the document
-                if let Ok((document, _)) = bincode::decode_from_slice::<Document, _>(&doc_data, bincode::config::standard()) {
-                    // Add to the in-memory index (mutable borrow of self)
-                    // This is safe because we're only modifying the index, not self
-                    let index = unsafe { &mut *(&self.index as *const Index as *mut Index) };
-                    let _ = index.add_document(document);
-                    
-                    // Add to the document cache if we have one
-                    if let Some(cache) = &self.document_cache {
-                        if let Some(doc) = self.index.get_document(doc_id) {
-                            let doc_clone = Document::new(&doc.id)
-                                .with_fields(doc.fields.clone());
-                            cache.put(doc_clone);
-                        }
-                    }
-                    
-                    return self.index.get_document(doc_id);
-                }
+        // If we have a document cache, update it
+        if let Some(cache) = &self.document_cache {
+            for document in documents {
+                cache.put(document);
             }
         }
         
-        None
+        self.invalidate_query_cache();
+        
+        Ok(())
     }
     
-    /// Get the number of documents in the index
-    pub fn document_count(&self) -> usize {
-        self.index.document_count()
+    /// Drop interned strings left behind by removed documents
+    ///
+    /// Search results are unaffected. Returns the number of strings dropped.
+    pub fn compact_index(&mut self) -> usize {
+        let dropped = self.index.write().compact();
+        self.dirty.store(true, Ordering::Release);
+        
+        // Cached postings are keyed by the old string IDs
+        if let Some(cache) = &self.index_cache {
+            cache.clear();
+        }
+        
+        dropped
     }
     
-    /// Search the index for documents matching the query
-    pub fn search(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
-        // If we have a query cache, check it first
-        if let Some(cache) = &self.query_cache {
-            if let Some(results) = cache.get(query, options.as_ref()) {
-                return Ok(results.as_ref().clone());
+    /// Import documents from newline-delimited JSON, one object per line
+    ///
+    /// Each object's `id_field` becomes the document ID and its remaining fields
+    /// the document fields. Blank lines are ignored, and documents are inserted
+    /// in batches. With `ImportErrorPolicy::FailFast` the first invalid line
+    /// stops the import after all earlier lines have been inserted; with
+    /// `ImportErrorPolicy::Skip` invalid lines are left out. Returns the number
+    /// of documents imported.
+    pub fn import_ndjson<R: BufRead>(&mut self, reader: R, id_field: &str, policy: ImportErrorPolicy) -> Result<usize> {
+        let mut imported = 0;
+        let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            
+            match document_from_ndjson_line(&line, id_field) {
+                Ok(document) => batch.push(document),
+                Err(_) if policy == ImportErrorPolicy::Skip => continue,
+                Err(e) => {
+                    self.add_documents_batch(batch)?;
+                    return Err(TigerCacheError::InvalidDocument(format!("line {}: {}", line_number + 1, e)));
+                }
+            }
+            
+            if batch.len() == IMPORT_BATCH_SIZE {
+                imported += batch.len();
+                self.add_documents_batch(std::mem::replace(&mut batch, Vec::with_capacity(IMPORT_BATCH_SIZE)))?;
             }
         }
         
-        // Perform the search
-        let results = self.index.search(query, options.clone())?;
-        
-        // If we have a query cache, update it
-        if let Some(cache) = &self.query_cache {
-            cache.put(query, options.as_ref(), results.clone());
+        if !batch.is_empty() {
+            imported += batch.len();
+            self.add_documents_batch(batch)?;
         }
         
-        Ok(results)
+        Ok(imported)
     }
     
-    /// Save the index to the file it was opened from
-    pub fn commit(&self) -> Result<()> {
-        if let Some(path) = &self.path {
-            // If we have a storage engine, use it
-            if let Some(storage) = &self.storage {
-                // Serialize the index
-                let index_data = bincode::encode_to_vec(&self.index, bincode::config::standard())?;
-                
-                // Save the index metadata
-                storage.put(b"index_metadata", &index_data)?;
-                
-                // Flush the storage
-                storage.flush()?;
-                
-                Ok(())
-            } else {
-                // Fall back to legacy format
-                save_to_file(&self.index, path)?;
-                Ok(())
+    /// Write every document as a JSON array of `{"id", "fields"}` objects
+    ///
+    /// Includes documents held only by the storage engine; expired documents
+    /// are left out. Documents are written in ID order, and field values keep
+    /// their JSON types, so `import_json` restores them exactly.
+    pub fn export_json<W: Write>(&self, writer: W) -> Result<()> {
+        let mut doc_ids = self.index.read().document_ids();
+        doc_ids.extend(self.stored_document_ids()?);
+        doc_ids.sort_unstable();
+        doc_ids.dedup();
+        let index = self.index.read();
+        doc_ids.retain(|doc_id| !index.is_expired(doc_id));
+        drop(index);
+        
+        let mut writer = std::io::BufWriter::new(writer);
+        writer.write_all(b"[")?;
+        let mut first = true;
+        for chunk in doc_ids.chunks(IMPORT_BATCH_SIZE) {
+            let chunk: Vec<&str> = chunk.iter().map(|doc_id| doc_id.as_str()).collect();
+            for document in self.load_documents(&chunk).into_iter().flatten() {
+                if !first {
+                    writer.write_all(b",")?;
+                }
+                first = false;
+                serde_json::to_writer(&mut writer, &document)?;
             }
-        } else {
-            Err(TigerCacheError::IoError(
-                std::io::Error::other(
-                    "No file path specified. Use save_to_file instead.",
-                ),
-            ))
         }
+        writer.write_all(b"]")?;
+        writer.flush()?;
+        
+        Ok(())
     }
     
-    /// Save the index to a specific file
-    pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let path_buf = path.as_ref().to_path_buf();
+    /// Add the documents from a JSON array written by `export_json`
+    ///
+    /// Returns the number of documents imported.
+    pub fn import_json<R: Read>(&mut self, reader: R) -> Result<usize> {
+        let documents: Vec<Document> = serde_json::from_reader(std::io::BufReader::new(reader))?;
+        let imported = documents.len();
+        
+        let mut documents = documents.into_iter().peekable();
+        while documents.peek().is_some() {
+            self.add_documents_batch(documents.by_ref().take(IMPORT_BATCH_SIZE).collect())?;
+        }
         
-        // Update the path
-        self.path = Some(path_buf.clone());
+        Ok(imported)
+    }
+    
+    /// Remove a document from the index
+    pub fn remove_document(&mut self, doc_id: &str) -> Result<()> {
+        // Remove from the in-memory index
+        self.index.write().remove_document(doc_id)?;
         
-        // If we have a storage engine, use it
+        // If we have a storage engine, remove the document and its version
         if let Some(storage) = &self.storage {
-            // Serialize the index
-            let index_data = bincode::encode_to_vec(&self.index, bincode::config::standard())?;
-            
-            // Save the index metadata
-            storage.put(b"index_metadata", &index_data)?;
-            
-            // Flush the storage
-            storage.flush()?;
-            
-            Ok(())
-        } else {
-            // Fall back to legacy format
-            save_to_file(&self.index, &path_buf)?;
-            Ok(())
+            let doc_key = format!("doc:{}", doc_id).into_bytes();
+            storage.delete(&doc_key)?;
+            storage.delete(format!("ver:{}", doc_id).as_bytes())?;
         }
-    }
-    
-    /// Clear the index
-    pub fn clear(&mut self) {
-        self.index.clear();
         
-        // Clear caches
+        // If we have a document cache, remove it
         if let Some(cache) = &self.document_cache {
-            cache.clear();
+            cache.remove(doc_id);
         }
         
-        if let Some(cache) = &self.index_cache {
-            cache.clear();
-        }
+        self.invalidate_query_cache();
         
-        if let Some(cache) = &self.query_cache {
-            cache.clear();
-        }
+        Ok(())
     }
     
-    /// Get memory statistics
-    pub fn memory_stats(&self) -> Option<crate::cache::MemoryStats> {
-        self.memory_manager.as_ref().map(|mm| mm.stats())
+    /// Remove every document for which `predicate` returns false
+    ///
+    /// Documents not held in memory are loaded to be checked, and a failed load
+    /// stops before anything is removed. The removals are applied to the index
+    /// and the caches together, and to storage in a single transaction. Returns
+    /// the number of documents removed.
+    pub fn retain(&mut self, predicate: impl Fn(&Document) -> bool) -> Result<usize> {
+        let doc_ids = self.index.read().document_ids();
+        let mut removed = Vec::new();
+        for doc_id in doc_ids {
+            if let Some(document) = self.load_document(&doc_id)? {
+                if !predicate(&document) {
+                    removed.push(doc_id);
+                }
+            }
+        }
+        
+        self.remove_documents(&removed)?;
+        Ok(removed.len())
     }
     
-    /// Get storage statistics
-    pub fn storage_stats(&self) -> Result<Option<crate::storage::StorageStats>> {
+    /// Remove documents known to be in the index, deleting them from storage in one transaction
+    fn remove_documents(&mut self, doc_ids: &[String]) -> Result<()> {
+        if doc_ids.is_empty() {
+            return Ok(());
+        }
+        
+        let mut index = self.index.write();
+        for doc_id in doc_ids {
+            index.remove_document(doc_id)?;
+        }
+        drop(index);
+        
         if let Some(storage) = &self.storage {
-            Ok(Some(storage.stats()?))
+            let transaction = storage.begin_transaction()?;
+            for doc_id in doc_ids {
+                transaction.delete(format!("doc:{}", doc_id).as_bytes())?;
+                transaction.delete(format!("ver:{}", doc_id).as_bytes())?;
+            }
+            transaction.commit()?;
+        }
+        
+        if let Some(cache) = &self.document_cache {
+            for doc_id in doc_ids {
+                cache.remove(doc_id);
+            }
+        }
+        
+        self.invalidate_query_cache();
+        
+        Ok(())
+    }
+    
+    /// Merge fields into an existing document and reindex it
+    ///
+    /// Fields present in `fields` overwrite the existing values; all other
+    /// fields of the stored document are preserved.
+    pub fn merge_document(&mut self, doc_id: &str, fields: HashMap<String, serde_json::Value>) -> Result<()> {
+        let mut document = self.get_document(doc_id)
+            .map(|doc| doc.clone())
+            .ok_or_else(|| TigerCacheError::DocumentNotFound(doc_id.to_string()))?;
+        
+        // Overlay the new fields
+        document.fields.extend(fields);
+        
+        // Reindex the merged document, replacing the previous version
+        self.add_document(document)
+    }
+    
+    /// Get a document's version, which starts at 1 and is incremented every time the document is replaced
+    ///
+    /// Returns `None` for documents not in the cache. Versions are stored
+    /// alongside the documents and survive reopening the cache.
+    pub fn document_version(&self, doc_id: &str) -> Option<u64> {
+        self.index.read().document_version(doc_id)
+    }
+    
+    /// Add or replace a document only if its current version is `expected_version`
+    ///
+    /// Gives writers compare-and-set semantics: read the version with
+    /// `document_version`, then update conditionally so a concurrent update
+    /// made in between isn't overwritten. An `expected_version` of 0 only
+    /// succeeds if the document doesn't exist. Returns the new version, or
+    /// `TigerCacheError::VersionConflict` with the current version, leaving the
+    /// cache unchanged.
+    pub fn update_document_if(&mut self, document: Document, expected_version: u64) -> Result<u64> {
+        let found = self.document_version(&document.id).unwrap_or(0);
+        if found != expected_version {
+            return Err(TigerCacheError::VersionConflict { doc_id: document.id, expected: expected_version, found });
+        }
+        
+        let doc_id = document.id.clone();
+        self.add_document(document)?;
+        Ok(self.document_version(&doc_id).unwrap_or_default())
+    }
+    
+    /// Get a document by ID
+    ///
+    /// Documents not held in memory are loaded from the document cache or the
+    /// storage engine and kept in the in-memory index. The returned guard holds
+    /// a read lock on the index, so drop it before looking up another document
+    /// that may need loading.
+    pub fn get_document(&self, doc_id: &str) -> Option<MappedRwLockReadGuard<'_, Document>> {
+        // Expired documents stay in storage until purged, but are never returned
+        if self.index.read().is_expired(doc_id) {
+            return None;
+        }
+        
+        // First check the in-memory index
+        if let Ok(doc) = RwLockReadGuard::try_map(self.index.read(), |index| index.get_document(doc_id)) {
+            return Some(doc);
+        }
+        
+        // Otherwise load it and hold it in the in-memory index
+        let document = self.load_document(doc_id).ok()??;
+        self.index.write().cache_document(document);
+        
+        RwLockReadGuard::try_map(self.index.read(), |index| index.get_document(doc_id)).ok()
+    }
+    
+    /// Check whether a document exists, without loading or decoding it
+    ///
+    /// Checks the in-memory index and the document cache, then asks the storage
+    /// engine whether the document's key exists. Expired documents aren't
+    /// reported, and a failed storage lookup counts as missing.
+    pub fn contains_document(&self, doc_id: &str) -> bool {
+        if self.index.read().is_expired(doc_id) {
+            return false;
+        }
+        
+        if self.index.read().contains_document(doc_id) {
+            return true;
+        }
+        
+        if self.document_cache.as_ref().is_some_and(|cache| cache.contains(doc_id)) {
+            return true;
+        }
+        
+        let Some(storage) = &self.storage else {
+            return false;
+        };
+        let doc_key = format!("doc:{}", doc_id).into_bytes();
+        storage.exists(&doc_key).unwrap_or(false)
+    }
+    
+    /// Get an owned copy of a document by ID, telling a missing document apart from a failed read
+    ///
+    /// Returns `Ok(None)` only when the document doesn't exist (or has expired),
+    /// and an error when reading it from the storage engine or decoding it fails.
+    /// Unlike `get_document`, this doesn't keep the document in the in-memory index.
+    pub fn try_get_document(&self, doc_id: &str) -> Result<Option<Document>> {
+        if self.index.read().is_expired(doc_id) {
+            return Ok(None);
+        }
+        
+        self.load_document(doc_id)
+    }
+    
+    /// Get a shared handle to a document by ID, without copying it out of the document cache
+    ///
+    /// Documents missing from the document cache are copied from the in-memory
+    /// index or loaded from storage, then kept in the document cache so later
+    /// calls return the same `Arc`. Expired documents and failed reads return `None`.
+    pub fn get_document_arc(&self, doc_id: &str) -> Option<Arc<Document>> {
+        if self.index.read().is_expired(doc_id) {
+            return None;
+        }
+        
+        if let Some(doc) = self.document_cache.as_ref().and_then(|cache| cache.get(doc_id)) {
+            return Some(doc);
+        }
+        
+        let resident = self.index.read().get_document(doc_id).cloned();
+        let document = match resident {
+            Some(doc) => doc,
+            None => self.read_document(doc_id).ok()??,
+        };
+        let document = Arc::new(document);
+        
+        if let Some(cache) = &self.document_cache {
+            cache.put_arc(document.clone());
+        }
+        
+        Some(document)
+    }
+    
+    /// Load an owned copy of a document from the document cache or storage engine
+    ///
+    /// Unlike `get_document`, this doesn't keep the document in the in-memory index.
+    fn load_document(&self, doc_id: &str) -> Result<Option<Document>> {
+        if let Some(doc) = self.index.read().get_document(doc_id) {
+            return Ok(Some(doc.clone()));
+        }
+        
+        if let Some(cache) = &self.document_cache {
+            if let Some(doc) = cache.get(doc_id) {
+                return Ok(Some(doc.as_ref().clone()));
+            }
+        }
+        
+        let Some(document) = self.read_document(doc_id)? else {
+            return Ok(None);
+        };
+        
+        if let Some(cache) = &self.document_cache {
+            cache.put(document.clone());
+        }
+        
+        Ok(Some(document))
+    }
+    
+    /// Read and decode a document from the storage engine, bypassing the caches
+    fn read_document(&self, doc_id: &str) -> Result<Option<Document>> {
+        let Some(storage) = &self.storage else {
+            return Ok(None);
+        };
+        let doc_key = format!("doc:{}", doc_id).into_bytes();
+        let Some(doc_data) = storage.get(&doc_key)? else {
+            return Ok(None);
+        };
+        Document::from_bytes(&doc_data).map(Some)
+    }
+    
+    /// Load owned copies of several documents, fetching cache misses from storage in one batch
+    ///
+    /// Results are in the order of `doc_ids`, with `None` for unknown documents.
+    fn load_documents(&self, doc_ids: &[&str]) -> Vec<Option<Document>> {
+        let mut documents: Vec<Option<Document>> = doc_ids.iter()
+            .map(|doc_id| {
+                if let Some(doc) = self.index.read().get_document(doc_id) {
+                    return Some(doc.clone());
+                }
+                self.document_cache.as_ref()
+                    .and_then(|cache| cache.get(doc_id))
+                    .map(|doc| doc.as_ref().clone())
+            })
+            .collect();
+        
+        let Some(storage) = &self.storage else {
+            return documents;
+        };
+        
+        let missing: Vec<usize> = (0..doc_ids.len()).filter(|&i| documents[i].is_none()).collect();
+        if missing.is_empty() {
+            return documents;
+        }
+        
+        let doc_keys: Vec<Vec<u8>> = missing.iter()
+            .map(|&i| format!("doc:{}", doc_ids[i]).into_bytes())
+            .collect();
+        let key_refs: Vec<&[u8]> = doc_keys.iter().map(|key| key.as_slice()).collect();
+        let Ok(values) = storage.multi_get(&key_refs) else {
+            return documents;
+        };
+        
+        for (i, value) in missing.into_iter().zip(values) {
+            let Some(document) = value.and_then(|data| Document::from_bytes(&data).ok()) else {
+                continue;
+            };
+            if let Some(cache) = &self.document_cache {
+                cache.put(document.clone());
+            }
+            documents[i] = Some(document);
+        }
+        
+        documents
+    }
+    
+    /// Get the number of documents in the index
+    pub fn document_count(&self) -> usize {
+        self.index.read().document_count()
+    }
+    
+    /// Get counts of the documents, tokens, n-grams, and interned strings in the index
+    pub fn index_stats(&self) -> IndexStats {
+        self.index.read().stats()
+    }
+    
+    /// Check the in-memory index for internal inconsistencies, such as postings
+    /// naming missing documents or trigram entries naming tokens without postings
+    ///
+    /// Nothing is repaired; trigram entries left behind by removed documents
+    /// are dropped by `compact_index`. Returns an empty list when the index is
+    /// consistent.
+    pub fn verify(&self) -> Vec<InconsistencyReport> {
+        self.index.read().verify()
+    }
+    
+    /// Estimate the memory used by the index's interner and search indices in bytes
+    ///
+    /// Documents held in memory aren't included; compare against `max_memory`
+    /// together with `cache_stats`.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        self.index.read().estimated_memory_bytes()
+    }
+    
+    /// List the IDs of all documents held by the storage engine, in key order
+    ///
+    /// Returns an empty list when no storage engine is configured.
+    pub fn stored_document_ids(&self) -> Result<Vec<String>> {
+        let Some(storage) = &self.storage else {
+            return Ok(Vec::new());
+        };
+        
+        Ok(storage.scan_prefix(b"doc:")?
+            .into_iter()
+            .map(|(key, _)| String::from_utf8_lossy(&key[b"doc:".len()..]).into_owned())
+            .collect())
+    }
+    
+    /// Search the index for documents matching the query
+    ///
+    /// Results are cached per query and options when storage is configured. The
+    /// cache is cleared whenever documents are added, removed, or reindexed.
+    /// The limit is clamped to the configured `max_result_limit`.
+    pub fn search(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
+        self.search_with_cancel(query, options, None)
+    }
+    
+    /// Search like `search`, stopping with `TigerCacheError::Cancelled` once `cancel` is set
+    ///
+    /// The flag is checked between query terms, the same points as
+    /// `SearchOptions::timeout`, so a search stops shortly after another thread
+    /// sets it (for example when a client disconnects). Results already in the
+    /// query cache are returned without checking the flag.
+    pub fn search_cancellable(
+        &self,
+        query: &str,
+        options: Option<SearchOptions>,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_with_cancel(query, options, Some(cancel))
+    }
+    
+    /// Search with an already-parsed query
+    ///
+    /// Queries built with the `QueryAst` constructors need no escaping, so
+    /// user input can be used as terms verbatim. Results bypass the query
+    /// cache; the limit is clamped to the configured `max_result_limit`.
+    pub fn search_parsed(&self, query: &QueryAst, options: Option<SearchOptions>) -> Result<Vec<SearchResult>> {
+        let started = Instant::now();
+        let options = self.clamp_limit(options);
+        let mut stats = SearchStats::default();
+        let query_text = query.to_string();
+        
+        let results = self.run_search(query, &query_text, &options, None, &mut stats)?;
+        self.observe_search(&query_text, started, &stats, results.len(), false);
+        Ok(results)
+    }
+    
+    /// Run a search, optionally abandoning it when `cancel` is set
+    fn search_with_cancel(
+        &self,
+        query: &str,
+        options: Option<SearchOptions>,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<Vec<SearchResult>> {
+        let started = Instant::now();
+        let options = self.clamp_limit(options);
+        let mut stats = SearchStats::default();
+        
+        // If we have a query cache, check it first
+        if let Some(cache) = &self.query_cache {
+            if let Some(results) = cache.get(query, options.as_ref()) {
+                // Cached results may include documents that have since expired
+                let now = SystemTime::now();
+                let index = self.index.read();
+                let results: Vec<SearchResult> = results.iter()
+                    .filter(|result| !index.is_expired_at(&result.document.id, now))
+                    .cloned()
+                    .collect();
+                self.observe_search(query, started, &stats, results.len(), true);
+                return Ok(results);
+            }
+        }
+        
+        let ast = self.index.read().parse_query(query);
+        let results = self.run_search(&ast, query, &options, cancel, &mut stats)?;
+        
+        // If we have a query cache, update it; partial results aren't worth reusing
+        if let Some(cache) = self.query_cache.as_ref().filter(|_| !stats.partial) {
+            cache.put(query, options.as_ref(), results.clone());
+        }
+        
+        self.observe_search(query, started, &stats, results.len(), false);
+        Ok(results)
+    }
+    
+    /// Evaluate a parsed query against the index, reporting it to the slow query
+    /// observer as `query_text` when it runs past the threshold
+    fn run_search(
+        &self,
+        query: &QueryAst,
+        query_text: &str,
+        options: &Option<SearchOptions>,
+        cancel: Option<Arc<AtomicBool>>,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<SearchResult>> {
+        // Perform the search, loading only the returned page of documents when
+        // they aren't held in memory
+        let search_started = Instant::now();
+        let internal_options = SearchOptionsInternal {
+            cancel,
+            ..SearchOptionsInternal::from(options.clone().unwrap_or_default())
+        };
+        let results = if self.index.read().retains_documents() {
+            self.index.read().search_with_stats(query, internal_options, stats)?
         } else {
-            Ok(None)
+            let ranked = self.index.read().search_ids_with_stats(query, &internal_options, stats)?;
+            let doc_ids: Vec<&str> = ranked.iter().map(|(doc_id, _)| doc_id.as_str()).collect();
+            let return_fields = options.as_ref().and_then(|options| options.return_fields.as_deref());
+            // Documents not held in memory are each their own group
+            let group_size = options.as_ref().and_then(|options| options.group_by.as_ref()).map(|_| 1);
+            
+            self.load_documents(&doc_ids)
+                .into_iter()
+                .zip(ranked)
+                .filter_map(|(document, (_, score))| {
+                    let document = match return_fields {
+                        Some(fields) => document?.project(fields),
+                        None => document?,
+                    };
+                    Some(SearchResult { document, score, group_size })
+                })
+                .collect()
+        };
+        
+        let search_elapsed = search_started.elapsed();
+        if self.config.slow_query_threshold.is_some_and(|threshold| search_elapsed > threshold) {
+            if let Some(observer) = &self.slow_query_observer.0 {
+                observer(&SlowQuery {
+                    query: query_text.to_string(),
+                    options: options.clone().unwrap_or_default(),
+                    elapsed: search_elapsed,
+                    result_count: results.len(),
+                });
+            }
         }
+        Ok(results)
     }
     
-    /// Flush all caches and storage
-    pub fn flush(&self) -> Result<()> {
-        // Flush storage
+    /// Clamp the options' limit to `max_result_limit`, if one is configured
+    fn clamp_limit(&self, options: Option<SearchOptions>) -> Option<SearchOptions> {
+        let Some(cap) = self.config.max_result_limit else {
+            return options;
+        };
+        let mut options = options.unwrap_or_default();
+        options.limit = options.limit.min(cap);
+        Some(options)
+    }
+    
+    /// Register a callback invoked at the end of each `search` with its timing and counts
+    ///
+    /// Replaces any previously registered observer. The callback runs on the
+    /// searching thread, so it should be cheap (such as updating a metric).
+    pub fn set_metrics_observer(&mut self, observer: MetricsObserver) {
+        self.metrics_observer = ObserverSlot(Some(observer));
+    }
+    
+    /// Remove the registered metrics observer
+    pub fn clear_metrics_observer(&mut self) {
+        self.metrics_observer = ObserverSlot(None);
+    }
+    
+    /// Register a callback invoked for searches slower than `slow_query_threshold`
+    ///
+    /// Only time spent searching the index and loading results counts; query
+    /// cache hits are never reported. Replaces any previously registered observer.
+    pub fn set_slow_query_observer(&mut self, observer: SlowQueryObserver) {
+        self.slow_query_observer = ObserverSlot(Some(observer));
+    }
+    
+    /// Report a finished search to the metrics observer, if one is registered
+    fn observe_search(&self, query: &str, started: Instant, stats: &SearchStats, result_count: usize, cache_hit: bool) {
+        if let Some(observer) = &self.metrics_observer.0 {
+            observer(&QueryMetrics {
+                query: query.to_string(),
+                duration: started.elapsed(),
+                candidate_tokens: stats.candidate_tokens,
+                result_count,
+                cache_hit,
+                partial: stats.partial,
+            });
+        }
+    }
+    
+    /// Search the index, returning `(document_id, score)` pairs without loading documents
+    ///
+    /// Cheaper than `search` when documents are fetched elsewhere, since nothing
+    /// is cloned or read from storage. Results bypass the query cache.
+    pub fn search_ids(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<(String, f64)>> {
+        self.index.read().search_ids(query, self.clamp_limit(options))
+    }
+    
+    /// Run several searches in parallel against the same index
+    ///
+    /// Each query goes through `search`, including the query cache. Result sets
+    /// are returned in the order of `queries`; the first failing query's error
+    /// is returned.
+    pub fn search_multi(&self, queries: &[(&str, Option<SearchOptions>)]) -> Result<Vec<Vec<SearchResult>>> {
+        queries
+            .par_iter()
+            .map(|(query, options)| self.search(query, options.clone()))
+            .collect()
+    }
+    
+    /// Count matching documents per distinct value of a field
+    ///
+    /// Runs the search with the given options (so `limit` bounds the documents
+    /// counted) and returns `(value, count)` pairs, most common first. Documents
+    /// without the field are skipped.
+    pub fn facet_counts(&self, query: &str, options: Option<SearchOptions>, field: &str) -> Result<Vec<(String, usize)>> {
+        // The faceted field must survive any projection
+        let options = options.map(|options| SearchOptions { return_fields: None, ..options });
+        let results = self.search(query, options)?;
+        
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for result in &results {
+            if let Some(value) = result.document.get_text_field(field) {
+                *counts.entry(value).or_insert(0) += 1;
+            }
+        }
+        
+        let mut facets: Vec<(String, usize)> = counts.into_iter().collect();
+        facets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(facets)
+    }
+    
+    /// Aggregate a numeric field over the documents matching a query
+    ///
+    /// Runs the search with the given options, like `facet_counts`, so `limit`
+    /// bounds the documents aggregated. Documents without a numeric value in the
+    /// field are skipped; with none left, `Sum` is 0 and the others are NaN.
+    pub fn aggregate(&self, query: &str, options: Option<SearchOptions>, field: &str, agg: AggKind) -> Result<f64> {
+        Ok(agg.apply(self.numeric_values(query, options, field)?))
+    }
+    
+    /// Count the documents matching a query per fixed-width range of a numeric field
+    ///
+    /// Values are bucketed by `floor(value / interval)`, so each bucket covers
+    /// `[start, start + interval)`, negative values included. Returns
+    /// `(bucket_start, count)` pairs for non-empty buckets in ascending order.
+    /// Like `aggregate`, `limit` bounds the documents counted and documents
+    /// without a numeric value in the field are skipped. Returns
+    /// `TigerCacheError::InvalidQuery` unless `interval` is positive and finite.
+    pub fn histogram(&self, query: &str, options: Option<SearchOptions>, field: &str, interval: f64) -> Result<Vec<(f64, usize)>> {
+        if !(interval.is_finite() && interval > 0.0) {
+            return Err(TigerCacheError::InvalidQuery(format!("histogram interval must be positive, got {interval}")));
+        }
+        
+        let mut buckets: BTreeMap<i64, usize> = BTreeMap::new();
+        for value in self.numeric_values(query, options, field)? {
+            *buckets.entry((value / interval).floor() as i64).or_insert(0) += 1;
+        }
+        
+        Ok(buckets
+            .into_iter()
+            .map(|(bucket, count)| (bucket as f64 * interval, count))
+            .collect())
+    }
+    
+    /// Get the finite numeric values of a field in the documents matching a query
+    fn numeric_values(&self, query: &str, options: Option<SearchOptions>, field: &str) -> Result<Vec<f64>> {
+        // The field must survive any projection
+        let options = options.map(|options| SearchOptions { return_fields: None, ..options });
+        let results = self.search(query, options)?;
+        
+        Ok(results
+            .iter()
+            .filter_map(|result| result.document.get_number_field(field))
+            .filter(|value| value.is_finite())
+            .collect())
+    }
+    
+    /// Suggest spelling corrections ("did you mean") for a query
+    ///
+    /// Returns up to `max` indexed tokens close to the query tokens that have no
+    /// exact match, best first; empty if the query matches exactly.
+    pub fn suggest(&self, query: &str, max: usize) -> Vec<String> {
+        let max = self.config.max_result_limit.map_or(max, |cap| max.min(cap));
+        self.index.read().suggest(query, max)
+    }
+    
+    /// Search the index and explain how each result's score was computed
+    ///
+    /// Intended for debugging relevance; results are neither read from nor
+    /// written to the query cache.
+    pub fn search_explain(&self, query: &str, options: Option<SearchOptions>) -> Result<Vec<(SearchResult, ScoreExplanation)>> {
+        self.index.read().search_explain(query, self.clamp_limit(options))
+    }
+    
+    /// Save the index to the file it was opened from
+    ///
+    /// Does nothing if the index hasn't changed since it was last committed,
+    /// saved, or loaded, returning zeroed stats. Otherwise returns the size of
+    /// the saved index, how long the commit took, and how many dirty storage
+    /// pages were flushed.
+    pub fn commit(&self) -> Result<CommitStats> {
+        if let Some(path) = &self.path {
+            // Nothing to write if the index hasn't changed since the last commit
+            if !self.dirty.load(Ordering::Acquire) {
+                return Ok(CommitStats::default());
+            }
+            
+            let started = Instant::now();
+            let mut stats = CommitStats::default();
+            
+            // If we have a storage engine, use it
+            if let Some(storage) = &self.storage {
+                stats.bytes_written = write_index(storage.as_ref(), &self.index.read())?;
+                
+                // Flush the storage, counting the pages it writes out
+                stats.dirty_pages_flushed = storage.stats().map_or(0, |storage_stats| storage_stats.dirty_page_count);
+                storage.flush()?;
+            } else {
+                // Fall back to legacy format
+                self.save_legacy(path)?;
+                stats.bytes_written = std::fs::metadata(path)?.len() as usize;
+            }
+            
+            self.dirty.store(false, Ordering::Release);
+            stats.duration = started.elapsed();
+            Ok(stats)
+        } else {
+            Err(TigerCacheError::IoError(
+                std::io::Error::other(
+                    "No file path specified. Use save_to_file instead.",
+                ),
+            ))
+        }
+    }
+    
+    /// Save a cache backed by in-memory storage to a snapshot file, index included
+    ///
+    /// In-memory storage loses its contents when the cache is dropped; reopen
+    /// the snapshot with `open_snapshot`. Fails for caches without storage and
+    /// for storage engines that persist on their own.
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let storage = self.storage.as_ref().ok_or_else(|| {
+            TigerCacheError::IoError(std::io::Error::other("No storage engine configured"))
+        })?;
+        
+        write_index(storage.as_ref(), &self.index.read())?;
+        std::fs::write(path, storage.snapshot()?)?;
+        
+        Ok(())
+    }
+    
+    /// Save the index to a specific file
+    pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path_buf = path.as_ref().to_path_buf();
+        
+        // Update the path
+        self.path = Some(path_buf.clone());
+        
+        // If we have a storage engine, use it
         if let Some(storage) = &self.storage {
+            // Save the index metadata
+            write_index(storage.as_ref(), &self.index.read())?;
+            
+            // Flush the storage
             storage.flush()?;
+        } else {
+            // Fall back to legacy format
+            self.save_legacy(&path_buf)?;
+        }
+        
+        self.dirty.store(false, Ordering::Release);
+        Ok(())
+    }
+    
+    /// Write the index to a single file in the configured persistence format
+    fn save_legacy(&self, path: &Path) -> Result<()> {
+        save_to_file_with(
+            &self.index.read(),
+            path,
+            self.config.persistence_format,
+            self.config.compress_index_file,
+            self.config.storage.encryption_key.as_ref(),
+        )
+    }
+    
+    /// Clear the index
+    pub fn clear(&mut self) {
+        self.index.write().clear();
+        self.dirty.store(true, Ordering::Release);
+        
+        // Clear caches
+        if let Some(cache) = &self.document_cache {
+            cache.clear();
+        }
+        
+        if let Some(cache) = &self.index_cache {
+            cache.clear();
+        }
+        
+        if let Some(cache) = &self.query_cache {
+            cache.clear();
+        }
+    }
+    
+    /// Drop all cached search results, keeping the index and the other caches
+    ///
+    /// Useful when results depend on data changed outside this cache, since
+    /// cached results are otherwise only dropped when the index changes.
+    pub fn clear_query_cache(&self) {
+        if let Some(cache) = &self.query_cache {
+            cache.clear();
+        }
+    }
+    
+    /// Drop all cached documents, keeping the index; documents are re-read from storage on next access
+    pub fn clear_document_cache(&self) {
+        if let Some(cache) = &self.document_cache {
+            cache.clear();
+        }
+    }
+    
+    /// Drop all cached trigram and inverted index entries, keeping the index itself
+    pub fn clear_index_cache(&self) {
+        if let Some(cache) = &self.index_cache {
+            cache.clear();
+        }
+    }
+    
+    /// Move the stored documents and committed index to a different storage engine
+    ///
+    /// Opens the engine described by `new_config`, copies every document, its
+    /// version, and the index saved by the last `commit` into it, and switches the cache over; the
+    /// previous engine is flushed and closed but keeps its data. The in-memory
+    /// index and the caches are left as they are, so commit first to carry over
+    /// uncommitted changes to the index. Fails if no storage engine is configured.
+    pub fn migrate_storage(&mut self, new_config: StorageConfig) -> Result<()> {
+        let Some(old_storage) = self.storage.clone() else {
+            return Err(TigerCacheError::IoError(std::io::Error::other("No storage engine to migrate from")));
+        };
+        let new_storage: Arc<dyn StorageEngine> = Arc::from(create_storage_engine(new_config.clone())?);
+        
+        for prefix in [&b"doc:"[..], b"ver:"] {
+            for (key, value) in old_storage.scan_prefix(prefix)? {
+                new_storage.put(&key, &value)?;
+            }
+        }
+        for key in [&b"index_metadata"[..], STORAGE_VERSION_KEY] {
+            if let Some(value) = old_storage.get(key)? {
+                new_storage.put(key, &value)?;
+            }
+        }
+        new_storage.flush()?;
+        
+        self.storage = Some(new_storage);
+        
+        // Background commits would otherwise keep going to the old engine
+        self.start_auto_commit();
+        if new_config.path.is_some() {
+            self.path = new_config.path.clone();
+        }
+        self.config.storage = new_config;
+        
+        old_storage.flush()?;
+        old_storage.close()?;
+        Ok(())
+    }
+    
+    /// Get memory statistics
+    pub fn memory_stats(&self) -> Option<crate::cache::MemoryStats> {
+        self.memory_manager.as_ref().map(|mm| mm.stats())
+    }
+    
+    /// Get the sizes and hit rates of the document, index, and query caches
+    ///
+    /// Returns `None` when no storage engine is configured, since the caches
+    /// only exist in front of one.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        match (&self.document_cache, &self.index_cache, &self.query_cache) {
+            (Some(document_cache), Some(index_cache), Some(query_cache)) => {
+                Some(CacheStats::collect(document_cache, index_cache, query_cache))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Get storage statistics
+    ///
+    /// Includes the on-disk size of the storage files for file-backed engines.
+    pub fn storage_stats(&self) -> Result<Option<crate::storage::StorageStats>> {
+        if let Some(storage) = &self.storage {
+            Ok(Some(storage.stats()?))
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Flush all caches and storage
+    pub fn flush(&self) -> Result<()> {
+        // Flush storage
+        if let Some(storage) = &self.storage {
+            storage.flush()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Reclaim storage space left behind by removed documents
+    ///
+    /// Useful after removing many documents. Does nothing without a storage engine.
+    pub fn compact(&self) -> Result<()> {
+        if let Some(storage) = &self.storage {
+            storage.compact()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Close the TigerCache instance
+    pub fn close(&self) -> Result<()> {
+        // Stop committing before the storage goes away
+        if let Some(committer) = &self.auto_committer {
+            committer.stop();
+        }
+        
+        // Flush and close storage
+        if let Some(storage) = &self.storage {
+            storage.flush()?;
+            storage.close()?;
+        }
+        
+        // Stop memory manager
+        if let Some(mm) = &self.memory_manager {
+            mm.stop();
+        }
+        
+        Ok(())
+    }
+    
+    /// Replace the background commit task, if auto-commit is enabled
+    pub(crate) fn restart_auto_commit(&mut self, commit: impl Fn() + Send + Sync + 'static) {
+        if self.auto_committer.is_some() {
+            self.auto_commit_task = Some(Arc::new(commit));
+            self.start_auto_commit();
+        }
+    }
+    
+    /// (Re)start the background commit task, if auto-commit is enabled
+    ///
+    /// Runs the task installed by `restart_auto_commit`, or else writes the
+    /// current index and flushes the current storage engine.
+    fn start_auto_commit(&self) {
+        let (Some(committer), Some(storage)) = (&self.auto_committer, &self.storage) else {
+            return;
+        };
+        
+        match &self.auto_commit_task {
+            Some(task) => {
+                let task = task.clone();
+                committer.start(move || task());
+            }
+            None => committer.start(index_commit_task(storage.clone(), self.index.clone(), self.dirty.clone())),
+        }
+    }
+    
+    /// Get the configuration
+    pub fn config(&self) -> &TigerCacheConfig {
+        &self.config
+    }
+    
+    /// Update the configuration
+    pub fn update_config(&mut self, config: TigerCacheConfig) -> Result<()> {
+        // Store the old path
+        let old_path = self.path.clone();
+        
+        // Close the current instance, releasing its storage engine so the new
+        // configuration can open the same path
+        self.close()?;
+        self.auto_committer = None;
+        self.storage = None;
+        
+        // Create a new instance with the new configuration
+        let mut new_instance = Self::with_config(config);
+        
+        // Restore the path
+        new_instance.path = old_path;
+        
+        // Carry over the index, and restart the background commits (including
+        // a replaced task) so they write it rather than the discarded one
+        new_instance.index = Arc::clone(&self.index);
+        new_instance.auto_commit_task = self.auto_commit_task.take();
+        new_instance.start_auto_commit();
+        
+        // Replace self with the new instance
+        *self = new_instance;
+        
+        Ok(())
+    }
+}
+
+impl Default for TigerCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for TigerCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Storage, caches, and background tasks have no useful representation
+        f.debug_struct("TigerCache")
+            .field("index", &self.index)
+            .field("path", &self.path)
+            .field("config", &self.config)
+            .field("storage", &self.storage.is_some())
+            .field("metrics_observer", &self.metrics_observer)
+            .field("slow_query_observer", &self.slow_query_observer)
+            .field("dirty", &self.dirty)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Guard returned by `TigerCache::batch` that defers cache invalidation and flushing
+///
+/// Dereferences to the underlying `TigerCache`. The batch ends when the guard
+/// is dropped; call `commit` instead to observe flush errors.
+pub struct BatchGuard<'a> {
+    cache: &'a mut TigerCache,
+}
+
+impl BatchGuard<'_> {
+    /// End the batch now, returning any error from flushing storage
+    pub fn commit(self) -> Result<()> {
+        let mut guard = std::mem::ManuallyDrop::new(self);
+        guard.cache.end_batch()
+    }
+}
+
+impl Deref for BatchGuard<'_> {
+    type Target = TigerCache;
+    
+    fn deref(&self) -> &TigerCache {
+        self.cache
+    }
+}
+
+impl DerefMut for BatchGuard<'_> {
+    fn deref_mut(&mut self) -> &mut TigerCache {
+        self.cache
+    }
+}
+
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.cache.end_batch();
+    }
+}
+
+/// Save the index as the storage engine's index metadata, returning the bytes written
+///
+/// The current storage format version is recorded alongside it.
+fn write_index(storage: &dyn StorageEngine, index: &Index) -> Result<usize> {
+    let index_data = bincode::encode_to_vec(index, bincode::config::standard())?;
+    storage.put(b"index_metadata", &index_data)?;
+    storage.put(STORAGE_VERSION_KEY, &STORAGE_FORMAT_VERSION.to_le_bytes())?;
+    Ok(index_data.len())
+}
+
+/// Background commit task of a cache that isn't shared
+///
+/// Writes the index when it changed since the last commit, then flushes the
+/// storage engine, logging failures.
+fn index_commit_task(storage: Arc<dyn StorageEngine>, index: Arc<RwLock<Index>>, dirty: Arc<AtomicBool>) -> impl Fn() + Send + 'static {
+    move || {
+        // Changes made while the index is written mark it dirty again
+        if dirty.swap(false, Ordering::AcqRel) {
+            if let Err(e) = write_index(storage.as_ref(), &index.read()) {
+                dirty.store(true, Ordering::Release);
+                log::warn!("Auto-commit failed to write the index: {e}");
+            }
+        }
+        if let Err(e) = storage.flush() {
+            log::warn!("Auto-commit failed to flush storage: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use crate::import::ImportErrorPolicy;
+    use crate::persistence::PersistenceFormat;
+    use crate::search::{Filter, SearchOptions};
+    use tempfile::tempdir;
+    
+    /// Poll `condition` until it holds, giving up after a few seconds
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            if Instant::now() > deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        true
+    }
+    
+    /// Reopen sled storage, waiting until sled's background threads release
+    /// the lock held by a cache that was just dropped
+    #[cfg(feature = "sled-storage")]
+    fn try_reopen(path: &Path, config: &TigerCacheConfig) -> Result<TigerCache> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let cache = TigerCache::open_with_config(path, config.clone())?;
+            if cache.storage.is_some() || Instant::now() > deadline {
+                return Ok(cache);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+    
+    /// Like `try_reopen`, for a reopen that must succeed
+    #[cfg(feature = "sled-storage")]
+    fn reopen(path: &Path, config: &TigerCacheConfig) -> TigerCache {
+        try_reopen(path, config).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let cache = TigerCache::new();
+        assert_eq!(cache.document_count(), 0);
+        assert!(cache.path.is_none());
+    }
+
+    #[test]
+    fn test_default() {
+        let cache: TigerCache = Default::default();
+        assert_eq!(cache.document_count(), 0);
+        assert!(cache.path.is_none());
+    }
+
+    #[test]
+    fn test_open_nonexistent() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("nonexistent.bin");
+        
+        let cache = TigerCache::open(&file_path).unwrap();
+        assert_eq!(cache.document_count(), 0);
+        assert_eq!(cache.path.as_ref().unwrap(), &file_path);
+    }
+
+    #[test]
+    fn test_set_indexed_fields() {
+        let mut cache = TigerCache::new();
+        let fields = vec!["title".to_string(), "description".to_string()];
+        
+        cache.set_indexed_fields(fields.clone());
+        
+        // We can't directly access the indexed_fields, but we can test the functionality
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Test Title")
+           .add_field("description", "Test Description")
+           .add_field("irrelevant", "This should not be indexed");
+        
+        cache.add_document(doc).unwrap();
+        
+        // Search for a term in the indexed fields
+        let results = cache.search("title", None).unwrap();
+        assert!(!results.is_empty());
+        
+        // Search for a term in the non-indexed field
+        let results = cache.search("irrelevant", None).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_configured_tokenizer() {
+        let config = TigerCacheConfig::new().with_tokenizer(TokenizerKind::Whitespace);
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Wi-Fi extender");
+        cache.add_document(doc).unwrap();
+        
+        let results = cache.search("wi-fi", None).unwrap();
+        assert_eq!(results[0].document.id, "test");
+    }
+
+    #[test]
+    fn test_custom_tokenizer() {
+        #[derive(Debug)]
+        struct CharTokenizer;
+        
+        impl crate::tokenizer::Tokenizer for CharTokenizer {
+            fn tokenize(&self, text: &str) -> Vec<String> {
+                text.chars().filter(|c| !c.is_whitespace()).map(|c| c.to_string()).collect()
+            }
+        }
+        
+        let config = TigerCacheConfig::new().with_custom_tokenizer(CharTokenizer);
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("test");
+        doc.add_field("title", "東京タワー");
+        cache.add_document(doc).unwrap();
+        
+        let results = cache.search("京", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "test");
+    }
+
+    #[test]
+    fn test_stopword_only_query() {
+        let config = TigerCacheConfig::new().with_english_stopwords();
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("test");
+        doc.add_field("title", "The Lord of the Rings");
+        cache.add_document(doc).unwrap();
+        
+        let results = cache.search("the of", None).unwrap();
+        assert!(results.is_empty());
+        
+        let results = cache.search("the rings", None).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_from_documents() {
+        let documents: Vec<Document> = [("doc1", "Apple iPhone"), ("doc2", "Apple Watch"), ("doc3", "Samsung Galaxy")]
+            .into_iter()
+            .map(|(id, title)| {
+                let mut doc = Document::new(id);
+                doc.add_field("title", title);
+                doc
+            })
+            .collect();
+        
+        let cache = TigerCache::from_documents(documents.clone()).unwrap();
+        assert_eq!(cache.document_count(), 3);
+        assert_eq!(cache.search("galaxy", None).unwrap()[0].document.id, "doc3");
+        
+        let config = TigerCacheConfig::new().with_min_token_length(6);
+        let cache = TigerCache::from_documents_with_config(documents, config).unwrap();
+        assert_eq!(cache.search("apple", None).unwrap().len(), 0);
+        assert_eq!(cache.search("iphone", None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sharded_batches() {
+        let documents: Vec<Document> = (0..5000)
+            .map(|i| {
+                let mut doc = Document::new(format!("doc{i}"));
+                doc.add_field("title", format!("Batch item{} group{}", i, i % 7));
+                doc
+            })
+            .collect();
+        
+        let sequential = TigerCache::from_documents(documents.clone()).unwrap();
+        let config = TigerCacheConfig::new().with_sharded_batches(true);
+        let sharded = TigerCache::from_documents_with_config(documents, config).unwrap();
+        
+        assert_eq!(sharded.document_count(), sequential.document_count());
+        let options = SearchOptions { limit: 1000, ..Default::default() };
+        for query in ["group3", "item4321", "batch"] {
+            let ids = |cache: &TigerCache| cache.search(query, Some(options.clone())).unwrap()
+                .into_iter()
+                .map(|r| r.document.id)
+                .collect::<Vec<_>>();
+            assert_eq!(ids(&sharded), ids(&sequential), "query {query}");
+        }
+    }
+
+    #[test]
+    fn test_add_documents_from_iter() {
+        let mut cache = TigerCache::new();
+        let documents = (0..2000).map(|i| {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("Streamed item{i}"));
+            doc
+        });
+        
+        assert_eq!(cache.add_documents_from_iter(documents, 64).unwrap(), 2000);
+        assert_eq!(cache.document_count(), 2000);
+        assert_eq!(cache.search("item1999", None).unwrap()[0].document.id, "doc1999");
+        
+        assert_eq!(cache.add_documents_from_iter(std::iter::empty(), 64).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_add_documents_with_progress() {
+        let make_documents = |count: usize| (0..count).map(|i| {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("Progress item{i}"));
+            doc
+        });
+        
+        let mut cache = TigerCache::new();
+        let mut reports = Vec::new();
+        let added = cache.add_documents_from_iter_with_progress(make_documents(1000), 64, |added, total| {
+            reports.push((added, total));
+        }).unwrap();
+        assert_eq!(added, 1000);
+        assert_eq!(reports.len(), 16);
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(reports.last(), Some(&(1000, Some(1000))));
+        
+        // Iterators without an exact length report no total
+        let mut reports = Vec::new();
+        cache.add_documents_from_iter_with_progress(make_documents(100).filter(|_| true), 30, |added, total| {
+            reports.push((added, total));
+        }).unwrap();
+        assert_eq!(reports, vec![(30, None), (60, None), (90, None), (100, None)]);
+        
+        let mut cache = TigerCache::new();
+        let mut reports = Vec::new();
+        let count = PROGRESS_BATCH_SIZE + 500;
+        cache.add_documents_batch_with_progress(make_documents(count).collect(), |added, total| {
+            reports.push((added, total));
+        }).unwrap();
+        assert_eq!(reports, vec![(PROGRESS_BATCH_SIZE, count), (count, count)]);
+        assert_eq!(cache.document_count(), count);
+    }
+
+    #[test]
+    fn test_schema_validation() {
+        use crate::schema::{FieldType, Schema};
+        
+        let schema = Schema::new()
+            .with_required_field("title", FieldType::String)
+            .with_field("price", FieldType::Number);
+        let mut cache = TigerCache::with_config(TigerCacheConfig::new().with_schema(schema));
+        
+        let mut missing_title = Document::new("doc1");
+        missing_title.add_field("price", 999.0);
+        assert!(matches!(cache.add_document(missing_title), Err(TigerCacheError::InvalidDocument(_))));
+        
+        let mut bad_price = Document::new("doc2");
+        bad_price.add_field("title", "Apple iPhone").add_field("price", "expensive");
+        assert!(matches!(cache.add_document(bad_price.clone()), Err(TigerCacheError::InvalidDocument(_))));
+        
+        // Rejected documents never reach the index
+        assert_eq!(cache.document_count(), 0);
+        assert!(cache.search("iphone", None).unwrap().is_empty());
+        
+        // A batch with one invalid document is rejected as a whole
+        let mut valid = Document::new("doc3");
+        valid.add_field("title", "Apple Watch").add_field("price", 399.0);
+        assert!(cache.add_documents_batch(vec![valid.clone(), bad_price]).is_err());
+        assert_eq!(cache.document_count(), 0);
+        
+        cache.add_document(valid).unwrap();
+        assert_eq!(cache.document_count(), 1);
+        
+        // Merged fields are validated before the document is replaced
+        let fields = HashMap::from([("price".to_string(), serde_json::json!("free"))]);
+        assert!(cache.merge_document("doc3", fields).is_err());
+        assert_eq!(cache.get_document("doc3").unwrap().fields["price"], serde_json::json!(399.0));
+    }
+
+    #[test]
+    fn test_update_document_if() {
+        let mut cache = TigerCache::new();
+        let document = |title: &str| Document::new("doc1").with_field("title", title);
+        
+        // Version 0 creates the document only if it doesn't exist
+        assert_eq!(cache.update_document_if(document("Apple iPhone"), 0).unwrap(), 1);
+        assert!(cache.update_document_if(document("Apple iPad"), 0).is_err());
+        
+        // Two writers read the same version; the second one's update is stale
+        let seen_by_first = cache.document_version("doc1").unwrap();
+        let seen_by_second = cache.document_version("doc1").unwrap();
+        assert_eq!(cache.update_document_if(document("Apple Watch"), seen_by_first).unwrap(), 2);
+        
+        let err = cache.update_document_if(document("Apple TV"), seen_by_second).unwrap_err();
+        assert!(matches!(err, TigerCacheError::VersionConflict { ref doc_id, expected: 1, found: 2 } if doc_id == "doc1"));
+        assert_eq!(cache.get_document("doc1").unwrap().get_text_field("title"), Some("Apple Watch".to_string()));
+        assert!(cache.search("tv", None).unwrap().is_empty());
+        
+        // Any replacement advances the version
+        cache.merge_document("doc1", HashMap::from([("color".to_string(), serde_json::json!("black"))])).unwrap();
+        assert_eq!(cache.document_version("doc1"), Some(3));
+        assert_eq!(cache.update_document_if(document("Apple TV"), 3).unwrap(), 4);
+        
+        cache.remove_document("doc1").unwrap();
+        assert_eq!(cache.document_version("doc1"), None);
+        assert!(cache.update_document_if(document("Apple TV"), 4).is_err());
+    }
+
+    #[test]
+    fn test_max_result_limit() {
+        let config = TigerCacheConfig::new().with_max_result_limit(5);
+        let mut cache = TigerCache::with_config(config);
+        for i in 0..20 {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("Apple product {i}"));
+            cache.add_document(doc).unwrap();
+        }
+        
+        let options = SearchOptions { limit: usize::MAX, ..Default::default() };
+        assert_eq!(cache.search("apple", Some(options.clone())).unwrap().len(), 5);
+        assert_eq!(cache.search_ids("apple", Some(options.clone())).unwrap().len(), 5);
+        assert_eq!(cache.search_explain("apple", Some(options)).unwrap().len(), 5);
+        assert!(cache.search("apple", None).unwrap().len() <= 5);
+        
+        // Limits below the cap are kept
+        let options = SearchOptions { limit: 3, ..Default::default() };
+        assert_eq!(cache.search("apple", Some(options)).unwrap().len(), 3);
+        
+        // Without a cap the requested limit applies
+        let mut uncapped = TigerCache::new();
+        for i in 0..20 {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("Apple product {i}"));
+            uncapped.add_document(doc).unwrap();
+        }
+        let options = SearchOptions { limit: usize::MAX, ..Default::default() };
+        assert_eq!(uncapped.search("apple", Some(options)).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_min_token_length() {
+        let config = TigerCacheConfig::new().with_min_token_length(3);
+        let mut cache = TigerCache::with_config(config);
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "LG TV stand");
+        cache.add_document(doc).unwrap();
+        
+        assert!(cache.index.read().get_documents_for_token("tv").is_empty());
+        assert!(cache.index.read().get_documents_for_token("lg").is_empty());
+        assert!(cache.search("tv", None).unwrap().is_empty());
+        
+        // Longer terms in the same query still match
+        assert_eq!(cache.search("tv stand", None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_numeric_token_search() {
+        let config = TigerCacheConfig::new().with_numeric_tokens(true);
+        let mut cache = TigerCache::with_config(config);
+        for (id, title, sku) in [
+            ("doc1", "Cordless Drill", "SKU-4471"),
+            ("doc2", "Cordless Drill Battery", "SKU-4470"),
+            ("doc3", "Impact Driver", "SKU-1447"),
+        ] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title).add_field("sku", sku);
+            cache.add_document(doc).unwrap();
+        }
+        
+        let ids = |query: &str| -> Vec<String> {
+            cache.search(query, None).unwrap().into_iter().map(|result| result.document.id).collect()
+        };
+        
+        // The embedded number matches exactly, without near misses
+        assert_eq!(ids("4471"), vec!["doc1"]);
+        assert_eq!(ids("1447"), vec!["doc3"]);
+        assert!(ids("4472").is_empty());
+        assert_eq!(ids("drill 4470")[0], "doc2");
+        
+        // Text tokens are still fuzzy
+        assert_eq!(ids("driil").len(), 2);
+        
+        // Without the setting the number isn't a token of its own
+        let mut plain = TigerCache::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("sku", "SKU-4471");
+        plain.add_document(doc).unwrap();
+        assert!(plain.search("4471", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_keyword_fields() {
+        let config = TigerCacheConfig::new().with_keyword_fields(vec!["status".to_string()]);
+        let mut cache = TigerCache::with_config(config);
+        for (id, status) in [("order1", "in progress"), ("order2", "active"), ("order3", "progress report")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Order").add_field("status", status);
+            cache.add_document(doc).unwrap();
+        }
+        let mut tagged = Document::new("order4");
+        tagged.add_field("title", "Order").add_field("status", vec!["active", "in progress"]);
+        cache.add_document(tagged).unwrap();
+        
+        let ids = |cache: &TigerCache, query: &str| -> Vec<String> {
+            let mut ids: Vec<String> = cache.search_ids(query, None).unwrap().into_iter().map(|(id, _)| id).collect();
+            ids.sort();
+            ids
+        };
+        
+        // A value with spaces only matches as a whole
+        assert_eq!(ids(&cache, "status:\"in progress\""), vec!["order1", "order4"]);
+        assert!(ids(&cache, "status:progress").is_empty());
+        assert!(ids(&cache, "status:\"in\"").is_empty());
+        
+        // Keyword values are exact and left out of full-text search
+        assert_eq!(ids(&cache, "status:active"), vec!["order2", "order4"]);
+        assert!(ids(&cache, "status:activ").is_empty());
+        assert!(ids(&cache, "status:Active").is_empty());
+        assert!(ids(&cache, "progress").is_empty());
+        
+        // Keyword filters narrow other searches
+        let options = SearchOptions {
+            filters: vec![Filter::keyword("status", "in progress")],
+            ..Default::default()
+        };
+        let results = cache.search("order", Some(options)).unwrap();
+        let mut filtered: Vec<&str> = results.iter().map(|result| result.document.id.as_str()).collect();
+        filtered.sort();
+        assert_eq!(filtered, vec!["order1", "order4"]);
+        
+        // Removing a document drops its keyword values
+        cache.remove_document("order1").unwrap();
+        assert_eq!(ids(&cache, "status:\"in progress\""), vec!["order4"]);
+    }
+
+    #[test]
+    fn test_range_filter() {
+        let mut plain = TigerCache::new();
+        let mut indexed = TigerCache::with_config(TigerCacheConfig::new().with_numeric_fields(vec!["price".to_string()]));
+        for (id, price) in [("p1", 99.0), ("p2", 249.0), ("p3", 499.0), ("p4", 999.0)] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Phone").add_field("price", price);
+            plain.add_document(doc.clone()).unwrap();
+            indexed.add_document(doc).unwrap();
+        }
+        let mut unpriced = Document::new("p5");
+        unpriced.add_field("title", "Phone");
+        indexed.add_document(unpriced).unwrap();
+        
+        // Results are the same with and without the numeric index
+        for cache in [&plain, &indexed] {
+            let ids = |filter: Filter| -> Vec<String> {
+                let options = SearchOptions { filters: vec![filter], ..Default::default() };
+                let mut ids: Vec<String> = cache.search_ids("phone", Some(options)).unwrap().into_iter().map(|(id, _)| id).collect();
+                ids.sort();
+                ids
+            };
+            assert_eq!(ids(Filter::range("price", Some(200.0), Some(499.0))), vec!["p2", "p3"]);
+            assert_eq!(ids(Filter::range("price", None, Some(249.0))), vec!["p1", "p2"]);
+            assert_eq!(ids(Filter::range("price", Some(1000.0), None)), Vec::<String>::new());
+        }
+        
+        // Numeric fields configured when reopening are indexed for the loaded documents
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("index.bin");
+        plain.save_to_file(&path).unwrap();
+        let reopened = TigerCache::open_with_config(&path, TigerCacheConfig::new().with_numeric_fields(vec!["price".to_string()])).unwrap();
+        let index = reopened.index.read();
+        let documents = index.numeric_range_documents("price", Some(200.0), Some(499.0)).unwrap();
+        assert!(index.document_in("p2", &documents) && index.document_in("p3", &documents));
+        assert!(!index.document_in("p4", &documents));
+    }
+
+    #[test]
+    fn test_phonetic_search() {
+        let config = TigerCacheConfig::new().with_phonetic_index(true);
+        let mut cache = TigerCache::with_config(config);
+        let people = [("doc1", "Rupert Smith"), ("doc2", "Catherine Ashcraft"), ("doc3", "Aaron Pfister"), ("doc4", "Robert Jones")];
+        for (id, name) in people {
+            let mut doc = Document::new(id);
+            doc.add_field("name", name);
+            cache.add_document(doc).unwrap();
+        }
+        
+        let ids = |query: &str, phonetic: bool| -> Vec<String> {
+            let options = SearchOptions { max_distance: 0, phonetic, ..Default::default() };
+            cache.search(query, Some(options)).unwrap().into_iter().map(|result| result.document.id).collect()
+        };
+        
+        assert!(ids("smyth", false).is_empty());
+        assert_eq!(ids("smyth", true), vec!["doc1"]);
+        assert_eq!(ids("ashcroft", true), vec!["doc2"]);
+        assert_eq!(ids("arran fister", true), vec!["doc3"]);
+        
+        // Exact matches still rank above phonetic ones
+        assert_eq!(ids("robert", false), vec!["doc4"]);
+        assert_eq!(ids("robert", true), vec!["doc4", "doc1"]);
+    }
+
+    #[test]
+    fn test_set_stemming() {
+        let mut cache = TigerCache::new();
+        
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Walked the dogs");
+        cache.add_document(doc).unwrap();
+        
+        let options = SearchOptions { max_distance: 0, ..Default::default() };
+        assert!(cache.search("walking", Some(options.clone())).unwrap().is_empty());
+        
+        cache.set_stemming(true).unwrap();
+        assert!(cache.config().stemming);
+        let results = cache.search("walking", Some(options.clone())).unwrap();
+        assert_eq!(results.len(), 1);
+        let results = cache.search("dog", Some(options)).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_synonyms_from_config() {
+        let mut synonyms = HashMap::new();
+        synonyms.insert("phone".to_string(), vec!["smartphone".to_string()]);
+        synonyms.insert("smartphone".to_string(), vec!["phone".to_string()]);
+        let mut cache = TigerCache::with_config(TigerCacheConfig::new().with_synonyms(synonyms));
+        
+        let mut doc1 = Document::new("doc1");
+        doc1.add_field("title", "Smartphone");
+        cache.add_document(doc1).unwrap();
+        let mut doc2 = Document::new("doc2");
+        doc2.add_field("title", "Phone");
+        cache.add_document(doc2).unwrap();
+        
+        let results = cache.search("PHONE", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].document.id, "doc2");
+        
+        let results = cache.search("smartphone", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].document.id, "doc1");
+    }
+
+    #[test]
+    fn test_fold_diacritics() {
+        let mut cache = TigerCache::with_config(TigerCacheConfig::new().with_fold_diacritics(true));
+        
+        let mut doc1 = Document::new("doc1");
+        doc1.add_field("title", "Café Crème");
+        cache.add_document(doc1).unwrap();
+        let mut doc2 = Document::new("doc2");
+        doc2.add_field("title", "Cafe Creme");
+        cache.add_document(doc2).unwrap();
+        
+        // Accented and plain forms find both documents as exact matches
+        for query in ["cafe creme", "CAFÉ CRÈME", "càfé"] {
+            let results = cache.search(query, None).unwrap();
+            assert_eq!(results.len(), 2, "query {query}");
+            assert_eq!(results[0].score, results[1].score);
+        }
+        
+        // Without folding the accented form is only a fuzzy match
+        let mut cache = TigerCache::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Café");
+        cache.add_document(doc).unwrap();
+        let options = SearchOptions { max_distance: 0, ..Default::default() };
+        assert!(cache.search("cafe", Some(options)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_preserve_punctuation() {
+        let build = |config: TigerCacheConfig| {
+            let mut cache = TigerCache::with_config(config);
+            for (id, title) in [("cpp", "Learning C++"), ("csharp", "Learning C#"), ("c", "Learning C"), ("node", "Learning Node.js")] {
+                let mut doc = Document::new(id);
+                doc.add_field("title", title);
+                cache.add_document(doc).unwrap();
+            }
+            cache
+        };
+        let exact = SearchOptions { max_distance: 0, ..Default::default() };
+        let ids = |cache: &TigerCache, query: &str| -> Vec<String> {
+            let mut ids: Vec<String> = cache.search(query, Some(exact.clone())).unwrap().into_iter().map(|result| result.document.id).collect();
+            ids.sort();
+            ids
+        };
+        
+        let preserving = build(TigerCacheConfig::new().with_preserve_punctuation("+#."));
+        assert_eq!(ids(&preserving, "c++"), vec!["cpp".to_string()]);
+        assert_eq!(ids(&preserving, "C#"), vec!["csharp".to_string()]);
+        assert_eq!(ids(&preserving, "c"), vec!["c".to_string()]);
+        assert_eq!(ids(&preserving, "node.js"), vec!["node".to_string()]);
+        
+        // By default the languages all collapse to "c"
+        let stripping = build(TigerCacheConfig::new());
+        assert_eq!(ids(&stripping, "c++"), vec!["c".to_string(), "cpp".to_string(), "csharp".to_string()]);
+    }
+
+    #[test]
+    fn test_case_sensitive_search() {
+        let build = |config: TigerCacheConfig| {
+            let mut cache = TigerCache::with_config(config);
+            let mut doc1 = Document::new("company");
+            doc1.add_field("title", "Apple Inc");
+            cache.add_document(doc1).unwrap();
+            let mut doc2 = Document::new("fruit");
+            doc2.add_field("title", "apple orchard");
+            cache.add_document(doc2).unwrap();
+            cache
+        };
+        let exact = SearchOptions { max_distance: 0, ..Default::default() };
+        
+        let insensitive = build(TigerCacheConfig::new());
+        let results = insensitive.search("Apple", Some(exact.clone())).unwrap();
+        assert_eq!(results.len(), 2);
+        
+        let sensitive = build(TigerCacheConfig::new().with_case_sensitive(true));
+        let results = sensitive.search("Apple", Some(exact.clone())).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "company");
+        let results = sensitive.search("apple", Some(exact)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "fruit");
+    }
+
+    #[test]
+    fn test_search_explain() {
+        let mut cache = TigerCache::new();
+        
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Test Document");
+        cache.add_document(doc).unwrap();
+        
+        let explained = cache.search_explain("tset document", None).unwrap();
+        assert_eq!(explained.len(), 1);
+        
+        let (result, explanation) = &explained[0];
+        assert_eq!(result.score, cache.search("tset document", None).unwrap()[0].score);
+        assert_eq!(explanation.matches.len(), 2);
+        assert_eq!(explanation.matches[0].token, "document");
+        assert_eq!(explanation.matches[1].token, "test");
+        assert_eq!(explanation.matches[1].distance, 2);
+    }
+
+    #[test]
+    fn test_try_get_document() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"))
+            .with_lazy_documents(true);
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        
+        assert_eq!(cache.try_get_document("doc1").unwrap().unwrap().id, "doc1");
+        assert!(cache.try_get_document("missing").unwrap().is_none());
+        
+        // A corrupted blob is an error rather than a missing document
+        cache.storage.as_ref().unwrap().put(b"doc:doc1", b"not a document").unwrap();
+        cache.clear_document_cache();
+        assert!(cache.try_get_document("doc1").is_err());
+        assert!(cache.get_document("doc1").is_none());
+    }
+
+    #[test]
+    fn test_get_document_arc() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"))
+            .with_lazy_documents(true);
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc.clone()).unwrap();
+        
+        // Documents are shared with the document cache rather than copied
+        let first = cache.get_document_arc("doc1").unwrap();
+        let cached = cache.document_cache.as_ref().unwrap().get("doc1").unwrap();
+        assert!(Arc::ptr_eq(&first, &cached));
+        assert!(Arc::ptr_eq(&first, &cache.get_document_arc("doc1").unwrap()));
+        assert_eq!(*first, doc);
+        
+        // Evicted documents are loaded from storage and cached again
+        cache.clear_document_cache();
+        let reloaded = cache.get_document_arc("doc1").unwrap();
+        assert_eq!(*reloaded, doc);
+        assert!(Arc::ptr_eq(&reloaded, &cache.get_document_arc("doc1").unwrap()));
+        
+        assert!(cache.get_document_arc("missing").is_none());
+        
+        // Without a document cache, documents come from the in-memory index
+        let mut memory_only = TigerCache::new();
+        memory_only.add_document(doc.clone()).unwrap();
+        assert_eq!(*memory_only.get_document_arc("doc1").unwrap(), doc);
+    }
+
+    #[test]
+    fn test_contains_document() {
+        let mut memory_only = TigerCache::new();
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        memory_only.add_document(doc.clone()).unwrap();
+        assert!(memory_only.contains_document("doc1"));
+        assert!(!memory_only.contains_document("doc2"));
+        
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"))
+            .with_lazy_documents(true);
+        let mut stored = TigerCache::with_config(config);
+        stored.add_document(doc).unwrap();
+        stored.clear_document_cache();
+        assert!(stored.contains_document("doc1"));
+        assert!(!stored.contains_document("doc2"));
+        
+        // Documents in storage are found without a stored blob being decoded
+        stored.storage.as_ref().unwrap().put(b"doc:doc3", b"not a document").unwrap();
+        assert!(stored.contains_document("doc3"));
+        
+        stored.remove_document("doc1").unwrap();
+        assert!(!stored.contains_document("doc1"));
+    }
+
+    #[test]
+    fn test_lazy_documents() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("lazy_db"))
+            .with_lazy_documents(true);
+        let mut cache = TigerCache::with_config(config);
+        assert!(cache.storage.is_some());
+        
+        for i in 0..20 {
+            let mut doc = Document::new(format!("doc{}", i));
+            doc.add_field("title", format!("Lazy document {}", i));
+            cache.add_document(doc).unwrap();
+        }
+        
+        // The index holds no documents, but search still returns them in full
+        assert_eq!(cache.document_count(), 20);
+        assert!(cache.index.read().get_document("doc3").is_none());
+        
+        let options = SearchOptions { limit: 5, ..Default::default() };
+        let results = cache.search("lazy", Some(options)).unwrap();
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|r| r.document.get_text_field("title").unwrap().starts_with("Lazy document")));
+        
+        // Reindexing loads the documents from storage
+        cache.set_stemming(true).unwrap();
+        assert_eq!(cache.search("documents", None).unwrap().len(), 20);
+        assert!(cache.index.read().get_document("doc3").is_none());
+    }
+
+    #[test]
+    fn test_suggest() {
+        let mut cache = TigerCache::new();
+        for (id, title) in [("1", "Chocolate cake"), ("2", "Chocolate chip cookies"), ("3", "Carrot cake")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        
+        let suggestions = cache.suggest("chocolat", 3);
+        assert_eq!(suggestions.first().map(String::as_str), Some("chocolate"));
+        
+        let suggestions = cache.suggest("carot", 3);
+        assert_eq!(suggestions.first().map(String::as_str), Some("carrot"));
+        
+        assert!(cache.suggest("chocolate cake", 3).is_empty());
+    }
+
+    #[test]
+    fn test_facet_counts() {
+        let mut cache = TigerCache::new();
+        let products = [
+            ("1", "Phone case", Some("Accessories")),
+            ("2", "Phone charger", Some("Accessories")),
+            ("3", "Budget phone", Some("Smartphone")),
+            ("4", "Flagship phone", Some("Smartphone")),
+            ("5", "Phone stand", Some("Accessories")),
+            ("6", "Phone sticker", None),
+            ("7", "Laptop", Some("Laptop")),
+        ];
+        for (id, title, category) in products {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            if let Some(category) = category {
+                doc.add_field("category", category);
+            }
+            cache.add_document(doc).unwrap();
+        }
+        
+        let facets = cache.facet_counts("phone", None, "category").unwrap();
+        assert_eq!(facets, vec![
+            ("Accessories".to_string(), 3),
+            ("Smartphone".to_string(), 2),
+        ]);
+        
+        assert!(cache.facet_counts("phone", None, "brand").unwrap().is_empty());
+        assert!(cache.facet_counts("nothing", None, "category").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let mut cache = TigerCache::new();
+        let products = [
+            ("1", "Phone case", Some(serde_json::json!(15.0))),
+            ("2", "Budget phone", Some(serde_json::json!(199))),
+            ("3", "Flagship phone", Some(serde_json::json!(1099.5))),
+            ("4", "Phone sticker", None),
+            ("5", "Phone stand", Some(serde_json::json!("free"))),
+            ("6", "Laptop", Some(serde_json::json!(1500))),
+        ];
+        for (id, title, price) in products {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            if let Some(price) = price {
+                doc.add_field("price", price);
+            }
+            cache.add_document(doc).unwrap();
+        }
+        
+        let aggregate = |agg| cache.aggregate("phone", None, "price", agg).unwrap();
+        assert_eq!(aggregate(AggKind::Min), 15.0);
+        assert_eq!(aggregate(AggKind::Max), 1099.5);
+        assert_eq!(aggregate(AggKind::Sum), 1313.5);
+        assert!((aggregate(AggKind::Avg) - 1313.5 / 3.0).abs() < 1e-9);
+        
+        // Only the documents within the limit are aggregated
+        let options = SearchOptions { limit: 1, ..Default::default() };
+        let top = cache.search("phone", Some(options.clone())).unwrap()[0].document.get_number_field("price");
+        assert_eq!(cache.aggregate("phone", Some(options), "price", AggKind::Sum).unwrap(), top.unwrap_or(0.0));
+        
+        assert_eq!(cache.aggregate("nothing", None, "price", AggKind::Sum).unwrap(), 0.0);
+        assert!(cache.aggregate("nothing", None, "price", AggKind::Min).unwrap().is_nan());
+        assert!(cache.aggregate("phone", None, "weight", AggKind::Avg).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut cache = TigerCache::new();
+        let prices = [-15.0, -0.5, 0.0, 9.99, 10.0, 24.0, 25.0, 49.5];
+        for (i, price) in prices.into_iter().enumerate() {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", "widget").add_field("price", price);
+            cache.add_document(doc).unwrap();
+        }
+        let mut doc = Document::new("unpriced");
+        doc.add_field("title", "widget");
+        cache.add_document(doc).unwrap();
+        
+        let histogram = cache.histogram("widget", None, "price", 10.0).unwrap();
+        assert_eq!(histogram, vec![(-20.0, 1), (-10.0, 1), (0.0, 2), (10.0, 1), (20.0, 2), (40.0, 1)]);
+        
+        let histogram = cache.histogram("widget", None, "price", 25.0).unwrap();
+        assert_eq!(histogram, vec![(-25.0, 2), (0.0, 4), (25.0, 2)]);
+        
+        assert!(cache.histogram("nothing", None, "price", 10.0).unwrap().is_empty());
+        for interval in [0.0, -10.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(cache.histogram("widget", None, "price", interval), Err(TigerCacheError::InvalidQuery(_))));
+        }
+    }
+
+    #[test]
+    fn test_open_corrupted_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test_index.bin");
+        
+        let mut cache = TigerCache::new();
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Test Document");
+        cache.add_document(doc).unwrap();
+        cache.save_to_file(&file_path).unwrap();
+        
+        let mut data = std::fs::read(&file_path).unwrap();
+        let last = data.len() - 3;
+        data[last] ^= 0x20;
+        std::fs::write(&file_path, &data).unwrap();
+        
+        let result = TigerCache::open(&file_path);
+        assert!(matches!(result, Err(TigerCacheError::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn test_bincode_persistence() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test_index.bin");
+        
+        let config = TigerCacheConfig::new()
+            .with_persistence_format(PersistenceFormat::Bincode);
+        let mut cache = TigerCache::with_config(config);
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Test Document");
+        cache.add_document(doc).unwrap();
+        cache.save_to_file(&file_path).unwrap();
+        
+        let loaded = TigerCache::open(&file_path).unwrap();
+        assert_eq!(loaded.search("document", None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_memory_snapshot_round_trip() {
+        let dir = tempdir().unwrap();
+        let snapshot_path = dir.path().join("cache.snapshot");
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("memory_db"))
+            .with_lazy_documents(true);
+        
+        let mut cache = TigerCache::with_config(config.clone());
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch"), ("test3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        cache.save_snapshot(&snapshot_path).unwrap();
+        drop(cache);
+        
+        let restored = TigerCache::open_snapshot(&snapshot_path, config).unwrap();
+        assert_eq!(restored.document_count(), 3);
+        let results = restored.search("aple", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].document.get_text_field("title").map(|t| t.starts_with("Apple")), Some(true));
+        assert_eq!(restored.stored_document_ids().unwrap().len(), 3);
+        
+        // Without storage there is nothing to snapshot
+        assert!(TigerCache::new().save_snapshot(dir.path().join("none.snapshot")).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "sled-storage")]
+    fn test_migrate_storage() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("memory_db"))
+            .with_lazy_documents(true);
+        let mut cache = TigerCache::with_config(config);
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch"), ("test3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        cache.commit().unwrap();
+        
+        let sled_path = dir.path().join("sled_db");
+        let mut sled_config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Sled)
+            .with_storage_path(&sled_path)
+            .with_lazy_documents(true);
+        sled_config.storage.use_compression = false;
+        cache.migrate_storage(sled_config.storage.clone()).unwrap();
+        
+        // The in-memory index is unchanged and documents load from the new engine
+        assert_eq!(cache.document_count(), 3);
+        assert_eq!(cache.search("aple", None).unwrap().len(), 2);
+        assert_eq!(cache.stored_document_ids().unwrap().len(), 3);
+        cache.close().unwrap();
+        drop(cache);
+        
+        let reopened = reopen(&sled_path, &sled_config);
+        assert_eq!(reopened.document_count(), 3);
+        let document = reopened.try_get_document("test3").unwrap().unwrap();
+        assert_eq!(document.get_text_field("title"), Some("Samsung Galaxy".to_string()));
+        assert_eq!(reopened.search("galaxy", None).unwrap()[0].document.id, "test3");
+        
+        // Without storage there is nothing to migrate
+        assert!(TigerCache::new().migrate_storage(StorageConfig::default()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "sled-storage")]
+    fn test_auto_commit_after_migrate_storage() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("memory_db"))
+            .with_auto_commit_interval(Duration::from_millis(20));
+        let mut cache = TigerCache::with_config(config);
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        cache.commit().unwrap();
+        
+        let sled_path = dir.path().join("sled_db");
+        let mut sled_config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Sled)
+            .with_storage_path(&sled_path);
+        sled_config.storage.use_compression = false;
+        cache.migrate_storage(sled_config.storage.clone()).unwrap();
+        
+        // The background task keeps writing the index, now to the new engine
+        let mut doc = Document::new("test2");
+        doc.add_field("title", "Apple Watch");
+        cache.add_document(doc).unwrap();
+        assert!(wait_until(|| !cache.dirty.load(Ordering::Acquire)));
+        cache.close().unwrap();
+        drop(cache);
+        
+        let reopened = reopen(&sled_path, &sled_config);
+        assert_eq!(reopened.document_count(), 2);
+        assert_eq!(reopened.search("watch", None).unwrap()[0].document.id, "test2");
+    }
+
+    #[test]
+    #[cfg(feature = "sled-storage")]
+    fn test_rebuild_from_storage() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sled_db");
+        let mut config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Sled)
+            .with_storage_path(&path);
+        config.storage.use_compression = false;
+        
+        let mut cache = TigerCache::open_with_config(&path, config.clone()).unwrap();
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch"), ("test3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        cache.merge_document("test1", HashMap::from([("color".to_string(), serde_json::json!("black"))])).unwrap();
+        cache.commit().unwrap();
+        
+        // A corrupted index is rebuilt from the stored documents on open
+        cache.storage.as_ref().unwrap().put(b"index_metadata", b"not an index").unwrap();
+        cache.close().unwrap();
+        drop(cache);
+        
+        let reopened = reopen(&path, &config);
+        assert_eq!(reopened.document_count(), 3);
+        assert_eq!(reopened.search("aple", None).unwrap().len(), 2);
+        assert_eq!(reopened.search("galaxy", None).unwrap()[0].document.id, "test3");
+        assert_eq!(reopened.document_version("test1"), Some(2));
+        
+        // So is a missing one
+        reopened.storage.as_ref().unwrap().delete(b"index_metadata").unwrap();
+        reopened.close().unwrap();
+        drop(reopened);
+        
+        let mut reopened = reopen(&path, &config);
+        assert_eq!(reopened.document_count(), 3);
+        assert_eq!(reopened.search("iphone", None).unwrap()[0].document.id, "test1");
+        assert_eq!(reopened.rebuild_from_storage().unwrap(), 3);
+        assert_eq!(reopened.document_count(), 3);
+        
+        assert!(TigerCache::new().rebuild_from_storage().is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "encryption", feature = "sled-storage"))]
+    fn test_open_reports_unreadable_index() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sled_db");
+        let config = |key: [u8; 32]| {
+            let mut config = TigerCacheConfig::new()
+                .with_storage_type(crate::storage::StorageType::Sled)
+                .with_storage_path(&path);
+            config.storage.use_compression = false;
+            config.storage.encryption_key = Some(key);
+            config
+        };
+        
+        let mut cache = TigerCache::open_with_config(&path, config([7; 32])).unwrap();
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        cache.commit().unwrap();
+        cache.close().unwrap();
+        drop(cache);
+        
+        // A stored index that can't be decrypted fails the open instead of
+        // leaving an empty cache over the stored data
+        assert!(try_reopen(&path, &config([8; 32])).is_err());
+        
+        let reopened = reopen(&path, &config([7; 32]));
+        assert_eq!(reopened.search("iphone", None).unwrap()[0].document.id, "test1");
+    }
+
+    #[test]
+    #[cfg(feature = "sled-storage")]
+    fn test_storage_format_version() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sled_db");
+        let mut config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Sled)
+            .with_storage_path(&path);
+        config.storage.use_compression = false;
+        let stored_version = |cache: &TigerCache| cache.storage.as_ref().unwrap().get(STORAGE_VERSION_KEY).unwrap();
+        
+        let mut cache = TigerCache::open_with_config(&path, config.clone()).unwrap();
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        cache.commit().unwrap();
+        assert_eq!(stored_version(&cache), Some(STORAGE_FORMAT_VERSION.to_le_bytes().to_vec()));
+        
+        // Storage from before versions were recorded, with an undecodable index
+        // and a document in the original bincode encoding, is migrated on open
+        let storage = cache.storage.clone().unwrap();
+        storage.delete(STORAGE_VERSION_KEY).unwrap();
+        storage.put(b"index_metadata", b"not an index").unwrap();
+        storage.put(b"doc:test3", b"\x05test3\x00").unwrap();
+        drop(storage);
+        cache.close().unwrap();
+        drop(cache);
+        
+        let reopened = reopen(&path, &config);
+        assert_eq!(reopened.document_count(), 2);
+        assert_eq!(reopened.search("aple", None).unwrap().len(), 2);
+        assert_eq!(stored_version(&reopened), Some(STORAGE_FORMAT_VERSION.to_le_bytes().to_vec()));
+        assert!(matches!(reopened.read_document("test3"), Err(TigerCacheError::LegacyDocumentEncoding(id)) if id == "test3"));
+        
+        // Storage written by a newer format version isn't loaded
+        reopened.storage.as_ref().unwrap().put(STORAGE_VERSION_KEY, &(STORAGE_FORMAT_VERSION + 1).to_le_bytes()).unwrap();
+        reopened.close().unwrap();
+        drop(reopened);
+        
+        let result = try_reopen(&path, &config);
+        assert!(matches!(result, Err(TigerCacheError::UnsupportedFormatVersion { .. })));
+    }
+
+    #[test]
+    #[cfg(feature = "sled-storage")]
+    fn test_auto_commit() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sled_db");
+        let mut config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Sled)
+            .with_storage_path(&path)
+            .with_auto_commit_interval(Duration::from_millis(20));
+        config.storage.use_compression = false;
+        let shared = crate::shared::SharedTigerCache::new(TigerCache::open_with_config(&path, config.clone()).unwrap());
+        
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        shared.add_document(doc).unwrap();
+        
+        // The index reaches storage without an explicit commit
+        let stored_count = || {
+            let cache = shared.read();
+            let metadata = cache.storage.as_ref().unwrap().get(b"index_metadata").unwrap()?;
+            let (index, _) = bincode::decode_from_slice::<Index, _>(&metadata, bincode::config::standard()).unwrap();
+            Some(index.document_count())
+        };
+        assert!(wait_until(|| stored_count() == Some(1)));
+        
+        // Closing stops the background commits, and what they wrote survives a reopen
+        shared.read().close().unwrap();
+        let mut doc = Document::new("test2");
+        doc.add_field("title", "Apple Watch");
+        shared.add_document(doc).unwrap();
+        drop(shared);
+        
+        let reopened = reopen(&path, &config);
+        assert_eq!(reopened.search("iphone", None).unwrap()[0].document.id, "test1");
+        assert!(reopened.search("watch", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_auto_commit_without_sharing() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("auto_commit_db"))
+            .with_auto_commit_interval(Duration::from_millis(20));
+        let mut cache = TigerCache::with_config(config);
+        let stored_index = |cache: &TigerCache| {
+            let metadata = cache.storage.as_ref().unwrap().get(b"index_metadata").unwrap()?;
+            let (index, _) = bincode::decode_from_slice::<Index, _>(&metadata, bincode::config::standard()).unwrap();
+            Some(index)
+        };
+        
+        // A plain cache's background task writes the index too, not just the storage
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+            assert!(wait_until(|| stored_index(&cache).map(|index| index.document_count()) == Some(cache.document_count())));
         }
+        assert!(!cache.dirty.load(Ordering::Acquire));
         
-        Ok(())
+        // Nothing is rewritten while the index is unchanged
+        let write_count = cache.storage_stats().unwrap().unwrap().write_count;
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(cache.storage_stats().unwrap().unwrap().write_count, write_count);
     }
-    
-    /// Close the TigerCache instance
-    pub fn close(&self) -> Result<()> {
-        // Flush and close storage
-        if let Some(storage) = &self.storage {
-            storage.flush()?;
-            storage.close()?;
+
+    #[test]
+    #[cfg(feature = "sled-storage")]
+    fn test_auto_commit_after_update_config() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sled_db");
+        let mut config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Sled)
+            .with_storage_path(&path)
+            .with_auto_commit_interval(Duration::from_millis(20));
+        config.storage.use_compression = false;
+        
+        let mut cache = TigerCache::open_with_config(&path, config.clone()).unwrap();
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        cache.update_config(config.clone()).unwrap();
+        
+        // The background task writes the index carried over by the update
+        let mut doc = Document::new("test2");
+        doc.add_field("title", "Apple Watch");
+        cache.add_document(doc).unwrap();
+        assert!(wait_until(|| !cache.dirty.load(Ordering::Acquire)));
+        cache.close().unwrap();
+        drop(cache);
+        
+        let reopened = reopen(&path, &config);
+        assert_eq!(reopened.document_count(), 2);
+        assert_eq!(reopened.search("watch", None).unwrap()[0].document.id, "test2");
+        
+        // A shared cache keeps its full commit task
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("shared_db"))
+            .with_auto_commit_interval(Duration::from_millis(20));
+        let shared = crate::shared::SharedTigerCache::new(TigerCache::with_config(config.clone()));
+        shared.write().update_config(config).unwrap();
+        assert!(shared.read().auto_commit_task.is_some());
+    }
+
+    #[test]
+    fn test_auto_commit_requires_background_ops() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("auto_commit_db"))
+            .with_auto_commit_interval(Duration::from_millis(20))
+            .with_background_ops(false);
+        let cache = TigerCache::with_config(config);
+        assert!(cache.storage.is_some());
+        assert!(cache.auto_committer.is_none());
+    }
+
+    #[test]
+    fn test_commit_skipped_when_unchanged() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("dirty_db"));
+        let mut cache = TigerCache::with_config(config);
+        let write_count = |cache: &TigerCache| cache.storage_stats().unwrap().unwrap().write_count;
+        
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        cache.commit().unwrap();
+        let committed = write_count(&cache);
+        
+        // Searching doesn't change the index, so nothing is rewritten
+        cache.search("apple", None).unwrap();
+        cache.commit().unwrap();
+        assert_eq!(write_count(&cache), committed);
+        
+        cache.remove_document("test1").unwrap();
+        let removed = write_count(&cache);
+        cache.commit().unwrap();
+        
+        // The index and its format version
+        assert_eq!(write_count(&cache), removed + 2);
+    }
+
+    #[test]
+    fn test_commit_stats() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("commit_stats_db"));
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        let stats = cache.commit().unwrap();
+        assert!(stats.bytes_written > 0);
+        assert!(stats.duration > Duration::ZERO);
+        
+        // An unchanged index isn't rewritten
+        assert_eq!(cache.commit().unwrap(), CommitStats::default());
+        
+        cache.remove_document("test1").unwrap();
+        assert!(cache.commit().unwrap().bytes_written > 0);
+    }
+
+    #[test]
+    fn test_stored_document_ids() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"));
+        let mut cache = TigerCache::with_config(config);
+        
+        for id in ["b", "a", "c"] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", "Stored Document");
+            cache.add_document(doc).unwrap();
         }
+        cache.remove_document("c").unwrap();
         
-        // Stop memory manager
-        if let Some(mm) = &self.memory_manager {
-            mm.stop();
+        assert_eq!(cache.stored_document_ids().unwrap(), vec!["a", "b"]);
+        assert!(TigerCache::new().stored_document_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_memory_pressure_evicts_caches() {
+        let dir = tempdir().unwrap();
+        let max_memory = ByteSize::kib(4);
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"))
+            .with_max_memory(max_memory);
+        let mut cache = TigerCache::with_config(config);
+        let memory_manager = cache.memory_manager.clone().unwrap();
+        let document_cache = cache.document_cache.clone().unwrap();
+        let index_cache = cache.index_cache.clone().unwrap();
+        let query_cache = cache.query_cache.clone().unwrap();
+        
+        // Cached documents alone outgrow max_memory, well within the cache sizes
+        for i in 0..100 {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", "Cached Document");
+            cache.add_document(doc).unwrap();
         }
         
-        Ok(())
+        // The manager's usage is what the caches hold, back under max_memory
+        let cached = || document_cache.size().as_u64() + index_cache.total_size().as_u64() + query_cache.size().as_u64();
+        assert!(wait_until(|| {
+            let usage = memory_manager.current_usage();
+            usage <= max_memory && usage.as_u64() == cached()
+        }));
+        assert!(document_cache.len() < 100);
+        assert!(!document_cache.is_empty());
     }
-    
-    /// Get the configuration
-    pub fn config(&self) -> &TigerCacheConfig {
-        &self.config
+
+    #[test]
+    fn test_query_cache_invalidated_on_mutation() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"));
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        assert_eq!(cache.search("apple", None).unwrap().len(), 1);
+        assert_eq!(cache.query_cache.as_ref().unwrap().len(), 1);
+        
+        let mut doc = Document::new("doc2");
+        doc.add_field("title", "Apple MacBook");
+        cache.add_document(doc).unwrap();
+        assert_eq!(cache.search("apple", None).unwrap().len(), 2);
+        
+        cache.remove_document("doc1").unwrap();
+        let results = cache.search("apple", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "doc2");
     }
-    
-    /// Update the configuration
-    pub fn update_config(&mut self, config: TigerCacheConfig) -> Result<()> {
-        // Store the old path
-        let old_path = self.path.clone();
+
+    #[test]
+    fn test_clear_caches() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"));
+        let mut cache = TigerCache::with_config(config);
         
-        // Close the current instance
-        self.close()?;
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        cache.search("apple", None).unwrap();
+        let doc_ids = smallvec::SmallVec::from_slice(&[crate::intern::StringId::new(1)]);
+        cache.index_cache.as_ref().unwrap().put_inverted(crate::intern::StringId::new(0), doc_ids);
         
-        // Create a new instance with the new configuration
-        let mut new_instance = Self::with_config(config);
+        assert!(!cache.document_cache.as_ref().unwrap().is_empty());
+        assert!(!cache.query_cache.as_ref().unwrap().is_empty());
+        assert!(cache.cache_stats().unwrap().index_cache_size.as_u64() > 0);
         
-        // Restore the path
-        new_instance.path = old_path;
+        // Each cache is cleared on its own
+        cache.clear_query_cache();
+        assert!(cache.query_cache.as_ref().unwrap().is_empty());
+        assert!(!cache.document_cache.as_ref().unwrap().is_empty());
         
-        // Copy the index
-        new_instance.index = self.index.clone();
+        cache.clear_document_cache();
+        assert!(cache.document_cache.as_ref().unwrap().is_empty());
+        assert!(cache.cache_stats().unwrap().index_cache_size.as_u64() > 0);
         
-        // Replace self with the new instance
-        *self = new_instance;
+        cache.clear_index_cache();
+        assert_eq!(cache.cache_stats().unwrap().index_cache_size.as_u64(), 0);
         
-        Ok(())
+        // The index is untouched
+        assert_eq!(cache.document_count(), 1);
+        assert_eq!(cache.get_document("doc1").unwrap().id, "doc1");
+        assert_eq!(cache.search("apple", None).unwrap().len(), 1);
     }
-}
 
-impl Default for TigerCache {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_batch_defers_query_cache_invalidation() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"));
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("doc0");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        cache.search("apple", None).unwrap();
+        
+        {
+            let mut batch = cache.batch();
+            for i in 1..4 {
+                let mut doc = Document::new(format!("doc{}", i));
+                doc.add_field("title", format!("Apple product {}", i));
+                batch.add_document(doc).unwrap();
+                
+                // Not cleared per document
+                assert_eq!(batch.query_cache.as_ref().unwrap().len(), 1);
+            }
+            batch.remove_document("doc0").unwrap();
+            assert_eq!(batch.query_cache.as_ref().unwrap().len(), 1);
+        }
+        
+        // Cleared once when the batch ends
+        assert!(cache.query_cache.as_ref().unwrap().is_empty());
+        assert!(!cache.in_batch);
+        assert_eq!(cache.search("apple", None).unwrap().len(), 3);
+        
+        // Committing explicitly ends the batch the same way
+        let mut batch = cache.batch();
+        batch.remove_document("doc1").unwrap();
+        batch.commit().unwrap();
+        assert_eq!(cache.search("apple", None).unwrap().len(), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::document::Document;
-    use crate::search::SearchOptions;
-    use tempfile::tempdir;
+    #[test]
+    fn test_search_multi() {
+        let mut cache = TigerCache::new();
+        cache.set_indexed_fields(vec!["title".to_string()]);
+        for (id, title) in [("doc1", "Apple iPhone"), ("doc2", "Samsung Galaxy"), ("doc3", "Apple MacBook")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        
+        let limited = SearchOptions { limit: 1, ..Default::default() };
+        let queries = [("apple", None), ("galaxy", None), ("apple", Some(limited))];
+        let results = cache.search_multi(&queries).unwrap();
+        
+        assert_eq!(results.len(), 3);
+        for ((query, options), result_set) in queries.iter().zip(&results) {
+            let expected = cache.search(query, options.clone()).unwrap();
+            let ids: Vec<&str> = result_set.iter().map(|r| r.document.id.as_str()).collect();
+            let expected_ids: Vec<&str> = expected.iter().map(|r| r.document.id.as_str()).collect();
+            assert_eq!(ids, expected_ids);
+        }
+        assert_eq!(results[0].len(), 2);
+        assert_eq!(results[1][0].document.id, "doc2");
+        assert_eq!(results[2].len(), 1);
+        
+        assert!(cache.search_multi(&[]).unwrap().is_empty());
+    }
 
     #[test]
-    fn test_new() {
-        let cache = TigerCache::new();
-        assert_eq!(cache.document_count(), 0);
-        assert!(cache.path.is_none());
+    fn test_cache_stats() {
+        assert!(TigerCache::new().cache_stats().is_none());
+        
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("storage"));
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc = Document::new("doc1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        
+        for _ in 0..4 {
+            cache.search("apple", None).unwrap();
+        }
+        
+        // One miss followed by three hits
+        let stats = cache.cache_stats().unwrap();
+        assert!(stats.query_cache_hit_rate > 0.0);
+        assert_eq!(stats.query_cache_hit_rate, 0.75);
+        assert!(stats.query_cache_size.as_u64() > 0);
     }
 
     #[test]
-    fn test_default() {
-        let cache: TigerCache = Default::default();
-        assert_eq!(cache.document_count(), 0);
-        assert!(cache.path.is_none());
+    fn test_document_ttl() {
+        let mut cache = TigerCache::new();
+        cache.set_indexed_fields(vec!["title".to_string()]);
+        
+        let mut doc1 = Document::new("doc1");
+        doc1.add_field("title", "Expiring Widget");
+        let mut doc2 = Document::new("doc2");
+        doc2.add_field("title", "Permanent Widget");
+        
+        cache.add_document_with_ttl(doc1, Duration::from_millis(50)).unwrap();
+        cache.add_document(doc2).unwrap();
+        assert!(cache.get_document("doc1").is_some());
+        assert_eq!(cache.search("widget", None).unwrap().len(), 2);
+        
+        std::thread::sleep(Duration::from_millis(100));
+        
+        assert!(cache.get_document("doc1").is_none());
+        let results = cache.search("widget", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "doc2");
+        
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.document_count(), 1);
+        assert_eq!(cache.purge_expired(), 0);
     }
 
     #[test]
-    fn test_open_nonexistent() {
+    fn test_import_ndjson() {
+        let ndjson = r#"{"sku": "A1", "title": "Red Widget", "price": 10}
+{"sku": "A2", "title": "Blue Lamp", "price": 12.5}
+
+not json
+{"title": "Missing SKU"}
+{"sku": "A3", "title": "Green Widget"}
+"#;
+
+        let mut cache = TigerCache::new();
+        cache.set_indexed_fields(vec!["title".to_string()]);
+        let imported = cache.import_ndjson(ndjson.as_bytes(), "sku", ImportErrorPolicy::Skip).unwrap();
+        assert_eq!(imported, 3);
+        assert_eq!(cache.document_count(), 3);
+        assert_eq!(cache.get_document("A2").unwrap().fields["price"], serde_json::json!(12.5));
+        assert!(!cache.get_document("A1").unwrap().fields.contains_key("sku"));
+        assert_eq!(cache.search("widget", None).unwrap().len(), 2);
+        
+        // Fail-fast stops at the first bad line, keeping the lines before it
+        let mut cache = TigerCache::new();
+        let result = cache.import_ndjson(ndjson.as_bytes(), "sku", ImportErrorPolicy::FailFast);
+        assert!(matches!(result, Err(TigerCacheError::InvalidDocument(ref msg)) if msg.starts_with("line 4:")));
+        assert_eq!(cache.document_count(), 2);
+    }
+
+    #[test]
+    fn test_export_import_json_round_trip() {
         let dir = tempdir().unwrap();
-        let file_path = dir.path().join("nonexistent.bin");
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("export_db"))
+            .with_lazy_documents(true);
+        let mut source = TigerCache::with_config(config);
+        
+        let mut doc1 = Document::new("doc1");
+        doc1.add_field("title", "Mixed Types")
+            .add_field("count", 42)
+            .add_field("ratio", 0.25)
+            .add_field("big", u64::MAX)
+            .add_field("active", true)
+            .add_field("tags", vec!["a", "b"])
+            .add_field("meta", serde_json::json!({"nested": {"level": 2}}))
+            .add_field("missing", serde_json::Value::Null);
+        let mut doc2 = Document::new("doc2");
+        doc2.add_field("title", "Second");
+        source.add_document(doc1.clone()).unwrap();
+        source.add_document(doc2).unwrap();
+        
+        let mut exported = Vec::new();
+        source.export_json(&mut exported).unwrap();
+        
+        let mut target = TigerCache::new();
+        assert_eq!(target.import_json(exported.as_slice()).unwrap(), 2);
+        assert_eq!(target.document_count(), 2);
+        
+        let restored = target.get_document("doc1").unwrap();
+        assert_eq!(restored.fields, doc1.fields);
+        assert!(restored.fields["count"].is_u64());
+        assert!(restored.fields["ratio"].is_f64());
+        assert_eq!(restored.fields["big"].as_u64(), Some(u64::MAX));
+        
+        // Exporting the restored cache produces the same documents
+        let mut reexported = Vec::new();
+        target.export_json(&mut reexported).unwrap();
+        let parse = |bytes: &[u8]| serde_json::from_slice::<serde_json::Value>(bytes).unwrap();
+        assert_eq!(parse(&reexported), parse(&exported));
+    }
+
+    #[test]
+    fn test_retain() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("retain_db"));
+        let mut cache = TigerCache::with_config(config);
+        
+        for (id, category) in [("doc1", Some("A")), ("doc2", Some("B")), ("doc3", Some("A")), ("doc4", None)] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", format!("Retained product {}", id));
+            if let Some(category) = category {
+                doc.add_field("category", category);
+            }
+            cache.add_document(doc).unwrap();
+        }
+        assert_eq!(cache.search("product", None).unwrap().len(), 4);
         
-        let cache = TigerCache::open(&file_path).unwrap();
-        assert_eq!(cache.document_count(), 0);
-        assert_eq!(cache.path.as_ref().unwrap(), &file_path);
+        let removed = cache.retain(|doc| doc.get_text_field("category").as_deref() == Some("A")).unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(cache.document_count(), 2);
+        assert!(cache.get_document("doc2").is_none());
+        assert!(!cache.contains_document("doc4"));
+        assert_eq!(cache.stored_document_ids().unwrap(), vec!["doc1".to_string(), "doc3".to_string()]);
+        
+        let mut ids: Vec<String> = cache.search("product", None).unwrap().into_iter().map(|result| result.document.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["doc1".to_string(), "doc3".to_string()]);
+        
+        // Nothing left to remove
+        assert_eq!(cache.retain(|doc| doc.get_text_field("category").is_some()).unwrap(), 0);
     }
 
     #[test]
-    fn test_set_indexed_fields() {
+    fn test_verify() {
         let mut cache = TigerCache::new();
-        let fields = vec!["title".to_string(), "description".to_string()];
+        for i in 0..10 {
+            let mut doc = Document::new(format!("doc{}", i));
+            doc.add_field("title", format!("Verified document{}", i));
+            cache.add_document(doc).unwrap();
+        }
+        assert!(cache.verify().is_empty());
         
-        cache.set_indexed_fields(fields.clone());
+        cache.remove_document("doc3").unwrap();
+        assert!(cache.verify().iter().all(|report| matches!(report, InconsistencyReport::DanglingTrigram { .. })));
+        assert!(!cache.verify().is_empty());
         
-        // We can't directly access the indexed_fields, but we can test the functionality
-        let mut doc = Document::new("test");
-        doc.add_field("title", "Test Title")
-           .add_field("description", "Test Description")
-           .add_field("irrelevant", "This should not be indexed");
+        cache.compact_index();
+        assert!(cache.verify().is_empty());
+    }
+
+    #[test]
+    fn test_compact() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("compact_db"));
+        let mut cache = TigerCache::with_config(config);
+        
+        for i in 0..10 {
+            let mut doc = Document::new(format!("doc{}", i));
+            doc.add_field("title", format!("Compactable document {}", i));
+            cache.add_document(doc).unwrap();
+        }
+        let before = cache.storage_stats().unwrap().unwrap();
         
-        cache.add_document(doc).unwrap();
+        for i in 0..8 {
+            cache.remove_document(&format!("doc{}", i)).unwrap();
+        }
+        cache.compact().unwrap();
         
-        // Search for a term in the indexed fields
-        let results = cache.search("title", None).unwrap();
-        assert!(!results.is_empty());
+        // Each document is stored with its version
+        let after = cache.storage_stats().unwrap().unwrap();
+        assert_eq!(after.key_count, before.key_count - 16);
+        assert!(after.total_value_size < before.total_value_size);
+        assert_eq!(cache.stored_document_ids().unwrap(), vec!["doc8".to_string(), "doc9".to_string()]);
         
-        // Search for a term in the non-indexed field
-        let results = cache.search("irrelevant", None).unwrap();
-        assert!(results.is_empty());
+        // Without storage, compaction is a no-op
+        assert!(TigerCache::new().compact().is_ok());
     }
 
     #[test]
@@ -545,6 +3577,39 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_merge_document() {
+        let mut cache = TigerCache::new();
+        let mut doc = Document::new("test");
+        doc.add_field("title", "Test Title")
+           .add_field("description", "Original text");
+        cache.add_document(doc).unwrap();
+        
+        let mut fields = HashMap::new();
+        fields.insert("description".to_string(), serde_json::json!("Refurbished gadget"));
+        cache.merge_document("test", fields).unwrap();
+        
+        let merged = cache.get_document("test").unwrap();
+        assert_eq!(merged.get_text_field("title").unwrap(), "Test Title");
+        assert_eq!(merged.get_text_field("description").unwrap(), "Refurbished gadget");
+        assert_eq!(cache.document_count(), 1);
+        
+        // The new description is searchable, the old one is not
+        let results = cache.search("refurbished", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "test");
+        
+        let results = cache.search("original", None).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_merge_nonexistent_document() {
+        let mut cache = TigerCache::new();
+        let result = cache.merge_document("nonexistent", HashMap::new());
+        assert!(matches!(result, Err(TigerCacheError::DocumentNotFound(_))));
+    }
+
     #[test]
     fn test_get_document() {
         let mut cache = TigerCache::new();
@@ -610,6 +3675,7 @@ mod tests {
             max_distance: 1,
             score_threshold: 0,
             limit: 1,
+            ..Default::default()
         };
         
         let results = cache.search("Aple", Some(options)).unwrap();
@@ -617,6 +3683,246 @@ mod tests {
         assert_eq!(results[0].document.id, "test1");
     }
 
+    #[test]
+    fn test_search_ids() {
+        let mut cache = TigerCache::new();
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch"), ("test3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        
+        let ids = cache.search_ids("apple", None).unwrap();
+        let results = cache.search("apple", None).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(
+            ids,
+            results.iter().map(|r| (r.document.id.clone(), r.score)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let dir = tempdir().unwrap();
+        let config = TigerCacheConfig::new()
+            .with_storage_type(crate::storage::StorageType::Memory)
+            .with_storage_path(dir.path().join("shard_db"))
+            .with_lazy_documents(true);
+        let mut shard = TigerCache::with_config(config);
+        for (id, title) in [("test2", "Apple Watch"), ("test3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            shard.add_document(doc).unwrap();
+        }
+        
+        let mut cache = TigerCache::new();
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        assert_eq!(cache.search("apple", None).unwrap().len(), 1);
+        
+        // Documents only held in the shard's storage come along in full
+        cache.merge(shard).unwrap();
+        assert_eq!(cache.document_count(), 3);
+        let results = cache.search("apple", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(cache.get_document("test3").unwrap().get_text_field("title"), Some("Samsung Galaxy".to_string()));
+        
+        // Documents held in memory by a cache without storage come along too
+        let mut in_memory = TigerCache::new();
+        let mut doc = Document::new("test4");
+        doc.add_field("title", "Apple Pencil");
+        in_memory.add_document(doc).unwrap();
+        cache.merge(in_memory).unwrap();
+        assert_eq!(cache.document_count(), 4);
+        assert_eq!(cache.search("apple", None).unwrap().len(), 3);
+        assert_eq!(cache.get_document("test4").unwrap().get_text_field("title"), Some("Apple Pencil".to_string()));
+        
+        let mut stemmed = TigerCache::new();
+        stemmed.set_stemming(true).unwrap();
+        assert!(matches!(cache.merge(stemmed), Err(TigerCacheError::IncompatibleIndex(_))));
+    }
+
+    #[test]
+    fn test_metrics_observer() {
+        use std::sync::Mutex;
+        
+        let mut cache = TigerCache::new();
+        for (id, title) in [("test1", "Apple iPhone"), ("test2", "Apple Watch"), ("test3", "Samsung Galaxy")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        
+        let observed: Arc<Mutex<Vec<QueryMetrics>>> = Arc::default();
+        let sink = observed.clone();
+        cache.set_metrics_observer(Box::new(move |metrics| sink.lock().unwrap().push(metrics.clone())));
+        
+        cache.search("apple", None).unwrap();
+        cache.search("nothing", None).unwrap();
+        
+        {
+            let observed = observed.lock().unwrap();
+            assert_eq!(observed.len(), 2);
+            assert_eq!(observed[0].query, "apple");
+            assert_eq!(observed[0].result_count, 2);
+            assert_eq!(observed[0].candidate_tokens, 1);
+            assert!(!observed[0].cache_hit);
+            assert_eq!(observed[1].result_count, 0);
+        }
+        
+        cache.clear_metrics_observer();
+        cache.search("apple", None).unwrap();
+        assert_eq!(observed.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_search_timeout() {
+        use std::sync::Mutex;
+        
+        let documents = (0..5000).map(|i| {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("product{} item{} model{}", i % 97, i % 89, i));
+            doc
+        });
+        let mut cache = TigerCache::new();
+        cache.add_documents_from_iter(documents, 1000).unwrap();
+        
+        let observed: Arc<Mutex<Vec<QueryMetrics>>> = Arc::default();
+        let sink = observed.clone();
+        cache.set_metrics_observer(Box::new(move |metrics| sink.lock().unwrap().push(metrics.clone())));
+        
+        // An exhausted budget returns whatever was gathered, flagged as partial
+        let query = "prodct itme modl";
+        let options = SearchOptions { timeout: Some(Duration::ZERO), ..Default::default() };
+        let partial = cache.search(query, Some(options)).unwrap();
+        let complete = cache.search(query, None).unwrap();
+        assert!(partial.len() <= complete.len());
+        assert!(!complete.is_empty());
+        
+        // A generous budget changes nothing
+        let options = SearchOptions { timeout: Some(Duration::from_secs(3600)), ..Default::default() };
+        let results = cache.search(query, Some(options)).unwrap();
+        let ids = |results: &[SearchResult]| results.iter().map(|r| r.document.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&results), ids(&complete));
+        
+        let observed = observed.lock().unwrap();
+        assert!(observed[0].partial);
+        assert!(!observed[1].partial);
+        assert!(!observed[2].partial);
+    }
+
+    #[test]
+    fn test_search_cancellable() {
+        let documents = (0..5000).map(|i| {
+            let mut doc = Document::new(format!("doc{i}"));
+            doc.add_field("title", format!("product{} item{} model{}", i % 97, i % 89, i));
+            doc
+        });
+        let mut cache = TigerCache::new();
+        cache.add_documents_from_iter(documents, 1000).unwrap();
+        let query = "prodct itme modl";
+        
+        // An unset flag changes nothing
+        let cancel = Arc::new(AtomicBool::new(false));
+        let results = cache.search_cancellable(query, None, cancel.clone()).unwrap();
+        assert_eq!(results.len(), cache.search(query, None).unwrap().len());
+        
+        // Searches running when another thread sets the flag are abandoned
+        let cancelled = std::thread::scope(|scope| {
+            let canceller = cancel.clone();
+            scope.spawn(move || {
+                std::thread::sleep(Duration::from_millis(20));
+                canceller.store(true, Ordering::Relaxed);
+            });
+            
+            let started = Instant::now();
+            loop {
+                match cache.search_cancellable(query, None, cancel.clone()) {
+                    Err(e) => break Some(e),
+                    Ok(_) if started.elapsed() > Duration::from_secs(30) => break None,
+                    Ok(_) => {}
+                }
+            }
+        });
+        assert!(matches!(cancelled, Some(TigerCacheError::Cancelled)));
+        
+        // Plain searches don't see the flag
+        assert!(!cache.search(query, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_parsed() {
+        let mut cache = TigerCache::new();
+        for (id, title) in [("doc1", "Rust tools"), ("doc2", "Go tools"), ("doc3", "Rust compiler")] {
+            let mut doc = Document::new(id);
+            doc.add_field("title", title);
+            cache.add_document(doc).unwrap();
+        }
+        let ids = |results: Vec<SearchResult>| {
+            let mut ids: Vec<String> = results.into_iter().map(|result| result.document.id).collect();
+            ids.sort();
+            ids
+        };
+        
+        let query = QueryAst::and([QueryAst::term("tools"), !QueryAst::term("rust")]);
+        assert_eq!(ids(cache.search_parsed(&query, None).unwrap()), vec!["doc2"]);
+        assert_eq!(ids(cache.search(&query.to_string(), None).unwrap()), vec!["doc2"]);
+        
+        // Terms built programmatically aren't parsed as query syntax
+        let query = QueryAst::term("-rust tools");
+        assert_eq!(ids(cache.search_parsed(&query, None).unwrap()), vec!["doc1", "doc2", "doc3"]);
+        assert_eq!(ids(cache.search("-rust tools", None).unwrap()), vec!["doc2"]);
+    }
+
+    #[test]
+    fn test_slow_query_observer() {
+        use std::sync::Mutex;
+        
+        let config = TigerCacheConfig::new().with_slow_query_threshold(Duration::ZERO);
+        let mut cache = TigerCache::with_config(config);
+        let mut doc = Document::new("test1");
+        doc.add_field("title", "Apple iPhone");
+        cache.add_document(doc).unwrap();
+        
+        let slow: Arc<Mutex<Vec<SlowQuery>>> = Arc::default();
+        let sink = slow.clone();
+        cache.set_slow_query_observer(Box::new(move |query| sink.lock().unwrap().push(query.clone())));
+        
+        let options = SearchOptions { limit: 5, ..Default::default() };
+        cache.search("apple", Some(options.clone())).unwrap();
+        
+        let slow = slow.lock().unwrap();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].query, "apple");
+        assert_eq!(slow[0].options, options);
+        assert_eq!(slow[0].result_count, 1);
+    }
+
+    #[test]
+    fn test_search_boost_field() {
+        let config = TigerCacheConfig::new().with_boost_field("popularity");
+        let mut cache = TigerCache::with_config(config);
+        
+        let mut doc1 = Document::new("test1");
+        doc1.add_field("title", "Red Widget");
+        cache.add_document(doc1).unwrap();
+        
+        let mut doc2 = Document::new("test2");
+        doc2.add_field("title", "Red Widget").add_field("popularity", 2.5);
+        cache.add_document(doc2).unwrap();
+        
+        // Both match equally, so the boost decides the order
+        let results = cache.search("widget", None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].document.id, "test2");
+        assert!((results[0].score - results[1].score * 2.5).abs() < 1e-9);
+        
+        let explained = cache.search_explain("widget", None).unwrap();
+        assert_eq!(explained[0].1.boost, 2.5);
+        assert_eq!(explained[1].1.boost, 1.0);
+    }
+
     #[test]
     fn test_commit_without_path() {
         let cache = TigerCache::new();
@@ -0,0 +1,194 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+
+use crate::trigram::{extract_tokens, extract_tokens_with, fold_diacritics, NormalizeOptions, PunctuationSet};
+
+/// Common English words that add little to relevance (the Lucene default set)
+pub const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in",
+    "into", "is", "it", "no", "not", "of", "on", "or", "such", "that", "the",
+    "their", "then", "there", "these", "they", "this", "to", "was", "will", "with",
+];
+
+/// Splits text into the tokens stored in the inverted index
+///
+/// The same tokenizer is applied to documents at indexing time and to queries
+/// at search time, so changing it requires reindexing.
+pub trait Tokenizer: Debug + Send + Sync {
+    /// Split text into tokens
+    fn tokenize(&self, text: &str) -> Vec<String>;
+    
+    /// Split text into tokens, honouring the index's normalization options
+    ///
+    /// Defaults to `tokenize`, ignoring the options. Built-in tokenizers override
+    /// this; custom tokenizers may do so to support options such as diacritic folding.
+    fn tokenize_with(&self, text: &str, _options: &NormalizeOptions) -> Vec<String> {
+        self.tokenize(text)
+    }
+    
+    /// Get the kind tag persisted alongside the index
+    fn kind(&self) -> TokenizerKind {
+        TokenizerKind::Custom
+    }
+}
+
+/// Tokenizer selection, persisted with the index as a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TokenizerKind {
+    /// Lowercase, strip punctuation, and split on whitespace
+    #[default]
+    Standard,
+    
+    /// Lowercase and split on whitespace, keeping punctuation inside words
+    Whitespace,
+    
+    /// Caller-supplied tokenizer
+    ///
+    /// Custom tokenizers cannot be persisted; a loaded index falls back to the
+    /// standard tokenizer until the custom one is supplied again.
+    Custom,
+}
+
+impl TokenizerKind {
+    /// Create the built-in tokenizer for this kind
+    pub fn build(self) -> Arc<dyn Tokenizer> {
+        match self {
+            TokenizerKind::Standard => Arc::new(StandardTokenizer),
+            TokenizerKind::Whitespace => Arc::new(WhitespaceTokenizer),
+            TokenizerKind::Custom => Arc::new(MissingCustomTokenizer),
+        }
+    }
+}
+
+/// The default tokenizer: lowercases, strips punctuation, and splits on whitespace
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardTokenizer;
+
+impl Tokenizer for StandardTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        extract_tokens(text)
+    }
+    
+    fn tokenize_with(&self, text: &str, options: &NormalizeOptions) -> Vec<String> {
+        extract_tokens_with(text, options)
+    }
+    
+    fn kind(&self) -> TokenizerKind {
+        TokenizerKind::Standard
+    }
+}
+
+/// Tokenizer that splits on whitespace only
+///
+/// Punctuation inside a word is preserved, so hyphenated terms such as
+/// "wi-fi" stay a single token. Leading and trailing punctuation is trimmed,
+/// apart from the normalization options' preserved punctuation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhitespaceTokenizer;
+
+impl WhitespaceTokenizer {
+    /// Split on whitespace and trim punctuation not in `preserve`, optionally lowercasing
+    fn split(text: &str, lowercase: bool, preserve: PunctuationSet) -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && !preserve.contains(c)))
+            .filter(|token| token.chars().any(char::is_alphanumeric))
+            .map(|token| if lowercase { token.to_lowercase() } else { token.to_string() })
+            .collect()
+    }
+}
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        Self::split(text, true, PunctuationSet::default())
+    }
+    
+    fn tokenize_with(&self, text: &str, options: &NormalizeOptions) -> Vec<String> {
+        let lowercase = !options.case_sensitive;
+        if options.fold_diacritics {
+            Self::split(&fold_diacritics(text), lowercase, options.preserve_punctuation)
+        } else {
+            Self::split(text, lowercase, options.preserve_punctuation)
+        }
+    }
+    
+    fn kind(&self) -> TokenizerKind {
+        TokenizerKind::Whitespace
+    }
+}
+
+/// Stand-in for a custom tokenizer that hasn't been supplied after loading
+///
+/// Tokenizes like `StandardTokenizer` but keeps the `Custom` tag, so the index
+/// still records that the real tokenizer must be re-attached.
+#[derive(Debug, Clone, Copy)]
+struct MissingCustomTokenizer;
+
+impl Tokenizer for MissingCustomTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        extract_tokens(text)
+    }
+    
+    fn tokenize_with(&self, text: &str, options: &NormalizeOptions) -> Vec<String> {
+        extract_tokens_with(text, options)
+    }
+}
+
+/// Serde adapter that persists a tokenizer as its `TokenizerKind` tag
+pub(crate) mod serde_tokenizer {
+    use std::sync::Arc;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    
+    use super::{Tokenizer, TokenizerKind};
+    
+    pub fn serialize<S: Serializer>(tokenizer: &Arc<dyn Tokenizer>, serializer: S) -> Result<S::Ok, S::Error> {
+        tokenizer.kind().serialize(serializer)
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<dyn Tokenizer>, D::Error> {
+        Ok(TokenizerKind::deserialize(deserializer)?.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_standard_tokenizer() {
+        let tokenizer = StandardTokenizer;
+        assert_eq!(tokenizer.tokenize("Wi-Fi Router, 2.4GHz"), extract_tokens("Wi-Fi Router, 2.4GHz"));
+        assert_eq!(tokenizer.kind(), TokenizerKind::Standard);
+    }
+    
+    #[test]
+    fn test_whitespace_tokenizer() {
+        let tokenizer = WhitespaceTokenizer;
+        assert_eq!(
+            tokenizer.tokenize("  State-of-the-art Wi-Fi, (fast)!  "),
+            vec!["state-of-the-art", "wi-fi", "fast"]
+        );
+        assert_eq!(tokenizer.tokenize("--- ..."), Vec::<String>::new());
+        
+        let options = NormalizeOptions { fold_diacritics: true, ..Default::default() };
+        assert_eq!(tokenizer.tokenize_with("Über-café", &options), vec!["uber-cafe"]);
+        
+        let options = NormalizeOptions { case_sensitive: true, ..Default::default() };
+        assert_eq!(tokenizer.tokenize_with("Wi-Fi (PRO)", &options), vec!["Wi-Fi", "PRO"]);
+        
+        let options = NormalizeOptions { preserve_punctuation: PunctuationSet::new("+#"), ..Default::default() };
+        assert_eq!(tokenizer.tokenize_with("(C++), C#! + F#.", &options), vec!["c++", "c#", "f#"]);
+        assert_eq!(tokenizer.kind(), TokenizerKind::Whitespace);
+    }
+    
+    #[test]
+    fn test_kind_build() {
+        assert_eq!(TokenizerKind::Standard.build().kind(), TokenizerKind::Standard);
+        assert_eq!(TokenizerKind::Whitespace.build().kind(), TokenizerKind::Whitespace);
+        
+        // Custom tokenizers can't be rebuilt from a tag, but the tag survives
+        let placeholder = TokenizerKind::Custom.build();
+        assert_eq!(placeholder.kind(), TokenizerKind::Custom);
+        assert_eq!(placeholder.tokenize("Hello, World!"), vec!["hello", "world"]);
+    }
+}
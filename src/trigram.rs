@@ -1,4 +1,80 @@
 use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Options controlling how text is normalized before tokenization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct NormalizeOptions {
+    /// Fold accented characters to their base form, so "café" matches "cafe"
+    pub fold_diacritics: bool,
+    
+    /// Keep the original casing instead of lowercasing, so "Apple" doesn't match "apple"
+    pub case_sensitive: bool,
+    
+    /// Punctuation kept inside words instead of being stripped, so "c++" and
+    /// "c#" stay distinct from "c"
+    #[serde(default)]
+    pub preserve_punctuation: PunctuationSet,
+}
+
+/// A set of ASCII punctuation characters, serialized as a string of its characters
+///
+/// Characters other than ASCII punctuation are ignored when building a set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct PunctuationSet(u128);
+
+impl PunctuationSet {
+    /// Create a set of the ASCII punctuation characters in `chars`
+    pub fn new(chars: &str) -> Self {
+        chars.chars().collect()
+    }
+    
+    /// Check whether a character is in the set
+    pub fn contains(self, c: char) -> bool {
+        c.is_ascii_punctuation() && self.0 & (1 << c as u32) != 0
+    }
+    
+    /// Check whether the set is empty
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+    
+    /// Iterate over the characters in the set, in ASCII order
+    pub fn chars(self) -> impl Iterator<Item = char> {
+        (0u8..128).map(char::from).filter(move |&c| self.contains(c))
+    }
+}
+
+impl FromIterator<char> for PunctuationSet {
+    fn from_iter<I: IntoIterator<Item = char>>(chars: I) -> Self {
+        Self(chars.into_iter()
+            .filter(char::is_ascii_punctuation)
+            .fold(0, |bits, c| bits | 1 << c as u32))
+    }
+}
+
+impl From<&str> for PunctuationSet {
+    fn from(chars: &str) -> Self {
+        Self::new(chars)
+    }
+}
+
+impl From<String> for PunctuationSet {
+    fn from(chars: String) -> Self {
+        Self::new(&chars)
+    }
+}
+
+impl From<PunctuationSet> for String {
+    fn from(set: PunctuationSet) -> Self {
+        set.chars().collect()
+    }
+}
+
+/// Default n-gram size used for fuzzy matching
+pub const DEFAULT_NGRAM_SIZE: usize = 3;
 
 /// Generate trigrams from a string
 ///
@@ -6,23 +82,33 @@ use std::collections::HashSet;
 /// For example, the word "apple" generates trigrams: "$$a", "$ap", "app", "ppl", "ple", "le$"
 /// where $ represents the start/end of the word.
 pub fn generate_trigrams(text: &str) -> HashSet<String> {
-    let normalized = normalize_text(text);
+    generate_ngrams(text, DEFAULT_NGRAM_SIZE)
+}
+
+/// Generate n-grams of `n` characters from a string
+///
+/// The word is padded with `n - 1` start markers and one end marker, so for n=2
+/// "apple" generates: "$a", "ap", "pp", "pl", "le", "e$". Smaller sizes improve
+/// recall on short terms; larger sizes improve precision on long ones.
+pub fn generate_ngrams(text: &str, n: usize) -> HashSet<String> {
+    generate_ngrams_with(text, n, &NormalizeOptions::default())
+}
+
+/// Generate n-grams of `n` characters, normalizing with the given options
+pub fn generate_ngrams_with(text: &str, n: usize, options: &NormalizeOptions) -> HashSet<String> {
+    let normalized = normalize_text_with(text, options);
     
     if normalized.is_empty() {
         return HashSet::new();
     }
     
-    let padded = format!("$${normalized}$");
+    let n = n.max(1);
+    let padded = format!("{}{normalized}$", "$".repeat(n - 1));
     let chars: Vec<char> = padded.chars().collect();
     
-    let mut trigrams = HashSet::new();
-    
-    for i in 0..chars.len().saturating_sub(2) {
-        let trigram = format!("{}{}{}", chars[i], chars[i + 1], chars[i + 2]);
-        trigrams.insert(trigram);
-    }
-    
-    trigrams
+    chars.windows(n)
+        .map(|window| window.iter().collect())
+        .collect()
 }
 
 /// Normalize text for indexing and searching
@@ -32,12 +118,46 @@ pub fn generate_trigrams(text: &str) -> HashSet<String> {
 /// 2. Removes punctuation
 /// 3. Trims whitespace
 pub fn normalize_text(text: &str) -> String {
-    text.chars()
-        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
-        .collect::<String>()
-        .to_lowercase()
-        .trim()
-        .to_string()
+    normalize_text_with(text, &NormalizeOptions::default())
+}
+
+/// Normalize text with the given options
+///
+/// Like `normalize_text`, additionally folding diacritics when enabled,
+/// skipping lowercasing when case-sensitive, and keeping the preserved
+/// punctuation. Preserved punctuation is kept wherever it appears in a word,
+/// but words made up only of punctuation are dropped.
+pub fn normalize_text_with(text: &str, options: &NormalizeOptions) -> String {
+    let text = if options.fold_diacritics {
+        fold_diacritics(text)
+    } else {
+        text.to_string()
+    };
+    
+    let preserve = options.preserve_punctuation;
+    let mut stripped = text.chars()
+        .filter(|&c| c.is_alphanumeric() || c.is_whitespace() || preserve.contains(c))
+        .collect::<String>();
+    if !preserve.is_empty() {
+        stripped = stripped.split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    
+    if options.case_sensitive {
+        stripped.trim().to_string()
+    } else {
+        stripped.to_lowercase().trim().to_string()
+    }
+}
+
+/// Strip diacritics by decomposing to NFD and dropping combining marks
+///
+/// For example "Crème Brûlée" becomes "Creme Brulee". Characters without a
+/// decomposition (such as "ø" or "ß") are left unchanged.
+pub fn fold_diacritics(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect()
 }
 
 /// Extract tokens (words) from text
@@ -47,7 +167,12 @@ pub fn normalize_text(text: &str) -> String {
 /// 2. Splits it into words
 /// 3. Filters out empty tokens
 pub fn extract_tokens(text: &str) -> Vec<String> {
-    normalize_text(text)
+    extract_tokens_with(text, &NormalizeOptions::default())
+}
+
+/// Extract tokens from text, normalizing with the given options
+pub fn extract_tokens_with(text: &str, options: &NormalizeOptions) -> Vec<String> {
+    normalize_text_with(text, options)
         .split_whitespace()
         .map(|s| s.to_string())
         .collect()
@@ -95,4 +220,65 @@ mod tests {
         assert_eq!(generate_trigrams("a").len(), 2); // $$a, $a$
         assert_eq!(generate_trigrams("ab").len(), 3); // $$a, $ab, ab$
     }
+
+    #[test]
+    fn test_fold_diacritics() {
+        assert_eq!(fold_diacritics("café"), "cafe");
+        assert_eq!(fold_diacritics("Crème Brûlée"), "Creme Brulee");
+        assert_eq!(fold_diacritics("naïve jalapeño façade"), "naive jalapeno facade");
+        assert_eq!(fold_diacritics("Ångström Øresund"), "Angstrom Øresund");
+        
+        let options = NormalizeOptions { fold_diacritics: true, ..Default::default() };
+        assert_eq!(normalize_text_with("Café, Zürich!", &options), "cafe zurich");
+        assert_eq!(extract_tokens_with("Señor Dvořák", &options), vec!["senor", "dvorak"]);
+        
+        // Off by default
+        assert_eq!(normalize_text("Café"), "café");
+    }
+
+    #[test]
+    fn test_generate_ngrams() {
+        assert_eq!(generate_ngrams("apple", 3), generate_trigrams("apple"));
+        
+        let bigrams = generate_ngrams("apple", 2);
+        let expected: HashSet<String> = ["$a", "ap", "pp", "pl", "le", "e$"]
+            .iter().map(|s| s.to_string()).collect();
+        assert_eq!(bigrams, expected);
+        
+        let fourgrams = generate_ngrams("apple", 4);
+        let expected: HashSet<String> = ["$$$a", "$$ap", "$app", "appl", "pple", "ple$"]
+            .iter().map(|s| s.to_string()).collect();
+        assert_eq!(fourgrams, expected);
+        
+        // Short words still produce n-grams thanks to padding
+        assert_eq!(generate_ngrams("a", 4).len(), 2); // $$$a, $$a$
+        assert!(generate_ngrams("", 2).is_empty());
+    }
+
+    #[test]
+    fn test_preserve_punctuation() {
+        let set = PunctuationSet::new("+#.é ");
+        assert!(set.contains('+') && set.contains('#') && set.contains('.'));
+        assert!(!set.contains('-') && !set.contains('é'));
+        assert_eq!(String::from(set), "#+.");
+        assert_eq!(serde_json::to_string(&set).unwrap(), "\"#+.\"");
+        assert_eq!(serde_json::from_str::<PunctuationSet>("\"#+.\"").unwrap(), set);
+        
+        let options = NormalizeOptions { preserve_punctuation: set, ..Default::default() };
+        assert_eq!(extract_tokens_with("C++, C# and Node.js!", &options), vec!["c++", "c#", "and", "node.js"]);
+        assert_eq!(normalize_text_with("a + b", &options), "a b");
+        assert!(generate_ngrams_with("c++", 3, &options).contains("c++"));
+        
+        // Stripped by default
+        assert_eq!(extract_tokens("C++, C# and Node.js!"), vec!["c", "c", "and", "nodejs"]);
+    }
+
+    #[test]
+    fn test_case_sensitive() {
+        let options = NormalizeOptions { case_sensitive: true, ..Default::default() };
+        assert_eq!(normalize_text_with("  Apple, iPhone!  ", &options), "Apple iPhone");
+        assert_eq!(extract_tokens_with("Apple apple", &options), vec!["Apple", "apple"]);
+        assert!(generate_ngrams_with("Apple", 3, &options).contains("$$A"));
+        assert!(!generate_ngrams_with("Apple", 3, &options).contains("$$a"));
+    }
 }
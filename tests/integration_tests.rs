@@ -1,4 +1,4 @@
-use tiger_cache::{Document, TigerCache, SearchOptions, Result};
+use tiger_cache::{Document, TigerCache, SearchOptions, SharedTigerCache, Result};
 use tempfile::tempdir;
 
 #[test]
@@ -35,6 +35,7 @@ fn test_full_workflow() {
         max_distance: 1,
         score_threshold: 0,
         limit: 10,
+        ..Default::default()
     };
     
     let results = tiger_cache.search("Aple", Some(options)).unwrap();
@@ -159,6 +160,7 @@ fn test_search_options() {
         max_distance: 2,
         score_threshold: 0,
         limit: 1,
+        ..Default::default()
     };
     
     let results = tiger_cache.search("Document", Some(options)).unwrap();
@@ -169,6 +171,7 @@ fn test_search_options() {
         max_distance: 2,
         score_threshold: 900, // High threshold (0.9 * 1000)
         limit: 10,
+        ..Default::default()
     };
     
     let results = tiger_cache.search("Documant", Some(options)).unwrap(); // Typo
@@ -179,6 +182,7 @@ fn test_search_options() {
         max_distance: 0, // No typo tolerance
         score_threshold: 0,
         limit: 10,
+        ..Default::default()
     };
     
     let results = tiger_cache.search("Documant", Some(options)).unwrap(); // Typo
@@ -208,6 +212,7 @@ fn test_large_dataset() {
         max_distance: 2,
         score_threshold: 0,
         limit: 10,
+        ..Default::default()
     };
     
     let results = tiger_cache.search("document", Some(options)).unwrap();
@@ -270,3 +275,101 @@ fn test_concurrent_operations() {
         assert!(handle.join().unwrap().is_ok());
     }
 }
+
+#[test]
+fn test_shared_concurrent_reads_and_writes() {
+    use std::thread;
+    
+    let shared = SharedTigerCache::new(TigerCache::new());
+    let mut doc = Document::new("doc0");
+    doc.add_field("title", "Shared Document");
+    shared.add_document(doc).unwrap();
+    
+    // A single writer adds documents while readers search the same instance
+    let writer = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for i in 1..=20 {
+                let mut doc = Document::new(format!("doc{i}"));
+                doc.add_field("title", format!("Shared Document {i}"));
+                shared.add_document(doc).unwrap();
+            }
+        })
+    };
+    
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..20 {
+                    let results = shared.search("shared", None).unwrap();
+                    assert!(!results.is_empty());
+                    assert!(shared.get_document("doc0").is_some());
+                }
+            })
+        })
+        .collect();
+    
+    writer.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    
+    assert_eq!(shared.document_count(), 21);
+    assert_eq!(shared.search("shared", None).unwrap().len(), 21);
+}
+
+#[test]
+fn test_shared_concurrent_lazy_loads() {
+    use std::thread;
+    use tiger_cache::{StorageType, TigerCacheConfig};
+    
+    let dir = tempdir().unwrap();
+    let config = TigerCacheConfig::new()
+        .with_storage_type(StorageType::Memory)
+        .with_storage_path(dir.path().join("storage"))
+        .with_lazy_documents(true);
+    let shared = SharedTigerCache::new(TigerCache::with_config(config));
+    for i in 0..50 {
+        let mut doc = Document::new(format!("doc{i}"));
+        doc.add_field("title", format!("Lazy Document {i}"));
+        shared.add_document(doc).unwrap();
+    }
+    
+    // Readers load documents from storage into the index under the shared
+    // lock while a single writer keeps adding documents
+    let writer = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for i in 50..100 {
+                let mut doc = Document::new(format!("doc{i}"));
+                doc.add_field("title", format!("Lazy Document {i}"));
+                shared.add_document(doc).unwrap();
+            }
+        })
+    };
+    
+    let readers: Vec<_> = (0..8)
+        .map(|reader| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for round in 0..100 {
+                    let doc_id = format!("doc{}", (reader * 7 + round) % 50);
+                    let cache = shared.read();
+                    let doc = cache.get_document(&doc_id).unwrap();
+                    assert_eq!(doc.id, doc_id);
+                    drop(doc);
+                    assert!(!cache.search("lazy", None).unwrap().is_empty());
+                }
+            })
+        })
+        .collect();
+    
+    writer.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    
+    assert_eq!(shared.document_count(), 100);
+    assert_eq!(shared.read().get_document("doc99").unwrap().get_text_field("title").unwrap(), "Lazy Document 99");
+}